@@ -1,18 +1,27 @@
+use std::collections::LinkedList;
 use percent_encoding::percent_decode_str;
-use tauri::http::{Response, StatusCode};
+use http::{Response, StatusCode};
 use url::Url;
 
 use mdx::storage::{EntryNo, KeyIndex};
+use mdx::utils::MdxHtmlRewriter;
 
-use crate::error::{Result, ZdbError};
-use crate::mdict_app::{with_read_access, with_write_access};
-use crate::mdx_db::MdxIndex;
-use crate::mdx_profile::ProfileId;
+use crate::error::{invalid_argument, Result};
+use crate::html_text::escape_html;
+use crate::mdict_app::{with_lookup_context, with_lookup_morphology, with_profile_db, with_read_access};
+use crate::lookup_context::LookupContext;
+use crate::mdx_db::{MdxDb, MdxIndex};
+use crate::mdx_db_group::MdxGroupIndex;
+use crate::mdx_profile::{ProfileId, INVALID_PROFILE_ID};
 use crate::mdx_url_parser::MdxAction;
+use crate::session_mgr::SessionId;
+use crate::fts_query::FtsQuery;
+use crate::snippet;
 
 /// Action handler trait
 pub trait ActionHandler {
-    fn handle(&self, url: &Url, action: MdxAction) -> Result<Response<Vec<u8>>>;
+    /// `base_url` is the service URL the request came in on (custom scheme or local HTTP server)
+    fn handle(&self, url: &Url, action: MdxAction, base_url: &str) -> Result<Response<Vec<u8>>>;
 }
 
 /// 获取URL查询参数的辅助函数
@@ -22,7 +31,21 @@ fn get_param(url: &Url, key: &str) -> Result<String> {
             return Ok(v.to_string());
         }
     }
-    Err(ZdbError::invalid_parameter(format!("Parameter {} not found", key)))
+    Err(invalid_argument(format!("Parameter {} not found", key)))
+}
+
+/// `highlight` 参数中的查询词在页面中用 `<mark>` 标出，第一个可以用 `#mdict-first-match` 定位
+/// 全文搜索语法中被排除的词和运算符不标出
+fn highlight_terms(url: &Url, html: String) -> String {
+    match get_param(url, "highlight") {
+        Ok(query) => {
+            let terms = FtsQuery::parse(&query)
+                .map(|query| query.terms())
+                .unwrap_or_else(|_| snippet::query_terms(&query));
+            snippet::highlight_html(&html, &terms)
+        }
+        Err(_) => html,
+    }
 }
 
 /// 根据文件扩展名确定MIME类型
@@ -47,14 +70,25 @@ fn build_response(status_code: StatusCode, content_type: &str, data: Vec<u8>) ->
     response
 }
 
-/// 获取单个条目的HTML内容
-pub fn get_entry_html_by_index(index:&MdxIndex) -> Result<String> {
-    with_write_access(|app| app.get_entry_html_by_index(index))
+/// 词头未找到时的响应
+fn not_found_response(key: &str) -> Response<Vec<u8>> {
+    build_response(StatusCode::NOT_FOUND, "text/plain", format!("Not found: {}", key).into_bytes())
 }
 
-/// 获取MDD数据
+/// 获取单个条目的HTML内容，词典可以在任一会话中打开
+pub fn get_entry_html_by_index(index:&MdxIndex, base_url: &str) -> Result<String> {
+    with_profile_db(index.profile_id, |mdx_db| {
+        let index = mdx_db.get_index(index.key_index.entry_no)?;
+        mdx_db.get_html(&index, base_url)
+    })
+}
+
+/// 获取MDD数据，`/$MdxDictIcon` 返回词典图标
 pub fn get_mdd_data(profile_id: &ProfileId, filename: &str) -> Result<Option<(Vec<u8>, String)>> {
-    with_write_access(|app| app.get_mdd_data(profile_id, filename))
+    if filename == "/$MdxDictIcon" {
+        return with_read_access(|app| app.library_manager.get_icon_for_profile(*profile_id));
+    }
+    with_profile_db(*profile_id, |mdx_db| mdx_db.get_data(filename))
 }
 
 /// 获取asset文件内容（二进制）
@@ -66,7 +100,7 @@ pub fn get_asset(filename: &str) -> Result<Option<Vec<u8>>> {
 pub struct ResHandler;
 
 impl ActionHandler for ResHandler {
-    fn handle(&self, url: &Url, _action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, _action: MdxAction, _base_url: &str) -> Result<Response<Vec<u8>>> {
         let path_with_action = url.path().strip_prefix("/service/").unwrap_or(url.path());
         let action_str = path_with_action.split('/').next().unwrap_or(path_with_action);
         let real_path = path_with_action.strip_prefix(action_str).unwrap_or(path_with_action).to_string();
@@ -86,7 +120,7 @@ impl ActionHandler for ResHandler {
 pub struct EntryXHandler;
 
 impl ActionHandler for EntryXHandler {
-    fn handle(&self, url: &Url, _action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, _action: MdxAction, base_url: &str) -> Result<Response<Vec<u8>>> {
         let profile_id = get_param(url, "profile_id")?.parse::<ProfileId>()?;
         let entry_no = get_param(url, "entry_no")?.parse::<EntryNo>()?;
         
@@ -101,7 +135,7 @@ impl ActionHandler for EntryXHandler {
             },
         };
         
-        let html = get_entry_html_by_index(&mdx_index)?;
+        let html = highlight_terms(url, get_entry_html_by_index(&mdx_index, base_url)?);
         
         Ok(build_response(StatusCode::OK, "text/html; charset=utf-8", html.into_bytes()))
     }
@@ -111,7 +145,7 @@ impl ActionHandler for EntryXHandler {
 pub struct MddHandler;
 
 impl ActionHandler for MddHandler {
-    fn handle(&self, url: &Url, action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, action: MdxAction, _base_url: &str) -> Result<Response<Vec<u8>>> {
         let (profile_id, filename) = if action == MdxAction::File {
             // file:// URL
             let profile_id = 0; // 默认profile_id，可能需要调整
@@ -138,7 +172,7 @@ impl ActionHandler for MddHandler {
 pub struct IFrameHandler;
 
 impl ActionHandler for IFrameHandler {
-    fn handle(&self, url: &Url, _action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, _action: MdxAction, base_url: &str) -> Result<Response<Vec<u8>>> {
         let profile_id = get_param(url, "profile_id")?.parse::<ProfileId>()?;
         let entry_no = get_param(url, "entry_no")?.parse::<EntryNo>()?;
         
@@ -153,7 +187,7 @@ impl ActionHandler for IFrameHandler {
             },
         };
         
-        let html = get_entry_html_by_index(&mdx_index)?;
+        let html = get_entry_html_by_index(&mdx_index, base_url)?;
         
         Ok(build_response(StatusCode::OK, "text/html; charset=utf-8", html.into_bytes()))
     }
@@ -163,7 +197,7 @@ impl ActionHandler for IFrameHandler {
 pub struct DebugHandler;
 
 impl ActionHandler for DebugHandler {
-    fn handle(&self, url: &Url, action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, action: MdxAction, _base_url: &str) -> Result<Response<Vec<u8>>> {
         let message = get_param(url, "message").unwrap_or_else(|_| "No message".to_string());
         let decoded_message = percent_decode_str(&message).decode_utf8()?.to_string();
         
@@ -177,7 +211,7 @@ impl ActionHandler for DebugHandler {
 pub struct LaunchHandler;
 
 impl ActionHandler for LaunchHandler {
-    fn handle(&self, url: &Url, _action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, _action: MdxAction, _base_url: &str) -> Result<Response<Vec<u8>>> {
         let _x = get_param(url, "x")?.parse::<i32>()?;
         let _y = get_param(url, "y")?.parse::<i32>()?;
         let _width = get_param(url, "width")?.parse::<i32>()?;
@@ -197,7 +231,7 @@ impl ActionHandler for LaunchHandler {
 pub struct SoundHandler;
 
 impl ActionHandler for SoundHandler {
-    fn handle(&self, url: &Url, _action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, _action: MdxAction, _base_url: &str) -> Result<Response<Vec<u8>>> {
         let profile_id = get_param(url, "profile_id")?.parse::<ProfileId>()?;
         let key = get_param(url, "key")?;
         let decoded_key = percent_decode_str(&key).decode_utf8()?.to_string();
@@ -217,7 +251,7 @@ impl ActionHandler for SoundHandler {
 pub struct ViewImageHandler;
 
 impl ActionHandler for ViewImageHandler {
-    fn handle(&self, url: &Url, _action: MdxAction) -> Result<Response<Vec<u8>>> {
+    fn handle(&self, url: &Url, _action: MdxAction, _base_url: &str) -> Result<Response<Vec<u8>>> {
         let path_with_action = url.path().strip_prefix("/service/").unwrap_or(url.path());
         let action_str = path_with_action.split('/').next().unwrap_or(path_with_action);
         let real_path = path_with_action.strip_prefix(action_str).unwrap_or(path_with_action).to_string();
@@ -233,6 +267,205 @@ impl ActionHandler for ViewImageHandler {
     }
 }
 
+/// 合并页面的样式
+const UNION_PAGE_STYLE: &str = "\
+.mdict-union-nav{display:flex;flex-wrap:wrap;gap:.5em;margin-bottom:.5em}\
+.mdict-union-nav a{text-decoration:none}\
+.mdict-dict>summary{cursor:pointer;font-weight:bold;padding:.25em 0;border-bottom:1px solid #ccc}\
+.mdict-dict-icon{width:1.2em;height:1.2em;vertical-align:middle;margin-right:.4em}\
+.mdict-dict-error{color:#c00}\
+@media print{.mdict-union-nav{display:none}}";
+
+/// 合并页面所需的词典和词条内容，实际来自查询上下文
+trait UnionSource {
+    /// 词典组中词典的顺序，单个词典时为空
+    fn profile_order(&self) -> Vec<ProfileId>;
+    fn dictionary_title(&self, profile_id: ProfileId) -> String;
+    fn entry_html(&self, index: &MdxIndex, base_url: &str) -> Result<String>;
+}
+
+impl UnionSource for LookupContext {
+    fn profile_order(&self) -> Vec<ProfileId> {
+        self.db.profile().get_profiles()
+            .map(|profiles| profiles.iter().map(|p| p.profile_id).collect())
+            .unwrap_or_default()
+    }
+
+    fn dictionary_title(&self, profile_id: ProfileId) -> String {
+        self.db.find_profile(profile_id).map(|p| p.title.clone()).unwrap_or_default()
+    }
+
+    fn entry_html(&self, index: &MdxIndex, base_url: &str) -> Result<String> {
+        self.get_entry_html_by_index(index, base_url)
+    }
+}
+
+/// 渲染合并页面：每个词典一个可折叠的分节，按词典组中的顺序排列，带词典标题、图标和锚点 (#dict-{profile_id})
+/// 某个词典的词条读取失败时，该分节显示错误说明，其他词典照常显示
+fn render_union_page(source: &impl UnionSource, title: &str, group_indexes: LinkedList<MdxGroupIndex>, base_url: &str) -> Result<String> {
+    let mut group_indexes: Vec<MdxGroupIndex> = group_indexes.into_iter().collect();
+    let order = source.profile_order();
+    group_indexes.sort_by_key(|g| order.iter().position(|id| *id == g.profile_id).unwrap_or(usize::MAX));
+
+    let mut nav = String::new();
+    let mut sections = String::new();
+    for group_index in group_indexes.iter() {
+        let profile_id = group_index.profile_id;
+        let dict_title = escape_html(&source.dictionary_title(profile_id));
+        nav.push_str(&format!("<a href=\"#dict-{}\">{}</a>\n", profile_id, dict_title));
+        sections.push_str(&format!(
+            "<details open class=\"mdict-dict\" id=\"dict-{id}\">\n<summary class=\"mdict-dict-title\"><img class=\"mdict-dict-icon\" src=\"{base}mdd?profile_id={id}&amp;key=/$MdxDictIcon\" alt=\"\">{title}</summary>\n<div class=\"mdict-dict-content\">\n",
+            id = profile_id, base = base_url, title = dict_title));
+        for index in group_index.indexes.iter() {
+            match source.entry_html(index, base_url) {
+                Ok(html) => sections.push_str(&html),
+                Err(e) => {
+                    log::warn!("Failed to read {} in profile {}: {}", index.key_index.key, profile_id, e);
+                    sections.push_str(&format!("<p class=\"mdict-dict-error\">{}</p>\n", escape_html(&e.to_string())));
+                    break;
+                }
+            }
+            sections.push('\n');
+        }
+        sections.push_str("</div>\n</details>\n");
+    }
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<nav class=\"mdict-union-nav\">\n{}</nav>\n{}</body>\n</html>\n",
+        escape_html(title), UNION_PAGE_STYLE, nav, sections))
+}
+
+/// 合并页面处理器 (Union)，把词典组查询结果中的一项渲染成一个页面
+pub struct UnionHandler;
+
+impl ActionHandler for UnionHandler {
+    fn handle(&self, url: &Url, _action: MdxAction, base_url: &str) -> Result<Response<Vec<u8>>> {
+        let index_no = get_param(url, "index_no")?.parse::<usize>()?;
+        let session_id = get_param(url, "session_id").ok().map(|id| id.parse::<SessionId>()).transpose()?;
+
+        let html = with_lookup_context(session_id, |context| {
+            let group_indexes = context.get_group_indexes(index_no)?;
+            let title = group_indexes.front().map(|g| g.primary_key.clone()).unwrap_or_default();
+            render_union_page(context, &title, group_indexes, base_url)
+        })?;
+        let html = highlight_terms(url, html);
+
+        Ok(build_response(StatusCode::OK, "text/html; charset=utf-8", html.into_bytes()))
+    }
+}
+
+/// 词头链接处理器查找词条的来源，实际使用已打开的词典
+trait EntrySource {
+    /// 在指定词典中查找词头，词典没有打开时返回错误
+    fn find_in_profile(&self, profile_id: ProfileId, key: &str) -> Result<Option<MdxIndex>>;
+    /// 在会话打开的词典或词典组中查找词头
+    fn find_in_session(&self, session_id: Option<SessionId>, key: &str) -> Result<Option<MdxIndex>>;
+    /// 词条保存的内容，不跟随 @@@LINK= 跳转
+    fn stored_html(&self, index: &MdxIndex) -> Result<String>;
+    /// 把内容中的资源链接改写到 `base_url` 下
+    fn rewrite_html(&self, profile_id: ProfileId, html: &str, base_url: &str) -> Result<String>;
+}
+
+/// 已打开的词典
+struct OpenDictionaries;
+
+impl EntrySource for OpenDictionaries {
+    fn find_in_profile(&self, profile_id: ProfileId, key: &str) -> Result<Option<MdxIndex>> {
+        with_profile_db(profile_id, |mdx_db| mdx_db.find_index(key, false, false, true))
+    }
+
+    fn find_in_session(&self, session_id: Option<SessionId>, key: &str) -> Result<Option<MdxIndex>> {
+        with_lookup_context(session_id, |context| {
+            Ok(context.find_index(key)?
+                .into_iter()
+                .next()
+                .and_then(|group_index| group_index.indexes.into_iter().next()))
+        })
+    }
+
+    fn stored_html(&self, index: &MdxIndex) -> Result<String> {
+        with_profile_db(index.profile_id, |mdx_db| mdx_db.get_stored_html(index))
+    }
+
+    fn rewrite_html(&self, profile_id: ProfileId, html: &str, base_url: &str) -> Result<String> {
+        MdxHtmlRewriter::rewrite_html_with_base_url(html, profile_id, base_url)
+    }
+}
+
+/// 词头链接处理器 (Entry, ProgEntry, HProgEntry)，处理词典内容中 entry://key 形式的交叉引用
+/// 先在链接所属的词典中查找，找不到时在会话打开的词典或词典组中查找
+pub struct EntryHandler;
+
+impl EntryHandler {
+    fn find_entry(source: &impl EntrySource, profile_id: ProfileId, session_id: Option<SessionId>, key: &str) -> Result<Option<MdxIndex>> {
+        if profile_id != INVALID_PROFILE_ID {
+            match source.find_in_profile(profile_id, key) {
+                Ok(Some(index)) => return Ok(Some(index)),
+                Ok(None) => {}
+                // The owning dictionary is not open in any session, search the session instead
+                Err(e) => log::debug!("Entry link {} not resolved in profile {}: {}", key, profile_id, e),
+            }
+        }
+        source.find_in_session(session_id, key)
+    }
+
+    fn render(source: &impl EntrySource, url: &Url, base_url: &str) -> Result<Response<Vec<u8>>> {
+        let profile_id = match get_param(url, "profile_id") {
+            Ok(profile_id) => profile_id.parse::<ProfileId>()?,
+            Err(_) => INVALID_PROFILE_ID,
+        };
+        let key = get_param(url, "key")?;
+        let key = percent_decode_str(&key).decode_utf8()?.to_string();
+        let session_id = get_param(url, "session_id").ok().map(|id| id.parse::<SessionId>()).transpose()?;
+
+        let Some(index) = Self::find_entry(source, profile_id, session_id, &key)? else {
+            return Ok(not_found_response(&key));
+        };
+        // Redirect targets are in the same dictionary as the redirect
+        let (resolved, html) = MdxDb::follow_links(
+            &index,
+            |entry| source.stored_html(entry),
+            |target| source.find_in_profile(index.profile_id, target),
+        )?;
+        let html = highlight_terms(url, source.rewrite_html(resolved.profile_id, &html, base_url)?);
+
+        Ok(build_response(StatusCode::OK, "text/html; charset=utf-8", html.into_bytes()))
+    }
+}
+
+impl ActionHandler for EntryHandler {
+    fn handle(&self, url: &Url, _action: MdxAction, base_url: &str) -> Result<Response<Vec<u8>>> {
+        Self::render(&OpenDictionaries, url, base_url)
+    }
+}
+
+/// 按词头查询处理器 (Lookup)，渲染当前词典或词典组中所有匹配的词条
+/// 查不到时按词形还原得到的词干查询，标题显示为 "word → stem"
+pub struct LookupHandler;
+
+impl ActionHandler for LookupHandler {
+    fn handle(&self, url: &Url, _action: MdxAction, base_url: &str) -> Result<Response<Vec<u8>>> {
+        let word = get_param(url, "word")?;
+        let session_id = get_param(url, "session_id").ok().map(|id| id.parse::<SessionId>()).transpose()?;
+
+        let html = with_lookup_morphology(session_id, |context, morphology| {
+            let (group_indexes, stem) = context.find_index_or_stem(&word, morphology)?;
+            if group_indexes.is_empty() {
+                return Ok(None);
+            }
+            let title = match stem {
+                Some(stem) => MdxDb::redirect_display_key(&word, &stem),
+                None => word.clone(),
+            };
+            render_union_page(context, &title, group_indexes, base_url).map(Some)
+        })?;
+
+        match html {
+            Some(html) => Ok(build_response(StatusCode::OK, "text/html; charset=utf-8", html.into_bytes())),
+            None => Ok(not_found_response(&word)),
+        }
+    }
+}
+
 
 /// 获取action handler
 pub fn get_action_handler(action: MdxAction) -> Option<Box<dyn ActionHandler + Send + Sync>> {
@@ -240,11 +473,169 @@ pub fn get_action_handler(action: MdxAction) -> Option<Box<dyn ActionHandler + S
         MdxAction::Res | MdxAction::EncryptedRes => Some(Box::new(ResHandler)),
         MdxAction::ViewImage => Some(Box::new(ViewImageHandler)),
         MdxAction::Sound => Some(Box::new(SoundHandler)),
+        MdxAction::Entry | MdxAction::ProgEntry | MdxAction::HProgEntry => Some(Box::new(EntryHandler)),
         MdxAction::EntryX | MdxAction::ProgEntryX | MdxAction::HProgEntryX => Some(Box::new(EntryXHandler)),
         MdxAction::Mdd | MdxAction::File => Some(Box::new(MddHandler)),
         MdxAction::IFrame => Some(Box::new(IFrameHandler)),
         MdxAction::Debug | MdxAction::Notify | MdxAction::Info => Some(Box::new(DebugHandler)),
         MdxAction::Launch => Some(Box::new(LaunchHandler)),
+        MdxAction::Lookup => Some(Box::new(LookupHandler)),
+        MdxAction::Union => Some(Box::new(UnionHandler)),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::entry_not_found;
+
+    /// Dictionaries 1 and 2, dictionary 2 is the one open in the session, dictionary 3 is not open
+    struct TestDictionaries {
+        entries: Vec<(ProfileId, &'static str, &'static str)>,
+    }
+
+    impl TestDictionaries {
+        fn new() -> Self {
+            Self {
+                entries: vec![
+                    (1, "color", "<b>color</b> in dictionary 1"),
+                    (1, "colour", "@@@LINK=color"),
+                    (1, "loop", "@@@LINK=loop2"),
+                    (1, "loop2", "@@@LINK=loop"),
+                    (1, "grey", "@@@LINK=gray"),
+                    (2, "hue", "<b>hue</b> in dictionary 2"),
+                ],
+            }
+        }
+
+        fn find(&self, profile_id: ProfileId, key: &str) -> Option<MdxIndex> {
+            self.entries.iter().position(|entry| entry.0 == profile_id && entry.1 == key).map(|entry_no| MdxIndex {
+                profile_id,
+                key_index: KeyIndex { entry_no: entry_no as EntryNo, key: key.to_string(), ..Default::default() },
+            })
+        }
+    }
+
+    impl EntrySource for TestDictionaries {
+        fn find_in_profile(&self, profile_id: ProfileId, key: &str) -> Result<Option<MdxIndex>> {
+            if profile_id == 3 {
+                return Err(invalid_argument("Profile 3 is not open"));
+            }
+            Ok(self.find(profile_id, key))
+        }
+
+        fn find_in_session(&self, _session_id: Option<SessionId>, key: &str) -> Result<Option<MdxIndex>> {
+            Ok(self.find(2, key))
+        }
+
+        fn stored_html(&self, index: &MdxIndex) -> Result<String> {
+            self.entries.get(index.key_index.entry_no as usize)
+                .map(|entry| entry.2.to_string())
+                .ok_or_else(|| entry_not_found(&index.key_index.key))
+        }
+
+        fn rewrite_html(&self, _profile_id: ProfileId, html: &str, _base_url: &str) -> Result<String> {
+            Ok(html.to_string())
+        }
+    }
+
+    fn render(query: &str) -> Result<(StatusCode, String)> {
+        let url = Url::parse(&format!("mdx://mdict.cn/service/entry?{}", query)).unwrap();
+        let response = EntryHandler::render(&TestDictionaries::new(), &url, "mdx://mdict.cn/service/")?;
+        Ok((response.status(), String::from_utf8(response.into_body()).unwrap()))
+    }
+
+    #[test]
+    fn test_entry_key_resolution() {
+        // The dictionary of the link first, then the session
+        assert_eq!(render("profile_id=1&key=color").unwrap(), (StatusCode::OK, "<b>color</b> in dictionary 1".to_string()));
+        assert_eq!(render("profile_id=1&key=hue").unwrap(), (StatusCode::OK, "<b>hue</b> in dictionary 2".to_string()));
+        assert_eq!(render("profile_id=3&key=hue").unwrap().1, "<b>hue</b> in dictionary 2");
+        assert_eq!(render("key=hue").unwrap().1, "<b>hue</b> in dictionary 2");
+        // Keys are percent-decoded
+        assert_eq!(render("profile_id=1&key=%63olor").unwrap().1, "<b>color</b> in dictionary 1");
+    }
+
+    #[test]
+    fn test_entry_links() {
+        assert_eq!(render("profile_id=1&key=colour").unwrap(), (StatusCode::OK, "<b>color</b> in dictionary 1".to_string()));
+        // A loop or a dangling target shows the last link instead of failing the page
+        assert_eq!(render("profile_id=1&key=loop").unwrap(), (StatusCode::OK, "@@@LINK=loop".to_string()));
+        assert_eq!(render("profile_id=1&key=grey").unwrap(), (StatusCode::OK, "@@@LINK=gray".to_string()));
+    }
+
+    #[test]
+    fn test_entry_not_found() {
+        assert_eq!(render("profile_id=1&key=red").unwrap(), (StatusCode::NOT_FOUND, "Not found: red".to_string()));
+        assert!(render("profile_id=1").is_err());
+    }
+
+    /// A group of the dictionaries 2 and 1 in this order
+    struct TestGroup;
+
+    impl UnionSource for TestGroup {
+        fn profile_order(&self) -> Vec<ProfileId> {
+            vec![2, 1]
+        }
+
+        fn dictionary_title(&self, profile_id: ProfileId) -> String {
+            match profile_id {
+                1 => "Oxford <Advanced>".to_string(),
+                _ => "Collins & Co".to_string(),
+            }
+        }
+
+        fn entry_html(&self, index: &MdxIndex, _base_url: &str) -> Result<String> {
+            if index.profile_id == 3 {
+                return Err(invalid_argument("Dictionary 3 <is> not open"));
+            }
+            Ok(format!("<p>{} {}</p>", index.profile_id, index.key_index.key))
+        }
+    }
+
+    fn group_index(profile_id: ProfileId, keys: &[&str]) -> MdxGroupIndex {
+        MdxGroupIndex {
+            profile_id,
+            primary_key: keys[0].to_string(),
+            indexes: keys.iter().enumerate().map(|(entry_no, key)| MdxIndex {
+                profile_id,
+                key_index: KeyIndex { entry_no: entry_no as EntryNo, key: key.to_string(), ..Default::default() },
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_union_page() {
+        let group_indexes = LinkedList::from([group_index(1, &["colour", "Colour"]), group_index(2, &["colour"])]);
+        let html = render_union_page(&TestGroup, "colour <b>", group_indexes, "mdx://mdict.cn/service/").unwrap();
+
+        assert!(html.contains("<title>colour &lt;b&gt;</title>"), "{}", html);
+        // Navigation and sections follow the group's order, not the order of the results
+        let positions = |needles: &[&str]| -> Vec<usize> { needles.iter().map(|needle| html.find(needle).unwrap()).collect() };
+        let nav = positions(&["<a href=\"#dict-2\">Collins &amp; Co</a>", "<a href=\"#dict-1\">Oxford &lt;Advanced&gt;</a>"]);
+        let sections = positions(&["id=\"dict-2\"", "<p>2 colour</p>", "id=\"dict-1\"", "<p>1 colour</p>", "<p>1 Colour</p>"]);
+        assert!(nav.is_sorted() && sections.is_sorted() && nav[1] < sections[0], "{}", html);
+        assert!(html.contains(">Oxford &lt;Advanced&gt;</summary>"), "{}", html);
+        assert!(html.contains("src=\"mdx://mdict.cn/service/mdd?profile_id=1&amp;key=/$MdxDictIcon\""), "{}", html);
+        assert_eq!(html.matches("<details open class=\"mdict-dict\"").count(), 2);
+    }
+
+    #[test]
+    fn test_union_page_error() {
+        let group_indexes = LinkedList::from([group_index(3, &["colour", "Colour"]), group_index(1, &["colour"])]);
+        let html = render_union_page(&TestGroup, "colour", group_indexes, "mdx://mdict.cn/service/").unwrap();
+
+        // The failing dictionary gets a note, the others are shown
+        assert_eq!(html.matches("<p class=\"mdict-dict-error\">").count(), 1, "{}", html);
+        assert!(html.contains("Dictionary 3 &lt;is&gt; not open"), "{}", html);
+        assert!(html.contains("<p>1 colour</p>"), "{}", html);
+    }
+
+    #[test]
+    fn test_entry_highlight() {
+        let (_, html) = render("profile_id=1&key=colour&highlight=color").unwrap();
+        assert!(html.contains("<mark"), "{}", html);
+        assert!(html.contains(">color</mark>"), "{}", html);
+    }
+}
@@ -0,0 +1,296 @@
+// API types module - versioned JSON data transfer objects for external applications
+// These types are the public contract of the JSON API (HTTP server and CLI):
+// fields may be added, but renaming or removing one requires a new API version
+use serde::{Deserialize, Serialize};
+
+use mdx::storage::EntryNo;
+
+use crate::error::ErrorCode;
+use crate::mdx_db::MdxIndex;
+use crate::mdx_db_group::MdxGroupIndex;
+use crate::mdx_profile::{MdxProfile, ProfileId};
+
+/// Current API version, also the path prefix of the HTTP endpoints (/api/v1/...)
+pub const API_VERSION: &str = "v1";
+
+/// Format of entry content in API responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    #[default]
+    Html,
+    Text,
+}
+
+impl ContentFormat {
+    pub fn from_str(s: &str) -> Option<ContentFormat> {
+        match s {
+            "html" => Some(ContentFormat::Html),
+            "text" => Some(ContentFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Reference to a single entry of a dictionary
+/// `resolved_key` is set when the entry is an `@@@LINK=` redirect to another key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryRef {
+    pub profile_id: ProfileId,
+    pub entry_no: EntryNo,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_key: Option<String>,
+}
+
+impl From<&MdxIndex> for EntryRef {
+    fn from(index: &MdxIndex) -> Self {
+        Self {
+            profile_id: index.profile_id,
+            entry_no: index.key_index.entry_no,
+            key: index.key_index.key.clone(),
+            resolved_key: None,
+        }
+    }
+}
+
+/// Entries of one dictionary for a headword, mirrors `MdxGroupIndex`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupEntry {
+    pub profile_id: ProfileId,
+    pub primary_key: String,
+    pub indexes: Vec<EntryRef>,
+}
+
+impl From<&MdxGroupIndex> for GroupEntry {
+    fn from(group_index: &MdxGroupIndex) -> Self {
+        Self {
+            profile_id: group_index.profile_id,
+            primary_key: group_index.primary_key.clone(),
+            indexes: group_index.indexes.iter().map(EntryRef::from).collect(),
+        }
+    }
+}
+
+/// Entry with its content, redirects are followed and `resolved_key` is the key of the content shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryContent {
+    pub profile_id: ProfileId,
+    pub dictionary: String,
+    pub entry_no: EntryNo,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_key: Option<String>,
+    pub content: String,
+}
+
+/// Response of a headword lookup with entry content
+/// `stem` is set when the query was not found and its stem (Hunspell morphology) was looked up instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookupResponse {
+    pub api_version: String,
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stem: Option<String>,
+    pub format: ContentFormat,
+    pub entries: Vec<EntryContent>,
+}
+
+/// Response of an index lookup, one `GroupEntry` per dictionary, `stem` as in `LookupResponse`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexResponse {
+    pub api_version: String,
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stem: Option<String>,
+    pub results: Vec<GroupEntry>,
+}
+
+/// Plain text of an entry around the matched terms of a full-text query
+/// `matches` are [start, end) offsets of the matched terms in characters (Unicode scalar values) of `text`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    pub matches: Vec<(usize, usize)>,
+}
+
+/// Full-text search hit, higher scores rank first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FtsHit {
+    pub score: f32,
+    pub profile_id: ProfileId,
+    pub entry_no: EntryNo,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+}
+
+/// Response of a full-text search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FtsResponse {
+    pub api_version: String,
+    pub query: String,
+    pub hits: Vec<FtsHit>,
+}
+
+/// Spelling suggestion, a lower distance is a closer match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub key: String,
+    pub distance: f32,
+    pub profile_ids: Vec<ProfileId>,
+}
+
+/// Response of a spelling suggestion request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestResponse {
+    pub api_version: String,
+    pub query: String,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// How a result of a combined search matched, see `LookupContext::combined_search`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// Headword next to where the query would be, no headword starts with the query
+    Nearest,
+    /// The query was found in the entry text
+    FullText,
+}
+
+/// Key of the cached result list, `count` is the number of dictionaries having the key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultKey {
+    pub key: String,
+    pub count: usize,
+    /// Relevance of a full-text result, the best hit of a dictionary scores the dictionary's weight (1.0 by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// Text of the first entry around the matched terms of a full-text result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+    /// How the result of a combined search matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_kind: Option<MatchKind>,
+}
+
+/// Key list item, `count` is the number of dictionaries having the key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyListItem {
+    pub index_no: usize,
+    pub key: String,
+    pub count: usize,
+    /// Relevance of a full-text result, see `ResultKey`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_kind: Option<MatchKind>,
+}
+
+/// One page of the key list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyListResponse {
+    pub api_version: String,
+    pub start: usize,
+    pub total: usize,
+    /// The key list of a group continues past `total`
+    #[serde(default)]
+    pub has_more: bool,
+    pub items: Vec<KeyListItem>,
+}
+
+/// Dictionary or group in the library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileInfo {
+    pub profile_id: ProfileId,
+    pub title: String,
+    pub description: String,
+    pub disabled: bool,
+    pub is_group: bool,
+    pub fts_enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<ProfileInfo>,
+}
+
+impl From<&MdxProfile> for ProfileInfo {
+    fn from(profile: &MdxProfile) -> Self {
+        Self {
+            profile_id: profile.profile_id,
+            title: profile.title.clone(),
+            description: profile.description.clone(),
+            disabled: profile.disabled,
+            is_group: profile.is_group(),
+            fts_enabled: profile.is_fts_enabled(),
+            profiles: profile.get_profiles()
+                .map(|profiles| profiles.iter().map(ProfileInfo::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Library listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryResponse {
+    pub api_version: String,
+    pub current_profile_id: ProfileId,
+    pub groups: Vec<ProfileInfo>,
+}
+
+/// Error body returned with non-2xx status codes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub api_version: String,
+    /// Stable code of the error, the same as the `code` of Tauri command errors
+    pub code: ErrorCode,
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::LinkedList;
+    use mdx::storage::KeyIndex;
+    use super::*;
+
+    #[test]
+    fn test_group_entry_contract() {
+        let mut indexes = LinkedList::new();
+        indexes.push_back(MdxIndex {
+            profile_id: 3,
+            key_index: KeyIndex { entry_no: 42, key: "colour".to_string(), ..Default::default() },
+        });
+        let group_index = MdxGroupIndex { profile_id: 3, primary_key: "colour".to_string(), indexes };
+
+        let json = serde_json::to_value(GroupEntry::from(&group_index)).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "profile_id": 3,
+            "primary_key": "colour",
+            "indexes": [{ "profile_id": 3, "entry_no": 42, "key": "colour" }]
+        }));
+    }
+
+    #[test]
+    fn test_content_format() {
+        assert_eq!(ContentFormat::from_str("text"), Some(ContentFormat::Text));
+        assert_eq!(ContentFormat::from_str("xml"), None);
+        assert_eq!(serde_json::to_value(ContentFormat::Html).unwrap(), "html");
+    }
+
+    #[test]
+    fn test_result_key_contract() {
+        let result_key = ResultKey {
+            key: "colour".to_string(),
+            count: 2,
+            score: None,
+            snippet: None,
+            match_kind: Some(MatchKind::FullText),
+        };
+        assert_eq!(serde_json::to_value(&result_key).unwrap(), serde_json::json!({
+            "key": "colour",
+            "count": 2,
+            "match_kind": "full_text"
+        }));
+    }
+}
@@ -33,6 +33,11 @@ pub enum ConfigKey {
     HotkeyModifier,
     UsePopoverForLookup,
     LastMainProfileId,
+    HttpServerEnabled,
+    HttpServerPort,
+    HttpServerAllowedOrigins,
+    HunspellDictPath,
+    ChineseConversion,
     
     // 视图设置键
     GuiLanguage,
@@ -60,6 +65,11 @@ impl ConfigKey {
             ConfigKey::HotkeyModifier => "hotkey_modifier",
             ConfigKey::UsePopoverForLookup => "use_popover_for_lookup",
             ConfigKey::LastMainProfileId => "last_main_profile_id",
+            ConfigKey::HttpServerEnabled => "http_server_enabled",
+            ConfigKey::HttpServerPort => "http_server_port",
+            ConfigKey::HttpServerAllowedOrigins => "http_server_allowed_origins",
+            ConfigKey::HunspellDictPath => "hunspell_dict_path",
+            ConfigKey::ChineseConversion => "chinese_conversion",
             
             // 视图设置
             ConfigKey::AppearanceMode => "appearance_mode",
@@ -86,6 +96,11 @@ impl ConfigKey {
             "hotkey_modifier" => Some(ConfigKey::HotkeyModifier),
             "use_popover_for_lookup" => Some(ConfigKey::UsePopoverForLookup),
             "last_main_profile_id" => Some(ConfigKey::LastMainProfileId),
+            "http_server_enabled" => Some(ConfigKey::HttpServerEnabled),
+            "http_server_port" => Some(ConfigKey::HttpServerPort),
+            "http_server_allowed_origins" => Some(ConfigKey::HttpServerAllowedOrigins),
+            "hunspell_dict_path" => Some(ConfigKey::HunspellDictPath),
+            "chinese_conversion" => Some(ConfigKey::ChineseConversion),
             
             // 视图设置
             "appearance_mode" => Some(ConfigKey::AppearanceMode),
@@ -137,7 +152,12 @@ impl Default for AppConfig {
             "use_popover_for_lookup": true,
             "use_tts": true,
             "tts_engine_id": "",
-            "extra_lib_search_path": ""
+            "extra_lib_search_path": "",
+            "http_server_enabled": false,
+            "http_server_port": 8765,
+            "http_server_allowed_origins": [],
+            "hunspell_dict_path": "",
+            "chinese_conversion": true
         }"#;
 
         // 使用JSON字符串定义默认的视图设置
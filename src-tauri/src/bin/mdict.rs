@@ -0,0 +1,390 @@
+// Command line interface for lookups, full-text search and library maintenance
+// Runs on the same data directory as the GUI (doc/library.json, doc/config.json, doc/mdict.db)
+use std::path::PathBuf;
+use std::sync::Arc;
+use humantime::format_rfc3339;
+use log::LevelFilter;
+use serde_json::Value;
+
+use mdict_app_lib::api_types::ContentFormat;
+use mdict_app_lib::app_config::{ConfigSection, ConfigKey};
+use mdict_app_lib::conversion;
+use mdict_app_lib::error::{invalid_argument, profile_not_found, Result, ZdbError};
+use mdict_app_lib::event_sink::EventSink;
+use mdict_app_lib::html_text::{escape_html, html_to_text};
+use mdict_app_lib::http_server;
+use mdict_app_lib::json_api;
+use mdict_app_lib::mdict_app::{self, MdictApp};
+use mdict_app_lib::mdx_db::MdxDb;
+use mdict_app_lib::mdx_db_group::MdxGroupIndex;
+use mdict_app_lib::mdx_profile::ProfileId;
+
+/// Tauri app identifier, the GUI stores its data under `<data dir>/<identifier>`
+const APP_IDENTIFIER: &str = "cn.mdict.mdict-app";
+
+const USAGE: &str = "Usage: mdict [options] <command> [arguments]
+
+Commands:
+  lookup <word> [--profile N]   Look up a headword in a dictionary or group
+  fts <query> [--profile N]     Full-text search
+  list-groups                   List dictionary groups and their dictionaries
+  refresh                       Rescan the library search paths
+  convert <profile> [--locale L] [--remove-old]
+                                Convert a dictionary to the new format
+  index <profile>               Build the full-text index of a dictionary
+  serve [--port N] [--profile N]
+                                Serve entries over HTTP on 127.0.0.1 (default port from config.json)
+
+Options:
+  --data-dir DIR                Data directory (default: the GUI's data directory, or $MDICT_DATA_DIR)
+  --format text|html|json       Output format (default: text)
+  --max-results N               Maximum results per dictionary for fts (default: 200)
+  --verbose                     Log debug messages to stderr
+  --help                        Show this help";
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Html,
+    Json,
+}
+
+/// Parsed command line: positional arguments and options
+struct CliArgs {
+    positional: Vec<String>,
+    data_dir: Option<PathBuf>,
+    format: OutputFormat,
+    profile_id: Option<ProfileId>,
+    locale: String,
+    remove_old: bool,
+    max_results: usize,
+    port: Option<u16>,
+    verbose: bool,
+    help: bool,
+}
+
+impl CliArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> std::result::Result<Self, String> {
+        let mut cli_args = CliArgs {
+            positional: Vec::new(),
+            data_dir: std::env::var_os("MDICT_DATA_DIR").map(PathBuf::from),
+            format: OutputFormat::Text,
+            profile_id: None,
+            locale: String::new(),
+            remove_old: false,
+            max_results: 200,
+            port: None,
+            verbose: false,
+            help: false,
+        };
+        while let Some(arg) = args.next() {
+            let mut value_of = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}", name));
+            match arg.as_str() {
+                "--data-dir" => cli_args.data_dir = Some(PathBuf::from(value_of("--data-dir")?)),
+                "--format" => {
+                    cli_args.format = match value_of("--format")?.as_str() {
+                        "text" => OutputFormat::Text,
+                        "html" => OutputFormat::Html,
+                        "json" => OutputFormat::Json,
+                        other => return Err(format!("Invalid format: {}", other)),
+                    }
+                }
+                "--profile" => {
+                    let value = value_of("--profile")?;
+                    cli_args.profile_id = Some(value.parse().map_err(|_| format!("Invalid profile id: {}", value))?);
+                }
+                "--locale" => cli_args.locale = value_of("--locale")?,
+                "--max-results" => {
+                    let value = value_of("--max-results")?;
+                    cli_args.max_results = value.parse().map_err(|_| format!("Invalid number: {}", value))?;
+                }
+                "--port" => {
+                    let value = value_of("--port")?;
+                    cli_args.port = Some(value.parse().map_err(|_| format!("Invalid port: {}", value))?);
+                }
+                "--remove-old" => cli_args.remove_old = true,
+                "--verbose" | "-v" => cli_args.verbose = true,
+                "--help" | "-h" => cli_args.help = true,
+                _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+                _ => cli_args.positional.push(arg),
+            }
+        }
+        Ok(cli_args)
+    }
+
+    /// Get the n-th positional argument (after the command name)
+    fn arg(&self, n: usize, name: &str) -> Result<&str> {
+        self.positional.get(n + 1)
+            .map(|s| s.as_str())
+            .ok_or_else(|| invalid_argument(format!("Missing argument: <{}>", name)))
+    }
+
+    fn profile_arg(&self) -> Result<ProfileId> {
+        let value = self.arg(0, "profile")?;
+        value.parse::<ProfileId>()
+            .map_err(|_| invalid_argument(format!("Invalid profile id: {}", value)))
+    }
+}
+
+/// Event sink printing conversion progress to stderr
+struct ProgressEventSink;
+
+impl EventSink for ProgressEventSink {
+    fn emit(&self, event: &str, payload: Value) -> Result<()> {
+        if event == "conversion-progress" {
+            eprintln!(
+                "[{}:{}] {}/{}",
+                payload["stage"].as_str().unwrap_or(""),
+                payload["subStage"].as_str().unwrap_or(""),
+                payload["current"],
+                payload["total"],
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Default data directory, same as Tauri's app_data_dir for this app
+fn default_data_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from).unwrap_or(home)
+    } else if cfg!(target_os = "macos") {
+        home.join("Library/Application Support")
+    } else {
+        std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).unwrap_or_else(|| home.join(".local/share"))
+    };
+    base.join(APP_IDENTIFIER)
+}
+
+fn setup_logging(verbose: bool) {
+    let level = if verbose { LevelFilter::Debug } else { LevelFilter::Warn };
+    let _ = fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{} {} {}] {}",
+                format_rfc3339(std::time::SystemTime::now()),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .level(level)
+        .chain(std::io::stderr())
+        .apply();
+}
+
+/// Assets (CSS/JS used by entry pages) are looked up next to the executable
+fn assets_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("assets")))
+}
+
+fn open_app(args: &CliArgs) -> Result<MdictApp> {
+    let data_dir = args.data_dir.clone().unwrap_or_else(default_data_dir);
+    let mut app = MdictApp::init_app_with_home_dir(&data_dir, Vec::new(), assets_dir(), Arc::new(ProgressEventSink))?;
+    if let Some(profile_id) = args.profile_id {
+        app.open_main_db(profile_id)?;
+    }
+    Ok(app)
+}
+
+fn profile_title(app: &MdictApp, profile_id: ProfileId) -> String {
+    app.library_manager.find_profile(profile_id)
+        .map(|p| p.title.clone())
+        .unwrap_or_else(|| profile_id.to_string())
+}
+
+/// Print the entries of a list of group indexes
+fn print_entries(app: &MdictApp, group_indexes: &[MdxGroupIndex], format: OutputFormat) -> Result<()> {
+    let context = app.lookup_context(None)?;
+    for group_index in group_indexes {
+        for index in group_index.indexes.iter() {
+            let (resolved, html) = context.get_resolved_entry(index, app.get_base_url())?;
+            if format == OutputFormat::Html {
+                println!("{}", html);
+            } else {
+                let key = if resolved.key_index.entry_no == index.key_index.entry_no {
+                    index.key_index.key.clone()
+                } else {
+                    MdxDb::redirect_display_key(&index.key_index.key, &resolved.key_index.key)
+                };
+                println!("== {} : {} ==", profile_title(app, index.profile_id), key);
+                println!("{}\n", html_to_text(&html));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn cmd_lookup(args: &CliArgs) -> Result<()> {
+    let word = args.arg(0, "word")?;
+    let app = open_app(args)?;
+    let context = app.lookup_context(None)?;
+    if args.format == OutputFormat::Json {
+        return print_json(&json_api::lookup(&context, &app.morphology(), word, ContentFormat::Html, app.get_base_url())?);
+    }
+    let (group_indexes, stem) = context.find_index_or_stem(word, &app.morphology())?;
+    let group_indexes: Vec<MdxGroupIndex> = group_indexes.into_iter().collect();
+    if group_indexes.is_empty() {
+        return Err(ZdbError::invalid_parameter(format!("Not found: {}", word)));
+    }
+    if let Some(stem) = stem {
+        println!("{}\n", MdxDb::redirect_display_key(word, &stem));
+    }
+    print_entries(&app, &group_indexes, args.format)
+}
+
+fn cmd_fts(args: &CliArgs) -> Result<()> {
+    let query = args.arg(0, "query")?;
+    let context = open_app(args)?.lookup_context(None)?;
+    if args.format == OutputFormat::Json {
+        return print_json(&json_api::fulltext_search(&context, query, args.max_results)?);
+    }
+    let total = context.fulltext_search(query, args.max_results)?;
+    if total == 0 {
+        return Ok(());
+    }
+    let keys = context.get_result_key_list(0, total)?;
+    if args.format == OutputFormat::Html {
+        println!("<ol>");
+        for result_key in keys {
+            println!("<li>{} ({})</li>", escape_html(&result_key.key), result_key.count);
+        }
+        println!("</ol>");
+    } else {
+        for result_key in keys {
+            println!("{}\t{}", result_key.key, result_key.count);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_list_groups(args: &CliArgs) -> Result<()> {
+    let app = open_app(args)?;
+    let groups = app.library_manager.get_groups();
+    match args.format {
+        OutputFormat::Json => print_json(&json_api::library(&app)?)?,
+        OutputFormat::Html => {
+            println!("<ul>");
+            for group in groups.iter() {
+                println!("<li>{} {}<ul>", group.profile_id, group.title);
+                for profile in group.get_profiles().into_iter().flatten() {
+                    println!("<li>{} {}</li>", profile.profile_id, profile.title);
+                }
+                println!("</ul></li>");
+            }
+            println!("</ul>");
+        }
+        OutputFormat::Text => {
+            for group in groups.iter() {
+                println!("{}\t{}", group.profile_id, group.title);
+                for profile in group.get_profiles().into_iter().flatten() {
+                    let disabled = if profile.disabled { " [disabled]" } else { "" };
+                    println!("  {}\t{}{}", profile.profile_id, profile.title, disabled);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_refresh(args: &CliArgs) -> Result<()> {
+    let mut app = open_app(args)?;
+    let search_paths = app.get_lib_search_paths()?.to_vec();
+    app.library_manager.refresh_library(&search_paths)?;
+    app.library_manager.save_library()?;
+    let count = app.library_manager.get_groups().front()
+        .and_then(|group| group.get_profiles())
+        .map(|profiles| profiles.len())
+        .unwrap_or(0);
+    println!("Library refreshed: {} dictionaries", count);
+    Ok(())
+}
+
+fn cmd_convert(args: &CliArgs) -> Result<()> {
+    let profile_id = args.profile_arg()?;
+    let app = open_app(args)?;
+    let profile = app.library_manager.find_profile(profile_id)
+        .ok_or_else(|| profile_not_found(profile_id))?
+        .clone();
+    let result = conversion::convert_db(&profile, &args.locale, args.remove_old, Arc::new(ProgressEventSink))?;
+    println!("{}", result.new_mdx_path);
+    if let Some(mdd_path) = result.new_mdd_path {
+        println!("{}", mdd_path);
+    }
+    Ok(())
+}
+
+fn cmd_index(args: &CliArgs) -> Result<()> {
+    let profile_id = args.profile_arg()?;
+    let app = open_app(args)?;
+    let profile = app.library_manager.find_profile(profile_id)
+        .ok_or_else(|| profile_not_found(profile_id))?;
+    let mdx_path = url::Url::parse(&profile.url)?
+        .to_file_path()
+        .map_err(|_| ZdbError::invalid_parameter(format!("Cannot convert URL to file path: {}", profile.url)))?;
+    conversion::create_fts_index(&mdx_path, Arc::new(ProgressEventSink))
+}
+
+fn cmd_serve(args: &CliArgs) -> Result<()> {
+    // The service actions work on the global app instance
+    let data_dir = args.data_dir.clone().unwrap_or_else(default_data_dir);
+    mdict_app::init_mdict_app(&data_dir, Vec::new(), assets_dir(), Arc::new(ProgressEventSink))?;
+    if let Some(profile_id) = args.profile_id {
+        mdict_app::with_write_access(|app| app.open_main_db(profile_id))?;
+    }
+    let port = match args.port {
+        Some(port) => port,
+        None => mdict_app::with_config_read(|config| {
+            Ok(config.get_config_with_default(ConfigSection::Global, ConfigKey::HttpServerPort, 0u16))
+        })?,
+    };
+
+    let base_url = http_server::start_http_server(port)?;
+    println!("Serving on {}", base_url);
+    println!("Look up words at {}lookup?word=", base_url.trim_end_matches("service/"));
+    loop {
+        std::thread::park();
+    }
+}
+
+fn main() {
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+    setup_logging(args.verbose);
+
+    let command = if args.help { Some("help") } else { args.positional.first().map(|s| s.as_str()) };
+    let result = match command {
+        Some("lookup") => cmd_lookup(&args),
+        Some("fts") => cmd_fts(&args),
+        Some("list-groups") => cmd_list_groups(&args),
+        Some("refresh") => cmd_refresh(&args),
+        Some("convert") => cmd_convert(&args),
+        Some("index") => cmd_index(&args),
+        Some("serve") => cmd_serve(&args),
+        Some("help") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => {
+            eprintln!("Unknown command: {}\n\n{}", command, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
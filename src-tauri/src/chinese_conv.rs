@@ -0,0 +1,211 @@
+// Chinese conversion module - Simplified/Traditional Chinese conversion for lookups
+// Tables use the OpenCC format ("简<TAB>簡 alternatives..."), phrases are matched longest first
+// before single characters. The bundled tables are OpenCC's STCharacters.txt/STPhrases.txt
+// (Apache-2.0, see opencc/NOTICE), other tables can be put in the opencc directory of the data directory
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::Result;
+
+const BUNDLED_CHARACTERS: &str = include_str!("opencc/STCharacters.txt");
+const BUNDLED_PHRASES: &str = include_str!("opencc/STPhrases.txt");
+pub const CHARACTERS_FILE: &str = "STCharacters.txt";
+pub const PHRASES_FILE: &str = "STPhrases.txt";
+
+/// Simplified/Traditional conversion of lookups, owned by the app and shared with the databases it opens
+/// Turned on and off by the `chinese_conversion` setting
+#[derive(Debug)]
+pub struct ChineseConversion {
+    enabled: AtomicBool,
+    converter: ChineseConverter,
+}
+
+impl ChineseConversion {
+    pub fn new(converter: ChineseConverter, enabled: bool) -> Self {
+        Self { enabled: AtomicBool::new(enabled), converter }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The converter, None when conversion is turned off
+    pub fn converter(&self) -> Option<&ChineseConverter> {
+        self.is_enabled().then_some(&self.converter)
+    }
+}
+
+/// Whether a character is a CJK ideograph
+pub fn is_han(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{2A6DF}')
+}
+
+/// Conversion in one direction
+#[derive(Debug, Default)]
+struct Table {
+    chars: HashMap<char, char>,
+    phrases: HashMap<String, String>,
+    max_phrase_len: usize,
+}
+
+impl Table {
+    fn add_phrase(&mut self, from: &str, to: &str) {
+        self.max_phrase_len = self.max_phrase_len.max(from.chars().count());
+        self.phrases.entry(from.to_string()).or_insert_with(|| to.to_string());
+    }
+
+    fn convert_char(&self, c: char) -> char {
+        self.chars.get(&c).copied().unwrap_or(c)
+    }
+
+    fn convert(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len());
+        let mut pos = 0;
+        while pos < chars.len() {
+            let max_len = self.max_phrase_len.min(chars.len() - pos);
+            let phrase = (2..=max_len).rev().find_map(|len| {
+                let phrase: String = chars[pos..pos + len].iter().collect();
+                self.phrases.get(&phrase).map(|converted| (len, converted))
+            });
+            match phrase {
+                Some((len, converted)) => {
+                    result.push_str(converted);
+                    pos += len;
+                }
+                None => {
+                    result.push(self.convert_char(chars[pos]));
+                    pos += 1;
+                }
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ChineseConverter {
+    to_traditional: Table,
+    to_simplified: Table,
+}
+
+impl ChineseConverter {
+    /// Converter with the tables built into the app
+    pub fn bundled() -> Self {
+        Self::from_tables(BUNDLED_CHARACTERS, BUNDLED_PHRASES)
+    }
+
+    /// Use the tables in `dir` when it has them, the bundled tables otherwise
+    pub fn load(dir: &Path) -> Result<Self> {
+        let characters_path = dir.join(CHARACTERS_FILE);
+        if !characters_path.exists() {
+            return Ok(Self::bundled());
+        }
+        let characters = std::fs::read_to_string(&characters_path)?;
+        let phrases = std::fs::read_to_string(dir.join(PHRASES_FILE)).unwrap_or_default();
+        log::info!("Loaded Chinese conversion tables from {:?}", dir);
+        Ok(Self::from_tables(&characters, &phrases))
+    }
+
+    /// Build from OpenCC simplified-to-traditional tables, the first alternative is the default
+    /// conversion, the traditional-to-simplified direction maps every alternative back
+    pub fn from_tables(characters: &str, phrases: &str) -> Self {
+        let mut converter = Self::default();
+        for (simplified, alternatives) in Self::parse(characters) {
+            let mut simplified_chars = simplified.chars();
+            let (Some(s), None) = (simplified_chars.next(), simplified_chars.next()) else { continue };
+            for (i, traditional) in alternatives.iter().enumerate() {
+                let mut traditional_chars = traditional.chars();
+                let (Some(t), None) = (traditional_chars.next(), traditional_chars.next()) else { continue };
+                if i == 0 {
+                    converter.to_traditional.chars.insert(s, t);
+                }
+                converter.to_simplified.chars.entry(t).or_insert(s);
+            }
+        }
+        for (simplified, alternatives) in Self::parse(phrases) {
+            for (i, traditional) in alternatives.iter().enumerate() {
+                if i == 0 {
+                    converter.to_traditional.add_phrase(simplified, traditional);
+                }
+                converter.to_simplified.add_phrase(traditional, simplified);
+            }
+        }
+        converter
+    }
+
+    fn parse(table: &str) -> impl Iterator<Item = (&str, Vec<&str>)> {
+        table.lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(from, to)| (from.trim(), to.split_whitespace().collect()))
+    }
+
+    pub fn to_traditional(&self, text: &str) -> String {
+        self.to_traditional.convert(text)
+    }
+
+    pub fn to_simplified(&self, text: &str) -> String {
+        self.to_simplified.convert(text)
+    }
+
+    pub fn to_simplified_char(&self, c: char) -> char {
+        self.to_simplified.convert_char(c)
+    }
+
+    /// Forms of `text` in the other script, empty when nothing in it converts
+    pub fn variants(&self, text: &str) -> Vec<String> {
+        let mut variants = Vec::new();
+        if !text.chars().any(is_han) {
+            return variants;
+        }
+        for form in [self.to_simplified(text), self.to_traditional(text)] {
+            if form != text && !variants.contains(&form) {
+                variants.push(form);
+            }
+        }
+        variants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_tables() {
+        let converter = ChineseConverter::bundled();
+        assert_eq!(converter.to_traditional("发展"), "發展");
+        assert_eq!(converter.to_traditional("头发"), "頭髮");
+        assert_eq!(converter.to_traditional("皇后来了"), "皇后來了");
+        assert_eq!(converter.to_simplified("後來"), "后来");
+        assert_eq!(converter.to_simplified("頭髮"), "头发");
+        assert_eq!(converter.to_simplified("鬱鬱蔥蔥"), "郁郁葱葱");
+        assert_eq!(converter.to_traditional("㐹"), "㑶");
+        assert_eq!(converter.variants("学习"), vec!["學習"]);
+        assert_eq!(converter.variants("學習"), vec!["学习"]);
+        assert!(converter.variants("hello").is_empty());
+    }
+
+    #[test]
+    fn test_custom_tables() {
+        let converter = ChineseConverter::from_tables("# comment\n面\t面 麵\n条\t條\n", "面条\t麵條\n");
+        assert_eq!(converter.to_traditional("面条"), "麵條");
+        assert_eq!(converter.to_traditional("面"), "面");
+        assert_eq!(converter.to_simplified("麵條"), "面条");
+        assert_eq!(converter.to_simplified_char('麵'), '面');
+    }
+
+    #[test]
+    fn test_conversion_setting() {
+        let conversion = ChineseConversion::new(ChineseConverter::from_tables("条\t條\n", ""), true);
+        assert_eq!(conversion.converter().map(|converter| converter.to_traditional("条")).as_deref(), Some("條"));
+        conversion.set_enabled(false);
+        assert!(conversion.converter().is_none());
+    }
+}
@@ -3,13 +3,13 @@ use serde_json::Value;
 use tauri::command;
 
 use crate::app_config::{AppConfig, ConfigSection, ConfigKey};
-use crate::error::IntoStringResult;
+use crate::error::{invalid_argument, CommandResult, IntoCommandResult};
 use crate::mdict_app::{with_config_read, with_config_write};
 
 /// Get app configuration
 #[command]
-pub async fn config_get_app_config() -> std::result::Result<AppConfig, String> {
-    with_config_read(|config| Ok(config.clone())).into_string_result()
+pub async fn config_get_app_config() -> CommandResult<AppConfig> {
+    with_config_read(|config| Ok(config.clone())).into_command_result()
 }
 
 // ============ Generic Config Commands ============
@@ -20,19 +20,19 @@ pub async fn config_get_app_config() -> std::result::Result<AppConfig, String> {
 /// * `section` - "global" or "view"
 /// * `key` - config key string
 #[command]
-pub async fn config_get_value(section: String, key: String) -> std::result::Result<Value, String> {
+pub async fn config_get_value(section: String, key: String) -> CommandResult<Value> {
     with_config_read(|config| {
         let config_section = match section.as_str() {
             "global" => ConfigSection::Global,
             "view" => ConfigSection::View,
-            _ => return Err(crate::error::ZdbError::invalid_parameter(format!("Invalid section: {}", section))),
+            _ => return Err(invalid_argument(format!("Invalid section: {}", section))),
         };
         
         let config_key = ConfigKey::from_str(&key)
-            .ok_or_else(|| crate::error::ZdbError::invalid_parameter(format!("Invalid config key: {}", key)))?;
+            .ok_or_else(|| invalid_argument(format!("Invalid config key: {}", key)))?;
         
         config.get_config::<Value>(config_section, config_key)
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Generic set config value
@@ -42,48 +42,68 @@ pub async fn config_get_value(section: String, key: String) -> std::result::Resu
 /// * `key` - config key string
 /// * `value` - JSON value to set
 #[command]
-pub async fn config_set_value(section: String, key: String, value: Value) -> std::result::Result<(), String> {
+pub async fn config_set_value(section: String, key: String, value: Value) -> CommandResult<()> {
+    // Simplified/Traditional conversion of lookups, checked before the value is saved
+    let chinese_conversion = if key == ConfigKey::ChineseConversion.as_str() {
+        let enabled = value.as_bool()
+            .ok_or_else(|| invalid_argument(format!("Invalid value for {}: {}", key, value)));
+        Some(enabled.into_command_result()?)
+    } else {
+        None
+    };
     with_config_write(|config| {
         let config_section = match section.as_str() {
             "global" => ConfigSection::Global,
             "view" => ConfigSection::View,
-            _ => return Err(crate::error::ZdbError::invalid_parameter(format!("Invalid section: {}", section))),
+            _ => return Err(invalid_argument(format!("Invalid section: {}", section))),
         };
         
         let config_key = ConfigKey::from_str(&key)
-            .ok_or_else(|| crate::error::ZdbError::invalid_parameter(format!("Invalid config key: {}", key)))?;
+            .ok_or_else(|| invalid_argument(format!("Invalid config key: {}", key)))?;
         
         config.set_config(config_section, config_key, value)?;
         config.save()
-    }).into_string_result()
+    }).into_command_result()?;
+
+    // The Hunspell dictionaries are loaded from the configured directory
+    if key == ConfigKey::HunspellDictPath.as_str() {
+        crate::mdict_app::with_write_access(|app| app.reload_morphology()).into_command_result()?;
+    }
+    if let Some(enabled) = chinese_conversion {
+        crate::mdict_app::with_read_access(|app| {
+            app.set_chinese_conversion(enabled);
+            Ok(())
+        }).into_command_result()?;
+    }
+    Ok(())
 }
 
 /// Get all global settings
 #[command]
-pub async fn config_get_global_settings() -> std::result::Result<Value, String> {
-    with_config_read(|config| Ok(config.global_settings.clone())).into_string_result()
+pub async fn config_get_global_settings() -> CommandResult<Value> {
+    with_config_read(|config| Ok(config.global_settings.clone())).into_command_result()
 }
 
 /// Get all view settings
 #[command]
-pub async fn config_get_view_settings() -> std::result::Result<Value, String> {
-    with_config_read(|config| Ok(config.view_settings.clone())).into_string_result()
+pub async fn config_get_view_settings() -> CommandResult<Value> {
+    with_config_read(|config| Ok(config.view_settings.clone())).into_command_result()
 }
 
 /// Update multiple view settings at once
 #[command]
-pub async fn config_update_view_settings(settings: Value) -> std::result::Result<(), String> {
+pub async fn config_update_view_settings(settings: Value) -> CommandResult<()> {
     with_config_write(|config| {
         config.view_settings = settings;
         config.save()
-    }).into_string_result()
+    }).into_command_result()
 }
 
 // ============ Utility Commands ============
 
 /// List font files in a directory
 #[command]
-pub async fn config_list_fonts_in_directory(path: String) -> std::result::Result<Vec<String>, String> {
+pub async fn config_list_fonts_in_directory(path: String) -> CommandResult<Vec<String>> {
     use std::fs;
     
     let mut fonts = Vec::new();
@@ -108,20 +128,20 @@ pub async fn config_list_fonts_in_directory(path: String) -> std::result::Result
 
 /// Save app configuration to disk
 #[command]
-pub async fn config_save_app_config() -> std::result::Result<(), String> {
-    with_config_read(|config| config.save()).into_string_result()
+pub async fn config_save_app_config() -> CommandResult<()> {
+    with_config_read(|config| config.save()).into_command_result()
 }
 
 /// Reload app configuration from disk
 #[command]
-pub async fn config_reload_app_config() -> std::result::Result<(), String> {
-    with_config_write(|config| config.reload()).into_string_result()
+pub async fn config_reload_app_config() -> CommandResult<()> {
+    with_config_write(|config| config.reload()).into_command_result()
 }
 
 /// Reload resources
 #[command]
-pub async fn config_reload_resources() -> std::result::Result<(), String> {
-    crate::mdict_app::with_write_access(|app| app.reload_resources()).into_string_result()
+pub async fn config_reload_resources() -> CommandResult<()> {
+    crate::mdict_app::with_write_access(|app| app.reload_resources()).into_command_result()
 }
 
 
@@ -1,17 +1,240 @@
 // Re-export ZdbError for convenience
 pub use mdx::{ZdbError, Result};
+use serde::{Deserialize, Serialize};
 
 // Type alias for backward compatibility
 pub type MdictAppError = ZdbError;
 
-/// Helper trait for converting Result<T> to Result<T, String>
-/// Used by Tauri commands to convert errors to strings
-pub trait IntoStringResult<T> {
-    fn into_string_result(self) -> std::result::Result<T, String>;
+// Fixed message prefixes of the errors the frontend reacts on, the code of an error is never
+// taken from its message, see `AppError`
+const NO_DATABASE_OPENED: &str = "No database opened";
+const PROFILE_NOT_FOUND: &str = "Profile not found";
+const SESSION_NOT_FOUND: &str = "Session not found";
+const CANCELLED: &str = "Operation cancelled";
+const ENTRY_NOT_FOUND: &str = "Entry not found";
+const INVALID_QUERY: &str = "Invalid full-text query";
+
+/// An error of the app with its code, carried as the source of a ZdbError
+/// `ErrorCode::of` finds it in the source chain, so the code doesn't depend on the wording of the message
+#[derive(Debug)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
 }
 
-impl<T> IntoStringResult<T> for Result<T> {
-    fn into_string_result(self) -> std::result::Result<T, String> {
-        self.map_err(|e| crate::error_printer::format_error(&e))
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
     }
-}
\ No newline at end of file
+}
+
+impl std::error::Error for AppError {}
+
+/// A ZdbError with a code, ZdbError can only wrap foreign errors as I/O errors
+fn app_error(code: ErrorCode, message: String) -> ZdbError {
+    ZdbError::from(std::io::Error::other(AppError { code, message }))
+}
+
+/// No dictionary or group is open in the session
+pub fn no_database_opened() -> ZdbError {
+    app_error(ErrorCode::NoDatabaseOpen, NO_DATABASE_OPENED.to_string())
+}
+
+/// A dictionary or group is not in the library
+pub fn profile_not_found(profile_id: impl std::fmt::Display) -> ZdbError {
+    app_error(ErrorCode::ProfileNotFound, format!("{}: {}", PROFILE_NOT_FOUND, profile_id))
+}
+
+/// A headword (e.g. the target of a redirect) is not in the dictionary
+pub fn entry_not_found(key: &str) -> ZdbError {
+    app_error(ErrorCode::NotFound, format!("{}: {}", ENTRY_NOT_FOUND, key))
+}
+
+/// A full-text query can't be parsed, `position` is the 1-based character position of the problem
+pub fn invalid_query(position: usize, reason: &str) -> ZdbError {
+    app_error(ErrorCode::InvalidQuery, format!("{} at character {}: {}", INVALID_QUERY, position, reason))
+}
+
+/// A lookup session doesn't exist
+pub fn session_not_found(session_id: impl std::fmt::Display) -> ZdbError {
+    app_error(ErrorCode::SessionNotFound, format!("{}: {}", SESSION_NOT_FOUND, session_id))
+}
+
+/// A long running operation was cancelled by the user
+pub fn cancelled(operation: &str) -> ZdbError {
+    app_error(ErrorCode::Cancelled, cancelled_message(operation))
+}
+
+/// Message used by progress reporters when the user cancels
+pub fn cancelled_message(operation: &str) -> String {
+    format!("{}: {}", CANCELLED, operation)
+}
+
+/// A parameter given by the caller is not valid: a bad pattern, an index out of range, an unknown id
+pub fn invalid_argument(message: impl Into<String>) -> ZdbError {
+    app_error(ErrorCode::InvalidArgument, message.into())
+}
+
+/// Stable error codes of command errors, the frontend decides what to do from the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Entry or resource not found
+    NotFound,
+    NoDatabaseOpen,
+    ProfileNotFound,
+    SessionNotFound,
+    /// Syntax error in a full-text query, the details tell where
+    InvalidQuery,
+    /// A parameter of the request is not valid
+    InvalidArgument,
+    /// File system or network error
+    Io,
+    Cancelled,
+    Internal,
+}
+
+impl ErrorCode {
+    /// User-facing message, also the translation key in the frontend
+    pub fn message(&self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::NoDatabaseOpen => "No dictionary is open",
+            ErrorCode::ProfileNotFound => "Dictionary not found",
+            ErrorCode::SessionNotFound => "Lookup session not found",
+            ErrorCode::InvalidQuery => "Invalid search query",
+            ErrorCode::InvalidArgument => "Invalid argument",
+            ErrorCode::Io => "Failed to read or write a file",
+            ErrorCode::Cancelled => "Operation cancelled",
+            ErrorCode::Internal => "An unexpected error occurred",
+        }
+    }
+
+    /// Code of an error: from the `AppError` in its source chain, otherwise from its kind
+    pub fn of(error: &ZdbError) -> ErrorCode {
+        if let ZdbError::KeyNotFound { .. } = error {
+            return ErrorCode::NotFound;
+        }
+        if let Some(app_error) = AppError::find(error) {
+            return app_error.code;
+        }
+        let mut source = std::error::Error::source(error);
+        while let Some(e) = source {
+            if e.is::<std::io::Error>() {
+                return ErrorCode::Io;
+            }
+            source = e.source();
+        }
+        ErrorCode::Internal
+    }
+}
+
+impl AppError {
+    /// The app error a ZdbError was made from
+    pub fn find(error: &ZdbError) -> Option<&AppError> {
+        let mut source = std::error::Error::source(error);
+        while let Some(e) = source {
+            if let Some(app_error) = e.downcast_ref::<AppError>() {
+                return Some(app_error);
+            }
+            if let Some(app_error) = e.downcast_ref::<std::io::Error>()
+                .and_then(|io_error| io_error.get_ref())
+                .and_then(|inner| inner.downcast_ref::<AppError>())
+            {
+                return Some(app_error);
+            }
+            source = e.source();
+        }
+        None
+    }
+}
+
+/// Error returned by Tauri commands, serialized as `{code, message, details}`
+/// The message has no colors or backtrace, those are only written to the log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, details: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: code.message().to_string(),
+            details: Some(details.into()),
+        }
+    }
+}
+
+impl From<&ZdbError> for CommandError {
+    fn from(error: &ZdbError) -> Self {
+        // The message of an app error without the wrapping of ZdbError
+        let details = AppError::find(error).map_or_else(|| error.to_string(), |app_error| app_error.message.clone());
+        CommandError::new(ErrorCode::of(error), details)
+    }
+}
+
+impl From<ZdbError> for CommandError {
+    fn from(error: ZdbError) -> Self {
+        CommandError::from(&error)
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.details {
+            Some(details) => write!(f, "{}: {}", self.message, details),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Result type of Tauri commands
+pub type CommandResult<T> = std::result::Result<T, CommandError>;
+
+/// Helper trait for converting Result<T> to CommandResult<T>
+/// Used by Tauri commands, the full error with backtrace is logged
+pub trait IntoCommandResult<T> {
+    fn into_command_result(self) -> CommandResult<T>;
+}
+
+impl<T> IntoCommandResult<T> for Result<T> {
+    fn into_command_result(self) -> CommandResult<T> {
+        self.map_err(|e| {
+            log::error!("{}", crate::error_printer::format_error(&e));
+            CommandError::from(&e)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(CommandError::from(no_database_opened()).code, ErrorCode::NoDatabaseOpen);
+        assert_eq!(CommandError::from(profile_not_found(7)).code, ErrorCode::ProfileNotFound);
+        assert_eq!(CommandError::from(session_not_found(2)).code, ErrorCode::SessionNotFound);
+        assert_eq!(CommandError::from(cancelled("Conversion")).code, ErrorCode::Cancelled);
+        assert_eq!(CommandError::from(entry_not_found("color")).code, ErrorCode::NotFound);
+        assert_eq!(CommandError::from(invalid_query(4, "unmatched )")).code, ErrorCode::InvalidQuery);
+        assert_eq!(CommandError::from(invalid_argument("Index out of range: 9 >= 3")).code, ErrorCode::InvalidArgument);
+        // The code comes from the error, not from words in its message
+        let worded = ZdbError::invalid_data_format(format!("MDX conversion failed: {}", cancelled_message("Conversion")));
+        assert_eq!(CommandError::from(worded).code, ErrorCode::Internal);
+        assert_eq!(CommandError::from(ZdbError::invalid_parameter("bad".to_string())).code, ErrorCode::Internal);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let error = CommandError::from(no_database_opened());
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "no_database_open");
+        assert_eq!(json["message"], "No dictionary is open");
+        assert_eq!(json["details"], "No database opened");
+    }
+}
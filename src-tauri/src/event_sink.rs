@@ -0,0 +1,45 @@
+// Event sink module - decouples the app core from the Tauri event system
+use serde_json::Value;
+
+use crate::error::Result;
+
+/// Receiver for events emitted by the app core (navigation, progress reports, ...)
+///
+/// The GUI forwards events to the webview through `TauriEventSink`, while headless
+/// users (CLI, servers, tests) can plug in their own implementation.
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &str, payload: Value) -> Result<()>;
+}
+
+/// Event sink that only logs events, used when no frontend is attached
+#[derive(Default)]
+pub struct LogEventSink;
+
+impl EventSink for LogEventSink {
+    fn emit(&self, event: &str, payload: Value) -> Result<()> {
+        log::debug!("Event {}: {}", event, payload);
+        Ok(())
+    }
+}
+
+/// Event sink that forwards events to the Tauri frontend
+#[cfg(feature = "gui")]
+pub struct TauriEventSink {
+    app_handle: tauri::AppHandle,
+}
+
+#[cfg(feature = "gui")]
+impl TauriEventSink {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl EventSink for TauriEventSink {
+    fn emit(&self, event: &str, payload: Value) -> Result<()> {
+        use tauri::Emitter;
+        self.app_handle.emit(event, payload)
+            .map_err(|e| crate::error::ZdbError::invalid_data_format(format!("Failed to emit {} event: {}", event, e)))
+    }
+}
@@ -0,0 +1,508 @@
+// FTS query module - query language of full-text search
+//
+//   colour tint              both words (AND is implied)
+//   colour OR color          either word
+//   colour NOT paint         `-paint` is the same as `NOT paint`
+//   "light blue"             phrase, the words in this order
+//   red NEAR/3 blue          at most 3 words between them, in any order (NEAR alone allows 10)
+//   headword:colour          only in the headword, `body:` only in the entry text
+//   headword:(red OR blue)   a field applies to a whole group
+//
+// NOT binds tighter than NEAR, NEAR than AND and AND than OR; the operators are uppercase,
+// in lowercase they are words. A word matches words starting with it ("colour" matches "colours"),
+// Chinese and Japanese characters are words of their own.
+// The full-text index only finds candidate entries, the query is evaluated on each of them
+// by `FtsQuery::matches`, so all dictionaries of a group apply the same rules
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::error::{invalid_query, Result};
+use crate::snippet;
+
+/// Words allowed between the sides of NEAR without a distance
+const DEFAULT_NEAR_DISTANCE: usize = 10;
+
+/// Part of an entry a query term is searched in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Any,
+    Headword,
+    Body,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FtsQuery {
+    /// Words following each other, a single word is a phrase of one
+    Phrase { field: Field, words: Vec<String> },
+    /// Two phrases with at most `distance` words between them
+    Near { field: Field, left: Vec<String>, right: Vec<String>, distance: usize },
+    And(Vec<FtsQuery>),
+    Or(Vec<FtsQuery>),
+    Not(Box<FtsQuery>),
+}
+
+/// Lowercase words of a text, each Chinese or Japanese character is a word
+pub fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() && !snippet::is_unspaced(c) {
+            word.extend(c.to_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if snippet::is_unspaced(c) {
+            words.push(c.to_string());
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Words of an entry a query is evaluated on
+pub struct EntryWords {
+    pub headword: Vec<String>,
+    pub body: Vec<String>,
+}
+
+impl EntryWords {
+    pub fn new(headword: &str, body_text: &str) -> Self {
+        Self { headword: words(headword), body: words(body_text) }
+    }
+
+    fn fields(&self, field: Field) -> Vec<&[String]> {
+        match field {
+            Field::Any => vec![&self.headword, &self.body],
+            Field::Headword => vec![&self.headword],
+            Field::Body => vec![&self.body],
+        }
+    }
+}
+
+/// Start positions of `phrase` in `words`
+fn phrase_positions<'a>(words: &'a [String], phrase: &'a [String]) -> impl Iterator<Item = usize> + 'a {
+    (0..words.len()).filter(move |pos| {
+        phrase.iter().enumerate().all(|(i, term)| words.get(pos + i).is_some_and(|word| word.starts_with(term.as_str())))
+    })
+}
+
+/// Sum the scores of keys found in both maps
+fn intersect<K: Eq + Hash>(mut a: HashMap<K, f32>, b: HashMap<K, f32>) -> HashMap<K, f32> {
+    a.retain(|key, _| b.contains_key(key));
+    for (key, score) in b {
+        if let Some(sum) = a.get_mut(&key) {
+            *sum += score;
+        }
+    }
+    a
+}
+
+impl FtsQuery {
+    /// Parse a query, errors tell the character position of the problem
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut parser = Parser { tokens: tokenize(query)?, pos: 0, end: query.chars().count() + 1 };
+        if parser.tokens.is_empty() {
+            return Err(invalid_query(1, "the query is empty"));
+        }
+        let parsed = parser.parse_or(Field::Any)?;
+        if let Some((pos, _)) = parser.tokens.get(parser.pos) {
+            return Err(invalid_query(*pos, "unmatched )"));
+        }
+        if !parsed.is_bounded() {
+            return Err(invalid_query(1, "the query needs a word or phrase that is not excluded"));
+        }
+        Ok(parsed)
+    }
+
+    /// Whether the entries matching the query can be found from its words, false when it matches
+    /// everything except some words
+    fn is_bounded(&self) -> bool {
+        match self {
+            FtsQuery::Phrase { .. } | FtsQuery::Near { .. } => true,
+            FtsQuery::And(parts) => parts.iter().any(FtsQuery::is_bounded),
+            FtsQuery::Or(parts) => parts.iter().all(FtsQuery::is_bounded),
+            FtsQuery::Not(_) => false,
+        }
+    }
+
+    /// Words of the query that are not excluded, for snippets and highlighting
+    pub fn terms(&self) -> Vec<String> {
+        let mut terms = Vec::new();
+        self.collect_terms(&mut terms);
+        terms
+    }
+
+    fn collect_terms(&self, terms: &mut Vec<String>) {
+        let words: Vec<&String> = match self {
+            FtsQuery::Phrase { words, .. } => words.iter().collect(),
+            FtsQuery::Near { left, right, .. } => left.iter().chain(right).collect(),
+            FtsQuery::And(parts) | FtsQuery::Or(parts) => {
+                parts.iter().for_each(|part| part.collect_terms(terms));
+                Vec::new()
+            }
+            FtsQuery::Not(_) => Vec::new(),
+        };
+        for word in words {
+            if !terms.contains(word) {
+                terms.push(word.clone());
+            }
+        }
+    }
+
+    /// Whether an entry matches the query
+    pub fn matches(&self, entry: &EntryWords) -> bool {
+        match self {
+            FtsQuery::Phrase { field, words } => {
+                entry.fields(*field).iter().any(|text| phrase_positions(text, words).next().is_some())
+            }
+            FtsQuery::Near { field, left, right, distance } => entry.fields(*field).iter().any(|text| {
+                let rights: Vec<usize> = phrase_positions(text, right).collect();
+                phrase_positions(text, left).any(|l| {
+                    rights.iter().any(|&r| {
+                        let gap = if r >= l + left.len() {
+                            r - (l + left.len())
+                        } else {
+                            l.saturating_sub(r + right.len())
+                        };
+                        gap <= *distance
+                    })
+                })
+            }),
+            FtsQuery::And(parts) => parts.iter().all(|part| part.matches(entry)),
+            FtsQuery::Or(parts) => parts.iter().any(|part| part.matches(entry)),
+            FtsQuery::Not(part) => !part.matches(entry),
+        }
+    }
+
+    /// Entries that may match the query with their scores, None when the query doesn't limit them
+    /// `find` returns the entries having a phrase in a field, the result still has to be checked with `matches`
+    /// Its last argument tells whether all entries are needed: the sets of AND and NEAR are intersected,
+    /// so they must not be cut to the best scores before, the result may be cut after
+    pub fn candidates<K, F>(&self, find: &mut F) -> Result<Option<HashMap<K, f32>>>
+    where
+        K: Eq + Hash,
+        F: FnMut(Field, &[String], bool) -> Result<HashMap<K, f32>>,
+    {
+        self.find_candidates(find, false)
+    }
+
+    fn find_candidates<K, F>(&self, find: &mut F, complete: bool) -> Result<Option<HashMap<K, f32>>>
+    where
+        K: Eq + Hash,
+        F: FnMut(Field, &[String], bool) -> Result<HashMap<K, f32>>,
+    {
+        Ok(match self {
+            FtsQuery::Phrase { field, words } => Some(find(*field, words, complete)?),
+            FtsQuery::Near { field, left, right, .. } => Some(intersect(find(*field, left, true)?, find(*field, right, true)?)),
+            FtsQuery::And(parts) => {
+                // A single bounded part is not intersected with anything
+                let complete = complete || parts.iter().filter(|part| part.is_bounded()).count() > 1;
+                let mut result: Option<HashMap<K, f32>> = None;
+                for part in parts {
+                    if let Some(found) = part.find_candidates(find, complete)? {
+                        result = Some(match result {
+                            Some(result) => intersect(result, found),
+                            None => found,
+                        });
+                    }
+                }
+                result
+            }
+            FtsQuery::Or(parts) => {
+                let mut result = HashMap::new();
+                for part in parts {
+                    let Some(found) = part.find_candidates(find, complete)? else {
+                        return Ok(None);
+                    };
+                    for (key, score) in found {
+                        *result.entry(key).or_insert(0.0) += score;
+                    }
+                }
+                Some(result)
+            }
+            FtsQuery::Not(_) => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Field(Field),
+    And,
+    Or,
+    Not,
+    Minus,
+    Near(usize),
+    Open,
+    Close,
+}
+
+/// Tokens with their 1-based character positions
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i + 1;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push((start, Token::Open));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((start, Token::Close));
+                i += 1;
+            }
+            '"' => {
+                let Some(len) = chars[i + 1..].iter().position(|c| *c == '"') else {
+                    return Err(invalid_query(start, "unterminated quote"));
+                };
+                tokens.push((start, Token::Quoted(chars[i + 1..i + 1 + len].iter().collect())));
+                i += len + 2;
+            }
+            '-' if chars.get(i + 1).is_some_and(|c| !c.is_whitespace() && *c != ')') => {
+                tokens.push((start, Token::Minus));
+                i += 1;
+            }
+            _ => {
+                let len = chars[i..].iter().position(|c| c.is_whitespace() || "()\"".contains(*c)).unwrap_or(chars.len() - i);
+                let word: String = chars[i..i + len].iter().collect();
+                i += len;
+                if let Some((name, rest)) = word.split_once(':')
+                    && !name.is_empty()
+                    && name.chars().all(char::is_alphabetic)
+                {
+                    let field = match name {
+                        "headword" => Field::Headword,
+                        "body" => Field::Body,
+                        _ => {
+                            let message = format!("unknown field {}:, use headword: or body:, or quote the text", name);
+                            return Err(invalid_query(start, &message));
+                        }
+                    };
+                    tokens.push((start, Token::Field(field)));
+                    if !rest.is_empty() {
+                        tokens.push((start + name.chars().count() + 1, Token::Word(rest.to_string())));
+                    }
+                    continue;
+                }
+                let token = match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    "NEAR" => Token::Near(DEFAULT_NEAR_DISTANCE),
+                    _ => match word.strip_prefix("NEAR/") {
+                        Some(distance) => Token::Near(
+                            distance.parse().map_err(|_| invalid_query(start, "NEAR/ needs a number of words"))?,
+                        ),
+                        None => Token::Word(word),
+                    },
+                };
+                tokens.push((start, token));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Position after the last character, for errors at the end of the query
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn combine(mut parts: Vec<FtsQuery>, combine: fn(Vec<FtsQuery>) -> FtsQuery) -> FtsQuery {
+        if parts.len() == 1 { parts.remove(0) } else { combine(parts) }
+    }
+
+    fn parse_or(&mut self, field: Field) -> Result<FtsQuery> {
+        let mut parts = vec![self.parse_and(field)?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            parts.push(self.parse_and(field)?);
+        }
+        Ok(Self::combine(parts, FtsQuery::Or))
+    }
+
+    fn parse_and(&mut self, field: Field) -> Result<FtsQuery> {
+        let mut parts = vec![self.parse_near(field)?];
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(Token::And) => {
+                    self.next();
+                }
+                _ => {}
+            }
+            parts.push(self.parse_near(field)?);
+        }
+        Ok(Self::combine(parts, FtsQuery::And))
+    }
+
+    /// A chain `a NEAR b NEAR c` requires each pair to be near
+    fn parse_near(&mut self, field: Field) -> Result<FtsQuery> {
+        let first = self.parse_unary(field)?;
+        if !matches!(self.peek(), Some(Token::Near(_))) {
+            return Ok(first);
+        }
+        let mut parts = Vec::new();
+        let mut left = first;
+        while let Some((pos, Token::Near(distance))) = self.tokens.get(self.pos).cloned() {
+            self.pos += 1;
+            let right = self.parse_unary(field)?;
+            let (FtsQuery::Phrase { field: left_field, words: left_words }, FtsQuery::Phrase { field: right_field, words: right_words }) = (&left, &right) else {
+                return Err(invalid_query(pos, "NEAR needs a word or phrase on both sides"));
+            };
+            let near_field = match (*left_field, *right_field) {
+                (Field::Any, other) | (other, Field::Any) => other,
+                (a, b) if a == b => a,
+                _ => return Err(invalid_query(pos, "both sides of NEAR must be in the same field")),
+            };
+            parts.push(FtsQuery::Near {
+                field: near_field,
+                left: left_words.clone(),
+                right: right_words.clone(),
+                distance,
+            });
+            left = right;
+        }
+        Ok(Self::combine(parts, FtsQuery::And))
+    }
+
+    fn parse_unary(&mut self, field: Field) -> Result<FtsQuery> {
+        match self.peek() {
+            Some(Token::Not) | Some(Token::Minus) => {
+                self.next();
+                Ok(FtsQuery::Not(Box::new(self.parse_unary(field)?)))
+            }
+            _ => self.parse_primary(field),
+        }
+    }
+
+    fn parse_primary(&mut self, field: Field) -> Result<FtsQuery> {
+        let Some((pos, token)) = self.next() else {
+            return Err(invalid_query(self.end, "unexpected end of the query"));
+        };
+        match token {
+            Token::Open => {
+                let group = self.parse_or(field)?;
+                match self.next() {
+                    Some((_, Token::Close)) => Ok(group),
+                    _ => Err(invalid_query(pos, "missing )")),
+                }
+            }
+            Token::Field(scope) => {
+                if field != Field::Any && scope != field {
+                    return Err(invalid_query(pos, "a field can't be used inside another field"));
+                }
+                match self.peek() {
+                    Some(Token::Word(_)) | Some(Token::Quoted(_)) | Some(Token::Open) => self.parse_primary(scope),
+                    _ => Err(invalid_query(pos, "a field needs a word, phrase or group after it")),
+                }
+            }
+            Token::Word(text) | Token::Quoted(text) => {
+                let phrase = words(&text);
+                if phrase.is_empty() {
+                    return Err(invalid_query(pos, &format!("nothing to search in \"{}\"", text)));
+                }
+                Ok(FtsQuery::Phrase { field, words: phrase })
+            }
+            Token::Close => Err(invalid_query(pos, "unmatched )")),
+            Token::And | Token::Or | Token::Near(_) => Err(invalid_query(pos, "an operator needs a word or phrase before it")),
+            Token::Not | Token::Minus => Err(invalid_query(pos, "unexpected NOT")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phrase(field: Field, words: &[&str]) -> FtsQuery {
+        FtsQuery::Phrase { field, words: words.iter().map(|w| w.to_string()).collect() }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            FtsQuery::parse("colour -paint OR headword:\"light blue\"").unwrap(),
+            FtsQuery::Or(vec![
+                FtsQuery::And(vec![phrase(Field::Any, &["colour"]), FtsQuery::Not(Box::new(phrase(Field::Any, &["paint"])))]),
+                phrase(Field::Headword, &["light", "blue"]),
+            ])
+        );
+        assert_eq!(
+            FtsQuery::parse("body:(red NEAR/3 blue)").unwrap(),
+            FtsQuery::Near { field: Field::Body, left: vec!["red".into()], right: vec!["blue".into()], distance: 3 }
+        );
+        assert_eq!(FtsQuery::parse("a OR b c").unwrap().terms(), vec!["a", "b", "c"]);
+
+        for (query, pos) in [("\"red", 1), ("(red", 1), ("red)", 4), ("NOT red", 1), ("title:red", 1), ("red NEAR", 9), ("(a OR b) NEAR c", 10)] {
+            let error = FtsQuery::parse(query).unwrap_err().to_string();
+            assert!(error.contains(&format!("at character {}", pos)), "{}: {}", query, error);
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let entry = EntryWords::new("Colour", "The colours of a rainbow: red, orange and blue.");
+        let is_match = |query: &str| FtsQuery::parse(query).unwrap().matches(&entry);
+        assert!(is_match("colour rainbow"));
+        assert!(is_match("headword:colour"));
+        assert!(!is_match("headword:rainbow"));
+        assert!(is_match("\"red orange\""));
+        assert!(!is_match("\"orange red\""));
+        assert!(is_match("blue NEAR/2 orange"));
+        assert!(!is_match("red NEAR/1 blue"));
+        assert!(is_match("rainbow -green"));
+        assert!(!is_match("rainbow NOT (green OR blue)"));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let query = FtsQuery::parse("(red OR blue) green -grey").unwrap();
+        let mut find = |_field: Field, words: &[String], complete: bool| -> Result<HashMap<u32, f32>> {
+            assert!(complete, "intersected sets are not cut");
+            Ok(match words[0].as_str() {
+                "red" => HashMap::from([(1, 1.0), (2, 1.0)]),
+                "blue" => HashMap::from([(2, 1.0), (3, 1.0)]),
+                "green" => HashMap::from([(2, 0.5), (3, 0.5), (4, 0.5)]),
+                _ => panic!("excluded words are not searched"),
+            })
+        };
+        let candidates = query.candidates(&mut find).unwrap().unwrap();
+        assert_eq!(candidates, HashMap::from([(2, 2.5), (3, 1.5)]));
+
+        // Only sets that are intersected need all entries
+        let mut searched = Vec::new();
+        let mut find = |_field: Field, words: &[String], complete: bool| -> Result<HashMap<u32, f32>> {
+            searched.push((words[0].clone(), complete));
+            Ok(HashMap::new())
+        };
+        FtsQuery::parse("(red OR blue) -grey").unwrap().candidates(&mut find).unwrap();
+        FtsQuery::parse("red NEAR blue").unwrap().candidates(&mut find).unwrap();
+        assert_eq!(searched, vec![
+            ("red".to_string(), false), ("blue".to_string(), false),
+            ("red".to_string(), true), ("blue".to_string(), true),
+        ]);
+    }
+}
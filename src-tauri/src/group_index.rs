@@ -0,0 +1,234 @@
+// Group index module - persistent merged headword list of a dictionary group
+// The keys of all libraries are merged once in the group's collation order and stored in an SQLite
+// file under the data directory, so that a group can be browsed by position like a single dictionary.
+// The file records what it was built from and is rebuilt when that changes
+use std::cmp::Ordering;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use mdx::storage::{EntryNo, KeyIndex};
+
+use crate::error::{Result, ZdbError};
+use crate::mdx_db::MdxIndex;
+
+/// Changes when the layout of the index file changes, part of the signature
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// How long a statement waits for the file while another connection (e.g. a build) has it locked
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A merged key: (normalized_key, display_key, indexes of all libraries having it)
+pub type IndexedKey = (String, String, Vec<MdxIndex>);
+
+fn sql_error(e: rusqlite::Error) -> ZdbError {
+    ZdbError::invalid_data_format(format!("Group index error: {}", e))
+}
+
+/// Merged headword list of a group, read by position
+pub struct GroupKeyIndex {
+    conn: Mutex<Connection>,
+    len: usize,
+}
+
+impl GroupKeyIndex {
+    /// Open the index file if it was built for `signature`, None if it is missing or out of date
+    pub fn open(path: &Path, signature: &str) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let conn = Self::connect(path)?;
+        Self::create_tables(&conn)?;
+        let stored: Option<String> = conn
+            .query_row("SELECT value FROM meta WHERE name = 'signature'", [], |row| row.get(0))
+            .optional()
+            .map_err(sql_error)?;
+        if stored.as_deref() != Some(Self::versioned(signature).as_str()) {
+            return Ok(None);
+        }
+        Self::with_connection(conn).map(Some)
+    }
+
+    fn connect(path: &Path) -> Result<Connection> {
+        let conn = Connection::open(path).map_err(sql_error)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(sql_error)?;
+        Ok(conn)
+    }
+
+    /// Build the index file from the merged keys in order, `next_chunk` returns an empty chunk at the end
+    /// The file is replaced in one transaction, an interrupted build leaves it out of date
+    pub fn build<F>(path: &Path, signature: &str, mut next_chunk: F) -> Result<Self>
+    where
+        F: FnMut() -> Result<Vec<IndexedKey>>,
+    {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut conn = Self::connect(path)?;
+        Self::create_tables(&conn)?;
+        let tx = conn.transaction().map_err(sql_error)?;
+        tx.execute_batch("DELETE FROM meta; DELETE FROM group_keys; DELETE FROM group_entries;").map_err(sql_error)?;
+        {
+            let mut insert_key = tx
+                .prepare("INSERT INTO group_keys (position, normalized_key, display_key) VALUES (?1, ?2, ?3)")
+                .map_err(sql_error)?;
+            let mut insert_entry = tx
+                .prepare("INSERT INTO group_entries (position, profile_id, entry_no, key) VALUES (?1, ?2, ?3, ?4)")
+                .map_err(sql_error)?;
+            let mut position: i64 = 0;
+            loop {
+                let chunk = next_chunk()?;
+                if chunk.is_empty() {
+                    break;
+                }
+                for (normalized_key, display_key, indexes) in chunk {
+                    insert_key.execute(params![position, normalized_key, display_key]).map_err(sql_error)?;
+                    for index in indexes {
+                        insert_entry
+                            .execute(params![position, index.profile_id, index.key_index.entry_no, index.key_index.key])
+                            .map_err(sql_error)?;
+                    }
+                    position += 1;
+                }
+            }
+        }
+        tx.execute("INSERT INTO meta (name, value) VALUES ('signature', ?1)", params![Self::versioned(signature)])
+            .map_err(sql_error)?;
+        tx.commit().map_err(sql_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn versioned(signature: &str) -> String {
+        format!("{}|{}", INDEX_FORMAT_VERSION, signature)
+    }
+
+    fn create_tables(conn: &Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS meta (
+                name TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS group_keys (
+                position INTEGER PRIMARY KEY,
+                normalized_key TEXT NOT NULL,
+                display_key TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS group_entries (
+                position INTEGER NOT NULL,
+                profile_id INTEGER NOT NULL,
+                entry_no INTEGER NOT NULL,
+                key TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_group_entries_position ON group_entries(position);",
+        ).map_err(sql_error)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        let len: i64 = conn.query_row("SELECT COUNT(*) FROM group_keys", [], |row| row.get(0)).map_err(sql_error)?;
+        Ok(Self { conn: Mutex::new(conn), len: len as usize })
+    }
+
+    /// Number of merged keys
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Up to `count` keys starting at `start`, with the entries of every library having them
+    pub fn get_keys(&self, start: usize, count: usize) -> Result<Vec<IndexedKey>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut keys = Vec::new();
+        let mut select_keys = conn
+            .prepare("SELECT position, normalized_key, display_key FROM group_keys WHERE position >= ?1 ORDER BY position LIMIT ?2")
+            .map_err(sql_error)?;
+        let mut select_entries = conn
+            .prepare("SELECT profile_id, entry_no, key FROM group_entries WHERE position = ?1")
+            .map_err(sql_error)?;
+        let rows = select_keys
+            .query_map(params![start as i64, count as i64], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(sql_error)?;
+        for row in rows {
+            let (position, normalized_key, display_key) = row.map_err(sql_error)?;
+            let indexes = select_entries
+                .query_map(params![position], |row| {
+                    Ok(MdxIndex {
+                        profile_id: row.get(0)?,
+                        key_index: KeyIndex { entry_no: row.get::<_, EntryNo>(1)?, key: row.get(2)?, ..Default::default() },
+                    })
+                })
+                .map_err(sql_error)?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(sql_error)?;
+            keys.push((normalized_key, display_key, indexes));
+        }
+        Ok(keys)
+    }
+
+    fn display_key(&self, position: usize) -> Result<String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row("SELECT display_key FROM group_keys WHERE position = ?1", params![position as i64], |row| row.get(0))
+            .map_err(sql_error)
+    }
+
+    /// First position whose key is not ordered before the searched key, `compare` orders a stored key
+    /// against it. `len()` when every key is before it
+    pub fn lower_bound(&self, compare: impl Fn(&str) -> Ordering) -> Result<usize> {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if compare(&self.display_key(middle)?) == Ordering::Less {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(profile_id: i32, entry_no: EntryNo, key: &str) -> MdxIndex {
+        MdxIndex {
+            profile_id,
+            key_index: KeyIndex { entry_no, key: key.to_string(), ..Default::default() },
+        }
+    }
+
+    #[test]
+    fn test_build_and_open() {
+        // A directory of its own, so that concurrent test runs don't share the file
+        let dir = std::env::temp_dir().join(format!("mdict-group-index-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("group.db");
+        let chunks = vec![
+            vec![
+                ("a".to_string(), "a".to_string(), vec![index(1, 0, "a"), index(2, 0, "A")]),
+                ("b".to_string(), "B".to_string(), vec![index(2, 1, "B")]),
+            ],
+            vec![("c".to_string(), "c".to_string(), vec![index(1, 1, "c")])],
+        ];
+        let mut chunks = chunks.into_iter();
+        let index = GroupKeyIndex::build(&path, "libs", || Ok(chunks.next().unwrap_or_default())).unwrap();
+        assert_eq!(index.len(), 3);
+        let keys = index.get_keys(1, 10).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].1, "B");
+        assert_eq!(keys[0].2[0].key_index.key, "B");
+        assert_eq!(keys[1].1, "c");
+        assert_eq!(index.get_keys(0, 1).unwrap()[0].2.len(), 2);
+        assert_eq!(index.lower_bound(|key| key.to_lowercase().as_str().cmp("b")).unwrap(), 1);
+        assert_eq!(index.lower_bound(|key| key.to_lowercase().as_str().cmp("z")).unwrap(), 3);
+        drop(index);
+
+        assert!(GroupKeyIndex::open(&path, "other libs").unwrap().is_none());
+        assert_eq!(GroupKeyIndex::open(&path, "libs").unwrap().unwrap().len(), 3);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
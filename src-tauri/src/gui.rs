@@ -0,0 +1,274 @@
+// GUI module - Tauri application setup, custom protocol and command registration
+use std::sync::Arc;
+use humantime::format_rfc3339;
+use log::{LevelFilter, error, info, debug};
+use tauri::Manager;
+use tauri::http::{Response, StatusCode};
+use tauri::path::BaseDirectory;
+
+use crate::app_config::{ConfigSection, ConfigKey};
+use crate::event_sink::TauriEventSink;
+use crate::utils::log_if_err;
+use crate::{http_server, mdict_app, request_handler};
+#[cfg(all(not(target_os = "android"), not(target_os = "ios"), feature = "global-hotkey"))]
+use crate::hotkey_manager;
+
+// Import all command functions
+use crate::config_cmd::*;
+use crate::conversion_cmd::*;
+use crate::favorites_cmd::*;
+use crate::history_cmd::*;
+#[cfg(all(not(target_os = "android"), not(target_os = "ios"), feature = "global-hotkey"))]
+use crate::hotkey_cmd::*;
+use crate::library_cmd::*;
+use crate::search_cmd::*;
+use crate::session_cmd::*;
+use crate::system_cmd::*;
+
+/// Macro to generate the invoke handler with all commands
+/// 
+/// This macro centralizes all command registration by including all command functions
+/// from all modules. When adding new commands, you need to:
+/// 1. Add the command function to the appropriate module
+/// 2. Add the command name to the module's COMMANDS array
+/// 3. Add the function name to this macro
+/// 
+/// The macro ensures that all commands are registered consistently and makes it
+/// easy to see all available commands in one place.
+macro_rules! generate_all_commands_handler {
+    () => {
+        tauri::generate_handler![
+            // Search commands
+            search_search_incremental,
+            search_combined_search,
+            search_get_content_url,
+            search_get_entry_count,
+            search_has_more_results,
+            search_find_index,
+            search_fulltext_search,
+            search_pattern_search,
+            search_pinyin_search,
+            search_suggest,
+            search_get_result_key_list,
+            search_get_group_indexes,
+            // Session commands
+            session_create,
+            session_close,
+            session_open_profile,
+            session_list,
+            session_navigate,
+            session_go_back,
+            session_go_forward,
+            // History commands
+            history_add_to_history,
+            history_get_all_history,
+            history_get_history_entry_by_id,
+            history_remove_from_history,
+            history_clear_history,
+            history_get_history_count,
+            history_set_max_history_size,
+            history_get_max_history_size,
+            history_import_history,
+            // Favorites commands
+            favorites_add_favorite,
+            favorites_remove_favorite,
+            favorites_toggle_favorite,
+            favorites_is_favorited,
+            favorites_get_sorted_and_filtered_favorites,
+            favorites_get_all_favorites,
+            favorites_clear_all_favorites,
+            favorites_import_favorites,
+            favorites_get_favorites_count,
+            // Config commands - Generic interface
+            config_get_app_config,
+            config_get_value,
+            config_set_value,
+            config_get_global_settings,
+            config_get_view_settings,
+            config_update_view_settings,
+            config_list_fonts_in_directory,
+            config_save_app_config,
+            config_reload_app_config,
+            config_reload_resources,
+            // Library commands
+            library_create_dict_group,
+            library_delete_dict_group,
+            library_rename_dict_group,
+            library_set_key_normalization,
+            library_set_collation_locale,
+            library_set_fts_weight,
+            library_set_languages,
+            library_get_languages,
+            library_refresh_library,
+            library_get_group,
+            library_get_profile,
+            library_list_groups,
+            library_update_profile_disabled_status,
+            library_adjust_profile_order,
+            library_adjust_group_order,
+            library_open_main_database,
+            library_get_current_main_profile_id,
+            library_get_main_db_profile,
+            library_rebuild_index,
+            // Conversion commands
+            library_convert_db,
+            library_create_fts_index,
+            library_cancel_conversion,
+            // System commands
+            system_set_base_url,
+            system_start_http_server,
+            system_stop_http_server,
+            system_get_http_server_base_url,
+            // Hotkey commands (desktop only)
+            #[cfg(all(not(target_os = "android"), not(target_os = "ios"), feature = "global-hotkey"))]
+            hotkey_register,
+            #[cfg(all(not(target_os = "android"), not(target_os = "ios"), feature = "global-hotkey"))]
+            hotkey_unregister,
+        ]
+    };
+}
+
+
+pub fn run() {
+    unsafe { std::env::set_var("RUST_BACKTRACE", "1"); }
+
+    tauri::Builder::default()
+        .setup(|app| {
+            let app_handle = app.handle();
+            let data_dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+            
+            // Setup logging
+            fern::Dispatch::new()
+            .format(|out, message, record| {
+                out.finish(format_args!(
+                    "[{} {} {}] {}",
+                    format_rfc3339(std::time::SystemTime::now()),
+                    record.level(),
+                    record.target(),
+                    message
+                ))
+            })
+            .level(LevelFilter::Debug)  
+            .chain(std::io::stdout())
+            .apply().unwrap();
+            
+            // Initialize main app (includes history and favorites managers)
+            let assets_dir = app_handle.path().resolve("assets", BaseDirectory::Resource).ok();
+            let event_sink = Arc::new(TauriEventSink::new(app_handle.clone()));
+            log_if_err(&mdict_app::init_mdict_app(data_dir.clone(), Vec::new(), assets_dir, event_sink));
+
+            // Start the local HTTP server if enabled in settings
+            let http_server_config = mdict_app::with_config_read(|config| Ok((
+                config.get_config_with_default(ConfigSection::Global, ConfigKey::HttpServerEnabled, false),
+                config.get_config_with_default(ConfigSection::Global, ConfigKey::HttpServerPort, 0u16),
+            )));
+            if let Ok((true, port)) = http_server_config {
+                if let Err(e) = http_server::start_http_server(port) {
+                    error!("Failed to start HTTP server: {}", e);
+                }
+            }
+
+            // Setup window event handler for focus detection
+            let window = app.get_webview_window("main").expect("Failed to get main window");
+            let app_handle_clone = app_handle.clone();
+            window.on_window_event(move |event| {
+                if let tauri::WindowEvent::Focused(focused) = event {
+                    if *focused {
+                        info!("Window gained focus, notifying frontend");
+                        // Emit event to frontend
+                        // Frontend will check autoLookupSelection setting and handle clipboard accordingly
+                        use tauri::Emitter;
+                        if let Err(e) = app_handle_clone.emit("window-focus", ()) {
+                            error!("Failed to emit window-focus event: {}", e);
+                        }
+                    }
+                }
+            });
+
+            // Initialize hotkey manager (desktop only)
+            #[cfg(all(not(target_os = "android"), not(target_os = "ios"), feature = "global-hotkey"))]
+            {
+                if let Err(e) = hotkey_manager::init_hotkey_manager(app_handle.clone()) {
+                    error!("Failed to initialize hotkey manager: {}", e);
+                }
+
+                // Load hotkey configuration from settings
+                let app_handle_for_hotkey = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Ok(config) = mdict_app::with_config_read(|config| Ok(config.clone())) {
+                        let hotkey_letter = config.global_settings.get("hotkey_letter")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("");
+                        let hotkey_modifier = config.global_settings.get("hotkey_modifier")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("");
+                        
+                        if !hotkey_letter.is_empty() && !hotkey_modifier.is_empty() {
+                            info!("Registering hotkey from config: {} + {}", hotkey_modifier, hotkey_letter);
+                            if let Err(e) = hotkey_manager::register_hotkey(app_handle_for_hotkey, hotkey_letter, hotkey_modifier) {
+                                error!("Failed to register hotkey on startup: {}", e);
+                            }
+                        } else {
+                            info!("No hotkey configured");
+                        }
+                    }
+                });
+
+                // Spawn a task to check for hotkey events periodically
+                let _hotkey_task = std::thread::spawn(move || {
+                    loop {
+                        hotkey_manager::check_hotkey_events();
+                        std::thread::sleep(std::time::Duration::from_millis(100));
+                    }
+                });
+            }
+                        
+            Ok(())
+        })
+        // Register protocol scheme based on platform
+        // Windows: use "http" scheme
+        // Non-Windows (macOS, Linux): use "mdx" scheme
+        .register_asynchronous_uri_scheme_protocol(
+            #[cfg(target_os = "windows")]
+            "http",
+            #[cfg(not(target_os = "windows"))]
+            "mdx",
+            |_app, request, responder| {
+                tauri::async_runtime::spawn(async move {
+                    let uri = request.uri().to_string();
+                    
+                    // Check if the request matches the configured base_url
+                    // Only handle requests that start with base_url, otherwise let webview handle them
+                    let base_url = mdict_app::with_read_access(|app| {
+                        Ok(app.get_base_url().to_string())
+                    }).unwrap_or("mdx://mdict.cn/service/".to_string());
+                    
+                    if !uri.starts_with(&base_url.replace("http.localhost", "localhost")) {
+                        if uri.contains("/service/") {
+                            debug!("Request does not match base_url, skipping: {}", uri);
+                        }
+                        return;
+                    }
+
+                    // Handle the request using our custom handler
+                    match request_handler::handle_request(&request, &base_url) {
+                        Ok(response) => responder.respond(response),
+                        Err(err) => {
+                            error!("Error in protocol handler: {}", err);
+                            let error_html = format!("<html><body><h1>Protocol Error</h1><p>{}</p></body></html>", err);
+                            let mut error_response = Response::new(error_html.into_bytes());
+                            *error_response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                            error_response.headers_mut().insert("Content-Type", "text/html; charset=utf-8".parse().unwrap());
+                            responder.respond(error_response);
+                        }
+                    }
+                });
+            }
+        )
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .invoke_handler(generate_all_commands_handler!())
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
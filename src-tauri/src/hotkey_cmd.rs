@@ -6,17 +6,21 @@
 use log::info;
 use tauri::{AppHandle, command};
 
+use crate::error::{CommandError, CommandResult, ErrorCode};
+
 /// Register a global hotkey
 #[command]
-pub fn hotkey_register(app_handle: AppHandle, letter: String, modifier: String) -> Result<(), String> {
+pub fn hotkey_register(app_handle: AppHandle, letter: String, modifier: String) -> CommandResult<()> {
     info!("Command: hotkey_register - letter: {}, modifier: {}", letter, modifier);
     crate::hotkey_manager::register_hotkey(app_handle, &letter, &modifier)
+        .map_err(|e| CommandError::new(ErrorCode::Internal, e))
 }
 
 /// Unregister the current global hotkey
 #[command]
-pub fn hotkey_unregister() -> Result<(), String> {
+pub fn hotkey_unregister() -> CommandResult<()> {
     info!("Command: hotkey_unregister");
     crate::hotkey_manager::unregister_hotkey()
+        .map_err(|e| CommandError::new(ErrorCode::Internal, e))
 }
 
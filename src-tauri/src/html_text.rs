@@ -0,0 +1,177 @@
+// HTML text module - converts dictionary entry HTML to plain text
+
+/// Tags that start a new line in the plain text output
+const BLOCK_TAGS: &[&str] = &[
+    "br", "p", "div", "li", "tr", "h1", "h2", "h3", "h4", "h5", "h6",
+    "table", "ul", "ol", "dd", "dt", "blockquote", "hr",
+];
+
+/// Tags whose content is never visible text
+pub const SKIPPED_TAGS: &[&str] = &["script", "style", "head", "title"];
+
+/// Convert entry HTML to plain text
+/// Tags are stripped, script/style content dropped, entities decoded and whitespace collapsed
+pub fn html_to_text(html: &str) -> String {
+    let mut raw = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        push_decoded(&mut raw, &rest[..start]);
+        rest = &rest[start..];
+
+        // Comments may contain '>' so they are skipped as a whole
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map(|end| &rest[end + 3..]).unwrap_or("");
+            continue;
+        }
+
+        let Some(end) = find_tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let name = tag_name(tag);
+        if !tag.starts_with('/') && SKIPPED_TAGS.contains(&name.as_str()) {
+            // Skip everything up to and including the closing tag
+            let closing = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&closing) {
+                Some(pos) => {
+                    let closing_tag = &rest[pos..];
+                    find_tag_end(closing_tag).map(|end| &closing_tag[end + 1..]).unwrap_or("")
+                }
+                None => "",
+            };
+        } else if BLOCK_TAGS.contains(&name.as_str()) {
+            raw.push('\n');
+        }
+    }
+    push_decoded(&mut raw, rest);
+
+    // Collapse whitespace inside lines and drop empty lines
+    raw.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escape text for use in HTML content or attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Find the byte offset of the '>' closing the tag at the start of `s`, skipping quoted attribute values
+pub fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (pos, c) in s.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(pos),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Get the lowercase element name of a tag body like `/div` or `a href="..."`
+pub fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Append text to `out`, decoding HTML character references
+pub fn push_decoded(out: &mut String, text: &str) {
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let semicolon = rest.char_indices().take(12).find(|(_, c)| *c == ';').map(|(pos, _)| pos);
+        match semicolon.and_then(|pos| decode_entity(&rest[1..pos]).map(|c| (pos, c))) {
+            Some((pos, c)) => {
+                out.push(c);
+                rest = &rest[pos + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+}
+
+/// Decoded characters of HTML text, each with the byte offset in `text` where it starts
+/// Like `push_decoded` but keeps the position of every character in the source
+pub fn decoded_chars(text: &str) -> Vec<(usize, char)> {
+    let mut chars = Vec::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        if c == '&' {
+            let semicolon = rest.char_indices().take(12).find(|(_, c)| *c == ';').map(|(pos, _)| pos);
+            if let Some((end, decoded)) = semicolon.and_then(|end| decode_entity(&rest[1..end]).map(|decoded| (end, decoded))) {
+                chars.push((pos, decoded));
+                pos += end + 1;
+                continue;
+            }
+        }
+        chars.push((pos, c));
+        pos += c.len_utf8();
+    }
+    chars
+}
+
+/// Decode an entity name (without '&' and ';')
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let code = if let Some(hex) = number.strip_prefix('x').or_else(|| number.strip_prefix('X')) {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                number.parse::<u32>().ok()?
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<html><head><style>b { color: red; }</style></head>
+            <body><b title="a > b">colour</b>&nbsp;<i>n.</i><br/>the &quot;hue&quot; &amp; tint&#33;
+            <script>var x = "<p>";</script><!-- note > --><div>second   line</div></body></html>"#;
+        assert_eq!(html_to_text(html), "colour n.\nthe \"hue\" & tint!\nsecond line");
+    }
+
+    #[test]
+    fn test_unknown_entity_is_kept() {
+        assert_eq!(html_to_text("R&D &bogus; caf&#xE9;"), "R&D &bogus; café");
+    }
+}
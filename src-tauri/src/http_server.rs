@@ -0,0 +1,238 @@
+// HTTP server module - serves the mdx:// service actions on a localhost port
+// so that browsers, reading apps and editor plugins can show dictionary entries,
+// and the versioned JSON API (/api/v1/...) for external tools
+// Only requests addressed to 127.0.0.1 or localhost are served (against DNS rebinding), and web pages
+// can read the responses only from the origins allowed by `http_server_allowed_origins`
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use tiny_http::{Header, Server};
+
+use crate::app_config::{ConfigKey, ConfigSection};
+use crate::error::{Result, ZdbError};
+use crate::json_api::handle_api_request;
+use crate::mdict_app::with_config_read;
+use crate::request_handler::handle_request;
+
+/// Number of worker threads serving requests
+const WORKER_COUNT: usize = 4;
+
+/// Host names the server answers to
+const LOCAL_HOSTS: [&str; 2] = ["127.0.0.1", "localhost"];
+
+/// Who may send requests to the server and read its responses
+#[derive(Clone)]
+struct AccessPolicy {
+    port: u16,
+    /// Origins of web pages allowed to read responses (CORS), like "https://example.com"
+    allowed_origins: Vec<String>,
+}
+
+impl AccessPolicy {
+    /// Whether the Host header names this server, a rebound DNS name is rejected
+    fn is_local_host(&self, host: &str) -> bool {
+        let (name, port) = match host.rsplit_once(':') {
+            Some((name, port)) => (name, port.parse::<u16>().ok()),
+            None => (host, None),
+        };
+        LOCAL_HOSTS.iter().any(|local| name.eq_ignore_ascii_case(local)) && port.is_none_or(|port| port == self.port)
+    }
+
+    /// Whether a page of `origin` may use the server: pages served by the server itself and the allowed origins
+    fn is_allowed_origin(&self, origin: &str) -> bool {
+        LOCAL_HOSTS.iter().any(|host| origin == format!("http://{}:{}", host, self.port))
+            || self.allowed_origins.iter().any(|allowed| allowed == origin)
+    }
+}
+
+/// Running server state
+struct HttpServer {
+    base_url: String,
+    stopped: Arc<AtomicBool>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+static HTTP_SERVER: Lazy<Mutex<Option<HttpServer>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start the HTTP server on 127.0.0.1:`port`, returns the service base URL
+/// If the server is already running it is restarted on the new port
+pub fn start_http_server(port: u16) -> Result<String> {
+    stop_http_server();
+
+    let address = format!("127.0.0.1:{}", port);
+    let server = Arc::new(Server::http(&address)
+        .map_err(|e| ZdbError::invalid_parameter(format!("Failed to bind HTTP server on {}: {}", address, e)))?);
+    // Port 0 binds any free port
+    let port = server.server_addr().to_ip().map_or(port, |address| address.port());
+    let base_url = format!("http://127.0.0.1:{}/service/", port);
+    let policy = AccessPolicy {
+        port,
+        allowed_origins: with_config_read(|config| {
+            Ok(config.get_config_with_default(ConfigSection::Global, ConfigKey::HttpServerAllowedOrigins, Vec::<String>::new()))
+        }).unwrap_or_default(),
+    };
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let mut workers = Vec::with_capacity(WORKER_COUNT);
+    for _ in 0..WORKER_COUNT {
+        let server = server.clone();
+        let stopped = stopped.clone();
+        let base_url = base_url.clone();
+        let policy = policy.clone();
+        workers.push(std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match server.recv_timeout(Duration::from_millis(500)) {
+                    Ok(Some(request)) => serve_request(request, &base_url, &policy),
+                    Ok(None) => {}
+                    Err(e) => {
+                        error!("HTTP server failed to receive request: {}", e);
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+
+    info!("HTTP server listening on {}", base_url);
+    *HTTP_SERVER.lock().unwrap() = Some(HttpServer {
+        base_url: base_url.clone(),
+        stopped,
+        workers,
+    });
+    Ok(base_url)
+}
+
+/// Stop the HTTP server if it is running
+pub fn stop_http_server() {
+    let server = HTTP_SERVER.lock().unwrap().take();
+    if let Some(server) = server {
+        server.stopped.store(true, Ordering::Relaxed);
+        for worker in server.workers {
+            let _ = worker.join();
+        }
+        info!("HTTP server on {} stopped", server.base_url);
+    }
+}
+
+/// Get the service base URL of the running server, e.g. http://127.0.0.1:8765/service/
+pub fn get_http_server_base_url() -> Option<String> {
+    HTTP_SERVER.lock().unwrap().as_ref().map(|server| server.base_url.clone())
+}
+
+/// Map a request path to the service URL it is handled by
+/// `/lookup?word=` is a shortcut for `/service/lookup?word=`
+fn to_service_url(path: &str, base_url: &str) -> Option<String> {
+    if let Some(action) = path.strip_prefix("/service/") {
+        Some(format!("{}{}", base_url, action))
+    } else if path == "/lookup" || path.starts_with("/lookup?") {
+        Some(format!("{}{}", base_url, &path[1..]))
+    } else {
+        None
+    }
+}
+
+fn header_value<'a>(request: &'a tiny_http::Request, name: &'static str) -> Option<&'a str> {
+    request.headers().iter().find(|header| header.field.equiv(name)).map(|header| header.value.as_str())
+}
+
+fn serve_request(mut request: tiny_http::Request, base_url: &str, policy: &AccessPolicy) {
+    debug!("HTTP {} {}", request.method(), request.url());
+
+    let host = header_value(&request, "Host").unwrap_or_default();
+    if !policy.is_local_host(host) {
+        info!("Rejected HTTP request for host {}", host);
+        respond(request, text_response(403, "Forbidden"));
+        return;
+    }
+    let origin = header_value(&request, "Origin").map(str::to_string);
+    if let Some(origin) = &origin
+        && !policy.is_allowed_origin(origin)
+    {
+        info!("Rejected HTTP request from origin {}", origin);
+        respond(request, text_response(403, "Forbidden"));
+        return;
+    }
+
+    let mut response = if request.url().starts_with("/api/") {
+        match url::Url::parse(base_url).and_then(|url| url.join(request.url())) {
+            Ok(url) => to_tiny_response(handle_api_request(&url, base_url)),
+            Err(e) => text_response(400, &format!("Bad request: {}", e)),
+        }
+    } else {
+        match to_service_url(request.url(), base_url) {
+            Some(url) => {
+                let mut body = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut body);
+                match http::Request::builder().method(request.method().as_str()).uri(url.as_str()).body(body) {
+                    Ok(http_request) => match handle_request(&http_request, base_url) {
+                        Ok(response) => to_tiny_response(response),
+                        Err(e) => text_response(500, &format!("Internal error: {}", e)),
+                    },
+                    Err(e) => text_response(400, &format!("Bad request: {}", e)),
+                }
+            }
+            None => text_response(404, "Not found"),
+        }
+    };
+
+    // Only an allowed page may read the response, the handlers' CORS headers are for the webview
+    if let Some(origin) = origin
+        && let (Ok(allow_origin), Ok(vary)) = (Header::from_bytes(&b"Access-Control-Allow-Origin"[..], origin.as_bytes()),
+            Header::from_bytes(&b"Vary"[..], &b"Origin"[..]))
+    {
+        response.add_header(allow_origin);
+        response.add_header(vary);
+    }
+    respond(request, response);
+}
+
+fn respond(request: tiny_http::Request, response: tiny_http::Response<std::io::Cursor<Vec<u8>>>) {
+    if let Err(e) = request.respond(response) {
+        error!("Failed to send HTTP response: {}", e);
+    }
+}
+
+fn to_tiny_response(response: http::Response<Vec<u8>>) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let (parts, body) = response.into_parts();
+    let mut tiny_response = tiny_http::Response::from_data(body).with_status_code(parts.status.as_u16());
+    for (name, value) in parts.headers.iter() {
+        if name.as_str().starts_with("access-control-") {
+            continue;
+        }
+        if let Ok(header) = Header::from_bytes(name.as_str().as_bytes(), value.as_bytes()) {
+            tiny_response.add_header(header);
+        }
+    }
+    tiny_response
+}
+
+fn text_response(status: u16, message: &str) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let mut response = tiny_http::Response::from_data(message.as_bytes().to_vec()).with_status_code(status);
+    if let Ok(header) = Header::from_bytes(&b"Content-Type"[..], &b"text/plain; charset=utf-8"[..]) {
+        response.add_header(header);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_policy() {
+        let policy = AccessPolicy { port: 8765, allowed_origins: vec!["https://example.com".to_string()] };
+        assert!(policy.is_local_host("127.0.0.1:8765"));
+        assert!(policy.is_local_host("LOCALHOST:8765"));
+        assert!(policy.is_local_host("localhost"));
+        assert!(!policy.is_local_host("attacker.example:8765"));
+        assert!(!policy.is_local_host("127.0.0.1:80"));
+        assert!(!policy.is_local_host(""));
+        assert!(policy.is_allowed_origin("http://127.0.0.1:8765"));
+        assert!(policy.is_allowed_origin("https://example.com"));
+        assert!(!policy.is_allowed_origin("https://evil.example"));
+    }
+}
@@ -0,0 +1,344 @@
+// Japanese module - query normalization for Japanese dictionaries
+// Romaji is converted to hiragana, katakana and hiragana are folded together, and conjugated
+// verbs and adjectives are deinflected to their dictionary form with a rule table like Yomichan's
+use std::collections::HashSet;
+use once_cell::sync::Lazy;
+
+const KATAKANA_START: char = '\u{30A1}';
+const KATAKANA_END: char = '\u{30F6}';
+/// Distance from a katakana to the same hiragana
+const KANA_OFFSET: u32 = 0x60;
+
+/// Whether a character is hiragana or katakana (including the prolonged sound mark)
+pub fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}' | '\u{30A1}'..='\u{30FA}' | '\u{30FC}')
+}
+
+/// Whether a text has kana or CJK ideographs
+pub fn is_japanese(text: &str) -> bool {
+    text.chars().any(|c| is_kana(c) || matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}'))
+}
+
+pub fn katakana_to_hiragana_char(c: char) -> char {
+    if (KATAKANA_START..=KATAKANA_END).contains(&c) {
+        char::from_u32(c as u32 - KANA_OFFSET).unwrap_or(c)
+    } else {
+        c
+    }
+}
+
+pub fn katakana_to_hiragana(text: &str) -> String {
+    text.chars().map(katakana_to_hiragana_char).collect()
+}
+
+pub fn hiragana_to_katakana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{3041}'..='\u{3096}' => char::from_u32(c as u32 + KANA_OFFSET).unwrap_or(c),
+            c => c,
+        })
+        .collect()
+}
+
+/// Romaji syllables, both Hepburn and Kunrei spellings
+const ROMAJI_TABLE: &[(&str, &str)] = &[
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("sa", "さ"), ("si", "し"), ("shi", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("za", "ざ"), ("zi", "じ"), ("ji", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("ta", "た"), ("ti", "ち"), ("chi", "ち"), ("tu", "つ"), ("tsu", "つ"), ("te", "て"), ("to", "と"),
+    ("da", "だ"), ("di", "ぢ"), ("du", "づ"), ("de", "で"), ("do", "ど"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("ha", "は"), ("hi", "ひ"), ("hu", "ふ"), ("fu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("wa", "わ"), ("wo", "を"), ("n'", "ん"),
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"), ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("sha", "しゃ"), ("shu", "しゅ"), ("sho", "しょ"), ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"),
+    ("ja", "じゃ"), ("ju", "じゅ"), ("jo", "じょ"), ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"),
+    ("cha", "ちゃ"), ("chu", "ちゅ"), ("cho", "ちょ"), ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"), ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"), ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"), ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("-", "ー"),
+];
+
+/// Convert romaji (Hepburn or Kunrei) to hiragana, None when the text isn't romaji
+pub fn romaji_to_hiragana(text: &str) -> Option<String> {
+    let text = text.to_lowercase();
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_lowercase() || c == '-' || c == '\'') {
+        return None;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let c = chars[pos];
+        let is_vowel_or_y = |pos: usize| chars.get(pos).is_some_and(|c| "aeiouy".contains(*c));
+        // "nn" not followed by a vowel is a single ん
+        if c == 'n' && chars.get(pos + 1) == Some(&'n') && !is_vowel_or_y(pos + 2) {
+            result.push('ん');
+            pos += 2;
+            continue;
+        }
+        // A doubled consonant is a small tsu, "tch" as in "matcha" too
+        if pos + 1 < chars.len() && !"aeioun-'".contains(c)
+            && (chars[pos + 1] == c || (c == 't' && chars[pos + 1] == 'c'))
+        {
+            result.push('っ');
+            pos += 1;
+            continue;
+        }
+        let syllable = (1..=3).rev().find_map(|len| {
+            let end = pos + len;
+            if end > chars.len() {
+                return None;
+            }
+            let romaji: String = chars[pos..end].iter().collect();
+            ROMAJI_TABLE.iter().find(|(r, _)| *r == romaji).map(|(_, kana)| (len, *kana))
+        });
+        match syllable {
+            Some((len, kana)) => {
+                result.push_str(kana);
+                pos += len;
+            }
+            // "n" before a consonant or at the end
+            None if c == 'n' && !is_vowel_or_y(pos + 1) => {
+                result.push('ん');
+                pos += 1;
+            }
+            None => return None,
+        }
+    }
+    Some(result)
+}
+
+// Word types of the deinflection rules, a rule applies when the current form has one of its input types
+const V1: u8 = 1;
+const V5: u8 = 1 << 1;
+const VK: u8 = 1 << 2;
+const VS: u8 = 1 << 3;
+const ADJ_I: u8 = 1 << 4;
+/// Te form, reached from progressive forms like 〜ている
+const TE: u8 = 1 << 5;
+/// Dictionary forms, the candidates returned to the caller
+const DICTIONARY_FORMS: u8 = V1 | V5 | VK | VS | ADJ_I;
+
+/// A deinflection rule: `kana_in` at the end of a form of a type in `types_in`
+/// (any type when 0, which only the query itself has) is replaced by `kana_out` of type `type_out`
+struct Rule {
+    kana_in: String,
+    kana_out: &'static str,
+    types_in: u8,
+    type_out: u8,
+}
+
+/// The i-stem of godan verbs, used by the polite and -tai forms
+const V5_I_STEMS: [(&str, &str); 9] = [
+    ("い", "う"), ("き", "く"), ("ぎ", "ぐ"), ("し", "す"), ("ち", "つ"),
+    ("に", "ぬ"), ("び", "ぶ"), ("み", "む"), ("り", "る"),
+];
+/// The a-stem of godan verbs, used by the negative, passive and causative forms
+const V5_A_STEMS: [(&str, &str); 9] = [
+    ("わ", "う"), ("か", "く"), ("が", "ぐ"), ("さ", "す"), ("た", "つ"),
+    ("な", "ぬ"), ("ば", "ぶ"), ("ま", "む"), ("ら", "る"),
+];
+/// The e-stem of godan verbs, used by the potential, imperative and conditional forms
+const V5_E_STEMS: [(&str, &str); 9] = [
+    ("え", "う"), ("け", "く"), ("げ", "ぐ"), ("せ", "す"), ("て", "つ"),
+    ("ね", "ぬ"), ("べ", "ぶ"), ("め", "む"), ("れ", "る"),
+];
+/// The o-stem of godan verbs, used by the volitional form
+const V5_O_STEMS: [(&str, &str); 9] = [
+    ("お", "う"), ("こ", "く"), ("ご", "ぐ"), ("そ", "す"), ("と", "つ"),
+    ("の", "ぬ"), ("ぼ", "ぶ"), ("も", "む"), ("ろ", "る"),
+];
+
+fn rule(kana_in: &str, kana_out: &'static str, types_in: u8, type_out: u8) -> Rule {
+    Rule { kana_in: kana_in.to_string(), kana_out, types_in, type_out }
+}
+
+/// Rules for an ending added to a verb stem: ichidan, godan (`v5_stems`), kuru and suru
+fn stem_rules(rules: &mut Vec<Rule>, ending: &str, v5_stems: &[(&str, &'static str)], vk_stem: &str, vs_stem: &str, types_in: u8) {
+    rules.push(rule(ending, "る", types_in, V1));
+    for (stem, out) in v5_stems {
+        rules.push(rule(&format!("{}{}", stem, ending), out, types_in, V5));
+    }
+    rules.push(rule(&format!("{}{}", vk_stem, ending), "くる", types_in, VK));
+    rules.push(rule(&format!("{}{}", vs_stem, ending), "する", types_in, VS));
+}
+
+/// Rules for the past (た) or te (て) form
+fn past_rules(rules: &mut Vec<Rule>, ta: &str, da: &str, types_in: u8) {
+    rules.push(rule(ta, "る", types_in, V1));
+    for out in ["う", "つ", "る"] {
+        rules.push(rule(&format!("っ{}", ta), out, types_in, V5));
+    }
+    rules.push(rule(&format!("い{}", ta), "く", types_in, V5));
+    rules.push(rule(&format!("い{}", da), "ぐ", types_in, V5));
+    rules.push(rule(&format!("し{}", ta), "す", types_in, V5));
+    for out in ["ぬ", "ぶ", "む"] {
+        rules.push(rule(&format!("ん{}", da), out, types_in, V5));
+    }
+    // 行く is irregular
+    rules.push(rule(&format!("いっ{}", ta), "いく", types_in, V5));
+    rules.push(rule(&format!("行っ{}", ta), "行く", types_in, V5));
+    rules.push(rule(&format!("き{}", ta), "くる", types_in, VK));
+    rules.push(rule(&format!("来{}", ta), "来る", types_in, VK));
+    rules.push(rule(&format!("し{}", ta), "する", types_in, VS));
+}
+
+static DEINFLECTION_RULES: Lazy<Vec<Rule>> = Lazy::new(deinflection_rules);
+
+fn deinflection_rules() -> Vec<Rule> {
+    let mut rules = Vec::new();
+    // Polite forms
+    for ending in ["ます", "ました", "ません", "ませんでした", "ましょう"] {
+        stem_rules(&mut rules, ending, &V5_I_STEMS, "き", "し", 0);
+    }
+    // Past and te forms, progressive 〜ている/〜てる is undone to the te form first
+    past_rules(&mut rules, "た", "だ", 0);
+    past_rules(&mut rules, "て", "で", TE);
+    for (progressive, te) in [("ている", "て"), ("でいる", "で"), ("てる", "て"), ("でる", "で")] {
+        rules.push(rule(progressive, te, V1, TE));
+    }
+    // Forms conjugating like an i-adjective
+    stem_rules(&mut rules, "ない", &V5_A_STEMS, "こ", "し", ADJ_I);
+    stem_rules(&mut rules, "たい", &V5_I_STEMS, "き", "し", ADJ_I);
+    // Passive, potential and causative forms conjugate like an ichidan verb
+    rules.push(rule("られる", "る", V1, V1));
+    rules.push(rule("させる", "る", V1, V1));
+    for (stem, out) in V5_A_STEMS {
+        rules.push(rule(&format!("{}れる", stem), out, V1, V5));
+        rules.push(rule(&format!("{}せる", stem), out, V1, V5));
+    }
+    for (stem, out) in V5_E_STEMS {
+        rules.push(rule(&format!("{}る", stem), out, V1, V5));
+    }
+    rules.push(rule("こられる", "くる", V1, VK));
+    rules.push(rule("こさせる", "くる", V1, VK));
+    rules.push(rule("される", "する", V1, VS));
+    rules.push(rule("させる", "する", V1, VS));
+    rules.push(rule("できる", "する", V1, VS));
+    // Volitional, imperative and conditional
+    stem_rules(&mut rules, "よう", &[], "こ", "し", 0);
+    for (stem, out) in V5_O_STEMS {
+        rules.push(rule(&format!("{}う", stem), out, 0, V5));
+    }
+    rules.push(rule("ろ", "る", 0, V1));
+    for (stem, out) in V5_E_STEMS {
+        rules.push(rule(stem, out, 0, V5));
+        rules.push(rule(&format!("{}ば", stem), out, 0, V5));
+    }
+    rules.push(rule("れば", "る", 0, V1));
+    rules.push(rule("こい", "くる", 0, VK));
+    rules.push(rule("くれば", "くる", 0, VK));
+    rules.push(rule("しろ", "する", 0, VS));
+    rules.push(rule("すれば", "する", 0, VS));
+    // i-adjectives
+    rules.push(rule("かった", "い", 0, ADJ_I));
+    rules.push(rule("くない", "い", ADJ_I, ADJ_I));
+    rules.push(rule("くて", "い", TE, ADJ_I));
+    rules.push(rule("ければ", "い", 0, ADJ_I));
+    rules.push(rule("く", "い", 0, ADJ_I));
+    rules.push(rule("さ", "い", 0, ADJ_I));
+    rules
+}
+
+/// Maximum number of rules applied in a chain, e.g. 食べたくなかった needs three
+const MAX_DEINFLECTION_DEPTH: usize = 6;
+
+/// Dictionary forms a conjugated word may come from, most direct first
+pub fn deinflect(word: &str) -> Vec<String> {
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current = vec![(word.to_string(), 0u8)];
+    for _ in 0..MAX_DEINFLECTION_DEPTH {
+        let mut next = Vec::new();
+        for (term, types) in &current {
+            for rule in DEINFLECTION_RULES.iter() {
+                // The query itself may be of any type
+                let applies = *types == 0 || types & rule.types_in != 0;
+                let Some(base) = term.strip_suffix(rule.kana_in.as_str()) else { continue };
+                if !applies {
+                    continue;
+                }
+                let deinflected = format!("{}{}", base, rule.kana_out);
+                if seen.insert((deinflected.clone(), rule.type_out)) {
+                    if rule.type_out & DICTIONARY_FORMS != 0 && deinflected != word && !results.contains(&deinflected) {
+                        results.push(deinflected.clone());
+                    }
+                    next.push((deinflected, rule.type_out));
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        current = next;
+    }
+    results
+}
+
+/// Forms of a query to look up when it isn't found as is: the hiragana of romaji,
+/// the other kana script, and the dictionary forms of conjugated words
+/// Romaji is only read with `romaji`, i.e. when a Japanese dictionary is searched, as words
+/// like "kite" are valid romaji too
+pub fn query_forms(query: &str, romaji: bool) -> Vec<String> {
+    let base = romaji_to_hiragana(query).filter(|_| romaji).unwrap_or_else(|| query.to_string());
+    if !is_japanese(&base) {
+        return Vec::new();
+    }
+    let hiragana = katakana_to_hiragana(&base);
+    let mut forms = vec![base.clone(), hiragana.clone(), hiragana_to_katakana(&base)];
+    forms.extend(deinflect(&base));
+    if hiragana != base {
+        forms.extend(deinflect(&hiragana));
+    }
+    let mut seen = HashSet::new();
+    forms.retain(|form| form != query && seen.insert(form.clone()));
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kana() {
+        assert_eq!(katakana_to_hiragana("カタカナー"), "かたかなー");
+        assert_eq!(hiragana_to_katakana("ひらがな"), "ヒラガナ");
+        assert_eq!(romaji_to_hiragana("taberu").as_deref(), Some("たべる"));
+        assert_eq!(romaji_to_hiragana("Kitte").as_deref(), Some("きって"));
+        assert_eq!(romaji_to_hiragana("shinbun").as_deref(), Some("しんぶん"));
+        assert_eq!(romaji_to_hiragana("kon'ya").as_deref(), Some("こんや"));
+        assert_eq!(romaji_to_hiragana("matcha").as_deref(), Some("まっちゃ"));
+        assert_eq!(romaji_to_hiragana("konnichiha").as_deref(), Some("こんにちは"));
+        assert_eq!(romaji_to_hiragana("hello"), None);
+        assert_eq!(romaji_to_hiragana("食べる"), None);
+    }
+
+    #[test]
+    fn test_deinflect() {
+        assert!(deinflect("食べました").contains(&"食べる".to_string()));
+        assert!(deinflect("高かった").contains(&"高い".to_string()));
+        assert!(deinflect("書いた").contains(&"書く".to_string()));
+        assert!(deinflect("読んでいる").contains(&"読む".to_string()));
+        assert!(deinflect("食べたくなかった").contains(&"食べる".to_string()));
+        assert!(deinflect("行かない").contains(&"行く".to_string()));
+        assert!(deinflect("した").contains(&"する".to_string()));
+        assert!(deinflect("食べる").iter().all(|form| form != "食べる"));
+
+        let forms = query_forms("tabemashita", true);
+        assert_eq!(forms[0], "たべました");
+        assert!(forms.contains(&"たべる".to_string()));
+        assert!(query_forms("tabemashita", false).is_empty());
+        assert!(query_forms("kite", false).is_empty());
+        assert!(query_forms("タベル", false).contains(&"たべる".to_string()));
+        assert!(query_forms("hello", true).is_empty());
+    }
+}
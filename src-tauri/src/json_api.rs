@@ -0,0 +1,290 @@
+// JSON API module - versioned JSON endpoints for external applications
+// Served by the HTTP server under /api/v1/, the same builders are used by the CLI's json output
+//
+//   GET /api/v1/lookup?word=&format=html|text   headword lookup with entry content
+//   GET /api/v1/index?word=                     headword lookup, indexes grouped by dictionary
+//   GET /api/v1/fts?query=&max_results=         full-text search with scores
+//   GET /api/v1/suggest?word=&max_results=      spelling suggestions for a headword
+//   GET /api/v1/keys?start=&count=[&query=]     key list paging, optionally from the best match of `query`
+//   GET /api/v1/library                         dictionaries and groups
+//
+// The lookup endpoints take an optional `session_id`, the default session is used without it
+// The key list pages its own results without `session_id`, so API calls never change the GUI's search results
+use http::{Response, StatusCode};
+use log::error;
+use url::Url;
+
+use crate::api_types::*;
+use crate::error::{invalid_argument, CommandError, ErrorCode, Result, ZdbError};
+use crate::error_printer::format_error;
+use crate::fts_query::FtsQuery;
+use crate::html_text::html_to_text;
+use crate::lookup_context::LookupContext;
+use crate::mdict_app::{with_lookup_context, with_lookup_morphology, with_read_access, MdictApp};
+use crate::morphology::Morphology;
+use crate::session_mgr::SessionId;
+
+/// Default number of results per dictionary for full-text search
+const DEFAULT_FTS_MAX_RESULTS: usize = 200;
+/// Default number of spelling suggestions
+const DEFAULT_SUGGEST_MAX_RESULTS: usize = 10;
+/// Default and maximum page size of the key list
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Look up a headword and return the content of all matching entries
+/// An inflected form missing from the dictionaries is looked up by its stem
+pub fn lookup(context: &LookupContext, morphology: &Morphology, word: &str, format: ContentFormat, base_url: &str) -> Result<LookupResponse> {
+    let mut entries = Vec::new();
+    let (group_indexes, stem) = context.find_index_or_stem(word, morphology)?;
+    for group_index in group_indexes {
+        let dictionary = context.db.find_profile(group_index.profile_id)
+            .map(|p| p.title.clone())
+            .unwrap_or_default();
+        for index in group_index.indexes.iter() {
+            let (resolved, html) = context.get_resolved_entry(index, base_url)?;
+            entries.push(EntryContent {
+                profile_id: index.profile_id,
+                dictionary: dictionary.clone(),
+                entry_no: index.key_index.entry_no,
+                key: index.key_index.key.clone(),
+                resolved_key: Some(resolved.key_index.key).filter(|_| resolved.key_index.entry_no != index.key_index.entry_no),
+                content: match format {
+                    ContentFormat::Html => html,
+                    ContentFormat::Text => html_to_text(&html),
+                },
+            });
+        }
+    }
+    Ok(LookupResponse {
+        api_version: API_VERSION.to_string(),
+        query: word.to_string(),
+        stem,
+        format,
+        entries,
+    })
+}
+
+/// Look up a headword and return the matching indexes grouped by dictionary
+pub fn index_lookup(context: &LookupContext, morphology: &Morphology, word: &str) -> Result<IndexResponse> {
+    let (group_indexes, stem) = context.find_index_or_stem(word, morphology)?;
+    Ok(IndexResponse {
+        api_version: API_VERSION.to_string(),
+        query: word.to_string(),
+        stem,
+        results: context.group_entries(&group_indexes),
+    })
+}
+
+/// Full-text search in the current dictionary or group, see `FtsQuery` for the query syntax
+pub fn fulltext_search(context: &LookupContext, query: &str, max_results: usize) -> Result<FtsResponse> {
+    let parsed = FtsQuery::parse(query)?;
+    let terms = parsed.terms();
+    let hits = context.fulltext_hits(&parsed, max_results)?
+        .into_iter()
+        .map(|(score, index)| FtsHit {
+            score,
+            snippet: context.entry_snippet(&index, &terms),
+            profile_id: index.profile_id,
+            entry_no: index.key_index.entry_no,
+            key: index.key_index.key,
+        })
+        .collect();
+    Ok(FtsResponse {
+        api_version: API_VERSION.to_string(),
+        query: query.to_string(),
+        hits,
+    })
+}
+
+/// Spelling suggestions for a headword that is not found
+pub fn suggest(context: &LookupContext, word: &str, max_results: usize) -> Result<SuggestResponse> {
+    Ok(SuggestResponse {
+        api_version: API_VERSION.to_string(),
+        query: word.to_string(),
+        suggestions: context.suggest(word, max_results)?,
+    })
+}
+
+/// Get a page of the key list
+/// With `query` an incremental search is done first and `start` is relative to its best match
+pub fn key_list(context: &LookupContext, query: Option<&str>, start: usize, count: usize) -> Result<KeyListResponse> {
+    let (origin, total) = match query {
+        Some(query) => match context.incremental_search(query, count.max(DEFAULT_PAGE_SIZE))? {
+            Some((start_entry, total)) => (start_entry.max(0) as usize, total),
+            None => (0, 0),
+        },
+        None => (0, context.get_entry_count()),
+    };
+
+    let first = origin + start;
+    let mut items = Vec::new();
+    // The key list of a group continues past the first window
+    if (first < total || context.has_more_results()) && count > 0 {
+        for (offset, result_key) in context.get_result_key_list(first as i64, count)?.into_iter().enumerate() {
+            items.push(KeyListItem {
+                index_no: first + offset,
+                key: result_key.key,
+                count: result_key.count,
+                score: result_key.score,
+                snippet: result_key.snippet,
+                match_kind: result_key.match_kind,
+            });
+        }
+    }
+    // Continued keys are added to the cached results
+    let total = if total > 0 { total.max(context.get_entry_count()) } else { total };
+    Ok(KeyListResponse {
+        api_version: API_VERSION.to_string(),
+        start: first,
+        total,
+        has_more: context.has_more_results(),
+        items,
+    })
+}
+
+/// List the dictionaries and groups of the library
+pub fn library(app: &MdictApp) -> Result<LibraryResponse> {
+    Ok(LibraryResponse {
+        api_version: API_VERSION.to_string(),
+        current_profile_id: app.get_current_main_profile_id()?,
+        groups: app.library_manager.get_groups().iter().map(ProfileInfo::from).collect(),
+    })
+}
+
+fn param(url: &Url, key: &str) -> Option<String> {
+    url.query_pairs().find(|(k, _)| k == key).map(|(_, v)| v.to_string())
+}
+
+fn required_param(url: &Url, key: &str) -> Result<String> {
+    param(url, key).ok_or_else(|| invalid_argument(format!("Parameter {} not found", key)))
+}
+
+fn number_param(url: &Url, key: &str, default: usize) -> Result<usize> {
+    match param(url, key) {
+        Some(value) => value.parse()
+            .map_err(|_| invalid_argument(format!("Invalid number for {}: {}", key, value))),
+        None => Ok(default),
+    }
+}
+
+fn json_response<T: serde::Serialize>(status: StatusCode, body: &T) -> Response<Vec<u8>> {
+    let data = serde_json::to_vec(body).unwrap_or_default();
+    let mut response = Response::new(data);
+    *response.status_mut() = status;
+    response.headers_mut().insert("Content-Type", "application/json; charset=utf-8".parse().unwrap());
+    response
+}
+
+fn error_response(code: ErrorCode, message: String) -> Response<Vec<u8>> {
+    json_response(status_of(code), &ErrorResponse {
+        api_version: API_VERSION.to_string(),
+        code,
+        error: message,
+    })
+}
+
+/// HTTP status of an error: 400 for bad parameters or queries, 404 for things that don't exist
+pub fn status_of(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::InvalidArgument | ErrorCode::InvalidQuery => StatusCode::BAD_REQUEST,
+        ErrorCode::NotFound | ErrorCode::ProfileNotFound | ErrorCode::SessionNotFound => StatusCode::NOT_FOUND,
+        ErrorCode::NoDatabaseOpen => StatusCode::CONFLICT,
+        ErrorCode::Io | ErrorCode::Cancelled | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn zdb_error_response(error: &ZdbError) -> Response<Vec<u8>> {
+    let error = CommandError::from(error);
+    error_response(error.code, error.details.unwrap_or(error.message))
+}
+
+/// Parsed API request
+enum ApiRequest {
+    Lookup { word: String, format: ContentFormat },
+    Index { word: String },
+    Fts { query: String, max_results: usize },
+    Suggest { word: String, max_results: usize },
+    Keys { query: Option<String>, start: usize, count: usize },
+    Library,
+}
+
+impl ApiRequest {
+    /// Parse the endpoint and its parameters, returns Ok(None) for unknown endpoints
+    fn parse(endpoint: &str, url: &Url) -> Result<Option<ApiRequest>> {
+        Ok(Some(match endpoint {
+            "lookup" => ApiRequest::Lookup {
+                word: required_param(url, "word")?,
+                format: match param(url, "format") {
+                    Some(value) => ContentFormat::from_str(&value)
+                        .ok_or_else(|| invalid_argument(format!("Invalid format: {}", value)))?,
+                    None => ContentFormat::default(),
+                },
+            },
+            "index" => ApiRequest::Index { word: required_param(url, "word")? },
+            "fts" => ApiRequest::Fts {
+                query: required_param(url, "query")?,
+                max_results: number_param(url, "max_results", DEFAULT_FTS_MAX_RESULTS)?,
+            },
+            "suggest" => ApiRequest::Suggest {
+                word: required_param(url, "word")?,
+                max_results: number_param(url, "max_results", DEFAULT_SUGGEST_MAX_RESULTS)?,
+            },
+            "keys" => ApiRequest::Keys {
+                query: param(url, "query"),
+                start: number_param(url, "start", 0)?,
+                count: number_param(url, "count", DEFAULT_PAGE_SIZE)?.min(MAX_PAGE_SIZE),
+            },
+            "library" => ApiRequest::Library,
+            _ => return Ok(None),
+        }))
+    }
+
+    fn execute(self, session_id: Option<SessionId>, base_url: &str) -> Result<Response<Vec<u8>>> {
+        Ok(match self {
+            ApiRequest::Lookup { word, format } => json_response(StatusCode::OK,
+                &with_lookup_morphology(session_id, |context, morphology| lookup(context, morphology, &word, format, base_url))?),
+            ApiRequest::Index { word } => json_response(StatusCode::OK,
+                &with_lookup_morphology(session_id, |context, morphology| index_lookup(context, morphology, &word))?),
+            ApiRequest::Fts { query, max_results } => json_response(StatusCode::OK,
+                &with_lookup_context(session_id, |context| fulltext_search(context, &query, max_results))?),
+            ApiRequest::Suggest { word, max_results } => json_response(StatusCode::OK,
+                &with_lookup_context(session_id, |context| suggest(context, &word, max_results))?),
+            ApiRequest::Keys { query, start, count } => json_response(StatusCode::OK,
+                &with_lookup_context(session_id, |context| match session_id {
+                    Some(_) => key_list(context, query.as_deref(), start, count),
+                    None => key_list(&context.detached(), query.as_deref(), start, count),
+                })?),
+            ApiRequest::Library => json_response(StatusCode::OK, &with_read_access(library)?),
+        })
+    }
+}
+
+/// Handle a JSON API request, `url` path is like /api/v1/lookup
+/// `base_url` is the service URL used for resource links in entry content
+pub fn handle_api_request(url: &Url, base_url: &str) -> Response<Vec<u8>> {
+    let path = url.path().strip_prefix("/api/").unwrap_or("");
+    let (version, endpoint) = path.split_once('/').unwrap_or((path, ""));
+    if version != API_VERSION {
+        return error_response(ErrorCode::NotFound, format!("Unsupported API version: {}", version));
+    }
+
+    let request = match ApiRequest::parse(endpoint, url) {
+        Ok(Some(request)) => request,
+        Ok(None) => return error_response(ErrorCode::NotFound, format!("Unknown endpoint: {}", endpoint)),
+        Err(e) => return zdb_error_response(&e),
+    };
+    let session_id = match param(url, "session_id").map(|id| id.parse::<SessionId>()).transpose() {
+        Ok(session_id) => session_id,
+        Err(_) => return error_response(ErrorCode::InvalidArgument, "Invalid session_id".to_string()),
+    };
+    match request.execute(session_id, base_url) {
+        Ok(response) => response,
+        Err(e) => {
+            if status_of(ErrorCode::of(&e)).is_server_error() {
+                error!("JSON API request {} failed: {}", url, format_error(&e));
+            }
+            zdb_error_response(&e)
+        }
+    }
+}
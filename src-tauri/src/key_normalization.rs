@@ -0,0 +1,98 @@
+// Key normalization module - how headwords of different dictionaries are merged into one result
+// The policy is an option of the group (or dictionary) profile, each level folds more than the one before
+use icu_normalizer::{ComposingNormalizerBorrowed, DecomposingNormalizerBorrowed};
+use serde::{Deserialize, Serialize};
+
+use crate::chinese_conv::ChineseConverter;
+use crate::japanese;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyNormalization {
+    /// Keys are merged only when they are identical
+    Strict,
+    /// Ignore case, repeated spaces, katakana/hiragana and (with Chinese conversion) Simplified/Traditional
+    #[default]
+    CaseFold,
+    /// Case-fold and ignore diacritics: "résumé" = "resume"
+    DiacriticFold,
+    /// Diacritic-fold and ignore punctuation and spaces: "co-op" = "coop", digits are kept
+    PunctuationInsensitive,
+}
+
+impl KeyNormalization {
+    /// Key used to merge headwords and to order the merged results
+    /// Traditional characters are folded to Simplified when a converter is given
+    pub fn normalize(self, key: &str, converter: Option<&ChineseConverter>) -> String {
+        if self == KeyNormalization::Strict {
+            return key.to_string();
+        }
+        let folded = fold_case(key, converter);
+        let folded = match self {
+            KeyNormalization::DiacriticFold | KeyNormalization::PunctuationInsensitive => strip_diacritics(&folded),
+            _ => folded,
+        };
+        if self == KeyNormalization::PunctuationInsensitive {
+            folded.chars().filter(|c| c.is_alphanumeric()).collect()
+        } else {
+            folded
+        }
+    }
+}
+
+/// Lowercase with spaces collapsed, katakana folded to hiragana and Traditional characters to Simplified
+fn fold_case(key: &str, converter: Option<&ChineseConverter>) -> String {
+    let key = key.split_whitespace().collect::<Vec<_>>().join(" ");
+    key.chars()
+        .map(japanese::katakana_to_hiragana_char)
+        .map(|c| match converter {
+            Some(converter) => converter.to_simplified_char(c),
+            None => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Combining diacritical mark blocks, other nonspacing marks (kana voicing marks, Indic vowel signs)
+/// change the letter rather than decorate it and are kept
+fn is_diacritic(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}')
+}
+
+/// Remove diacritics by decomposing (NFD), dropping the marks and composing again (NFC)
+fn strip_diacritics(key: &str) -> String {
+    let decomposed = DecomposingNormalizerBorrowed::new_nfd().normalize(key);
+    let stripped: String = decomposed.chars().filter(|c| !is_diacritic(*c)).collect();
+    ComposingNormalizerBorrowed::new_nfc().normalize(&stripped).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(KeyNormalization::Strict.normalize("Résumé", None), "Résumé");
+        assert_eq!(KeyNormalization::CaseFold.normalize("Résumé", None), "résumé");
+        assert_eq!(KeyNormalization::CaseFold.normalize(" New  York ", None), "new york");
+        assert_eq!(KeyNormalization::CaseFold.normalize("カタカナ", None), "かたかな");
+        assert_eq!(KeyNormalization::DiacriticFold.normalize("Résumé", None), "resume");
+        assert_eq!(KeyNormalization::DiacriticFold.normalize("Ärger", None), "arger");
+        // Voicing marks are part of the kana
+        assert_eq!(KeyNormalization::DiacriticFold.normalize("ガ", None), "が");
+        assert_eq!(KeyNormalization::DiacriticFold.normalize("re-sign", None), "re-sign");
+        assert_eq!(KeyNormalization::PunctuationInsensitive.normalize("re-sign", None), "resign");
+        assert_eq!(KeyNormalization::PunctuationInsensitive.normalize("3D", None), "3d");
+        let converter = ChineseConverter::bundled();
+        assert_eq!(KeyNormalization::CaseFold.normalize("頭髮", Some(&converter)), "头发");
+        assert_eq!(KeyNormalization::CaseFold.normalize("頭髮", None), "頭髮");
+        assert_eq!(KeyNormalization::Strict.normalize("頭髮", Some(&converter)), "頭髮");
+    }
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(serde_json::to_string(&KeyNormalization::DiacriticFold).unwrap(), "\"diacriticFold\"");
+        let policy: KeyNormalization = serde_json::from_str("\"punctuationInsensitive\"").unwrap();
+        assert_eq!(policy, KeyNormalization::PunctuationInsensitive);
+    }
+}
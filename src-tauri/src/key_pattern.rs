@@ -0,0 +1,131 @@
+// Key pattern module - wildcard (`*`, `?`) and regex patterns for headword search
+// Patterns match case-insensitively, the literal prefix lets a scan start at the
+// matching range of the sorted key list instead of reading every headword
+use regex::{Regex, RegexBuilder};
+
+use crate::error::{invalid_argument, Result};
+
+/// Characters with a special meaning in a regex, a literal prefix stops at any of them
+const REGEX_META_CHARS: &str = "\\.+*?()|[]{}^$";
+
+#[derive(Debug, Clone)]
+pub struct KeyPattern {
+    regex: Regex,
+    literal_prefix: String,
+}
+
+impl KeyPattern {
+    /// Whether a query uses wildcards and should be searched as a pattern
+    pub fn is_wildcard(query: &str) -> bool {
+        query.contains(['*', '?'])
+    }
+
+    /// Wildcard pattern, `*` matches any characters and `?` one character, the whole key must match
+    pub fn wildcard(pattern: &str) -> Result<Self> {
+        let mut regex = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        let literal_prefix = pattern.chars().take_while(|c| *c != '*' && *c != '?').collect();
+        Ok(Self { regex: Self::build(&regex)?, literal_prefix })
+    }
+
+    /// Regular expression, matches anywhere in the key unless anchored with `^`/`$`
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let mut literal_prefix = String::new();
+        // With an alternation like `^ab|cd` a match may start with another branch
+        if let Some(rest) = pattern.strip_prefix('^')
+            && !Self::has_top_level_alternation(pattern)
+        {
+            let literal: Vec<char> = rest.chars().take_while(|c| !REGEX_META_CHARS.contains(*c)).collect();
+            // A quantifier after the literal applies to its last character
+            let quantified = rest.chars().nth(literal.len()).is_some_and(|c| "?*{".contains(c));
+            let len = if quantified { literal.len().saturating_sub(1) } else { literal.len() };
+            literal_prefix = literal[..len].iter().collect();
+        }
+        Ok(Self { regex: Self::build(pattern)?, literal_prefix })
+    }
+
+    /// Whether a regex has a `|` outside of groups and character classes
+    fn has_top_level_alternation(pattern: &str) -> bool {
+        let mut depth = 0usize;
+        let mut in_class = false;
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                // `]` right after `[` or `[^` is a literal
+                '[' if !in_class => {
+                    in_class = true;
+                    chars.next_if_eq(&'^');
+                    chars.next_if_eq(&']');
+                }
+                ']' if in_class => in_class = false,
+                '(' if !in_class => depth += 1,
+                ')' if !in_class => depth = depth.saturating_sub(1),
+                '|' if !in_class && depth == 0 => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn build(pattern: &str) -> Result<Regex> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| invalid_argument(format!("Invalid pattern {}: {}", pattern, e)))
+    }
+
+    /// Literal text every matching key starts with, may be empty
+    pub fn literal_prefix(&self) -> &str {
+        &self.literal_prefix
+    }
+
+    pub fn is_match(&self, key: &str) -> bool {
+        self.regex.is_match(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard() {
+        let pattern = KeyPattern::wildcard("*ology").unwrap();
+        assert_eq!(pattern.literal_prefix(), "");
+        assert!(pattern.is_match("Biology"));
+        assert!(!pattern.is_match("ologyst"));
+
+        let pattern = KeyPattern::wildcard("h?ck").unwrap();
+        assert_eq!(pattern.literal_prefix(), "h");
+        assert!(pattern.is_match("hack") && pattern.is_match("Heck"));
+        assert!(!pattern.is_match("hacks"));
+
+        // Regex characters are literal in wildcards
+        assert!(KeyPattern::wildcard("a.b*").unwrap().is_match("a.bc"));
+        assert!(!KeyPattern::wildcard("a.b*").unwrap().is_match("axbc"));
+        assert!(KeyPattern::is_wildcard("h?ck") && !KeyPattern::is_wildcard("hack"));
+    }
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(KeyPattern::regex("^colou?r$").unwrap().literal_prefix(), "colo");
+        assert_eq!(KeyPattern::regex("^ab.*").unwrap().literal_prefix(), "ab");
+        assert_eq!(KeyPattern::regex("ology$").unwrap().literal_prefix(), "");
+        assert_eq!(KeyPattern::regex("^ab|cd").unwrap().literal_prefix(), "");
+        assert!(KeyPattern::regex("^ab|cd").unwrap().is_match("cdx"));
+        assert_eq!(KeyPattern::regex("^ab(c|d)").unwrap().literal_prefix(), "ab");
+        assert_eq!(KeyPattern::regex(r"^ab[|]\|").unwrap().literal_prefix(), "ab");
+        assert!(KeyPattern::regex("ology$").unwrap().is_match("Biology"));
+        assert!(KeyPattern::regex("(").is_err());
+    }
+}
@@ -0,0 +1,278 @@
+// Language module - languages of dictionaries and scripts of queries
+// A group only searches the libraries whose headwords (for full-text search also definitions) can be
+// written in the scripts of the query, so a Cyrillic query skips English-Chinese dictionaries.
+// Languages are BCP 47 codes, set in the options of a library or detected from its header and headwords
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+
+use crate::chinese_conv;
+use crate::japanese;
+
+/// Share of the sampled headwords a script needs to count as a script of the dictionary
+const MIN_SCRIPT_SHARE: f32 = 0.05;
+
+/// Writing systems told apart by the routing
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Georgian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    /// Hiragana and katakana
+    Kana,
+    Han,
+}
+
+/// Script of a letter, None for digits, punctuation, symbols and marks shared by all scripts
+pub fn script_of(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    if chinese_conv::is_han(c) {
+        return Some(Script::Han);
+    }
+    Some(match c {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{0250}'..='\u{02AF}' | '\u{1E00}'..='\u{1EFF}' => Script::Latin,
+        '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Script::Greek,
+        '\u{0400}'..='\u{052F}' => Script::Cyrillic,
+        '\u{0530}'..='\u{058F}' => Script::Armenian,
+        '\u{10A0}'..='\u{10FF}' => Script::Georgian,
+        '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+        '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' | '\u{FB50}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}' => Script::Arabic,
+        '\u{0900}'..='\u{097F}' => Script::Devanagari,
+        '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+        '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => Script::Hangul,
+        '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => Script::Kana,
+        _ => return None,
+    })
+}
+
+/// Scripts of the letters of a text, sorted
+pub fn scripts_of(text: &str) -> Vec<Script> {
+    text.chars().filter_map(script_of).collect::<BTreeSet<_>>().into_iter().collect()
+}
+
+/// Scripts a language is written in, None for a language not known here (it may use any script)
+/// A script subtag ("sr-Latn", "zh-Hant") is taken into account
+pub fn language_scripts(language: &str) -> Option<Vec<Script>> {
+    let mut subtags = language.split(['-', '_']);
+    let primary = subtags.next()?.to_ascii_lowercase();
+    let script = subtags.find(|subtag| subtag.len() == 4).map(|subtag| subtag.to_ascii_lowercase());
+    use Script::*;
+    let scripts: &[Script] = match (primary.as_str(), script.as_deref()) {
+        ("ja", _) => &[Han, Kana],
+        ("ko", _) => &[Hangul, Han],
+        (_, Some("latn")) => &[Latin],
+        (_, Some("cyrl")) => &[Cyrillic],
+        (_, Some("arab")) => &[Arabic],
+        ("zh", _) => &[Han],
+        ("sr", _) => &[Cyrillic, Latin],
+        ("ru" | "uk" | "be" | "bg" | "mk" | "kk" | "ky" | "tg" | "mn", _) => &[Cyrillic],
+        ("el", _) => &[Greek],
+        ("hy", _) => &[Armenian],
+        ("ka", _) => &[Georgian],
+        ("he" | "yi", _) => &[Hebrew],
+        ("ar" | "fa" | "ur" | "ps", _) => &[Arabic],
+        ("hi" | "mr" | "ne" | "sa", _) => &[Devanagari],
+        ("th", _) => &[Thai],
+        ("en" | "fr" | "de" | "es" | "it" | "pt" | "nl" | "sv" | "da" | "no" | "nb" | "nn" | "is" | "fi" | "et"
+            | "lv" | "lt" | "pl" | "cs" | "sk" | "sl" | "hr" | "bs" | "hu" | "ro" | "tr" | "az" | "sq" | "ca"
+            | "gl" | "eu" | "ga" | "cy" | "la" | "eo" | "id" | "ms" | "vi" | "tl" | "sw" | "af" | "mt" | "uz", _) => &[Latin],
+        _ => return None,
+    };
+    Some(scripts.to_vec())
+}
+
+/// English names of languages as they appear in dictionary titles
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("english", "en"), ("chinese", "zh"), ("japanese", "ja"), ("korean", "ko"), ("russian", "ru"),
+    ("ukrainian", "uk"), ("french", "fr"), ("german", "de"), ("spanish", "es"), ("italian", "it"),
+    ("portuguese", "pt"), ("dutch", "nl"), ("swedish", "sv"), ("polish", "pl"), ("turkish", "tr"),
+    ("greek", "el"), ("latin", "la"), ("arabic", "ar"), ("persian", "fa"), ("hebrew", "he"),
+    ("hindi", "hi"), ("thai", "th"), ("vietnamese", "vi"),
+];
+
+/// Chinese abbreviations of languages, only recognized in pairs like "英汉" or "汉日"
+const LANGUAGE_ABBREVIATIONS: &[(char, &str)] = &[
+    ('英', "en"), ('汉', "zh"), ('漢', "zh"), ('中', "zh"), ('日', "ja"), ('韩', "ko"), ('韓', "ko"),
+    ('俄', "ru"), ('法', "fr"), ('德', "de"), ('西', "es"), ('意', "it"), ('葡', "pt"), ('阿', "ar"),
+];
+
+fn abbreviation(c: char) -> Option<&'static str> {
+    LANGUAGE_ABBREVIATIONS.iter().find(|(abbreviation, _)| *abbreviation == c).map(|(_, code)| *code)
+}
+
+/// Source and target languages named in a title or description, like "English-Chinese" or "英汉"
+/// The first language named is the source, the second one the target
+pub fn languages_from_header(text: &str) -> (Option<&'static str>, Option<&'static str>) {
+    let lowercase = text.to_lowercase();
+    let mut found: Vec<(usize, &'static str)> = Vec::new();
+    for (name, code) in LANGUAGE_NAMES {
+        found.extend(lowercase.match_indices(name).map(|(pos, _)| (pos, *code)));
+    }
+    let chars: Vec<(usize, char)> = lowercase.char_indices().collect();
+    for pair in chars.windows(2) {
+        if let (Some(source), Some(target)) = (abbreviation(pair[0].1), abbreviation(pair[1].1)) {
+            found.push((pair[0].0, source));
+            found.push((pair[1].0, target));
+        }
+    }
+    found.sort();
+    found.dedup();
+    (found.first().map(|(_, code)| *code), found.get(1).map(|(_, code)| *code))
+}
+
+/// Scripts used by at least `MIN_SCRIPT_SHARE` of the headwords having letters
+pub fn headword_scripts<'a>(headwords: impl IntoIterator<Item = &'a str>) -> Vec<Script> {
+    let mut counts = std::collections::BTreeMap::<Script, usize>::new();
+    let mut total = 0;
+    for headword in headwords {
+        let scripts = scripts_of(headword);
+        if scripts.is_empty() {
+            continue;
+        }
+        total += 1;
+        for script in scripts {
+            *counts.entry(script).or_default() += 1;
+        }
+    }
+    counts.into_iter()
+        .filter(|(_, count)| *count as f32 >= total as f32 * MIN_SCRIPT_SHARE)
+        .map(|(script, _)| script)
+        .collect()
+}
+
+/// Languages of a dictionary and the scripts of its headwords
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Languages {
+    /// Language of the headwords, empty when unknown
+    pub source_language: String,
+    /// Language of the definitions, empty when unknown
+    pub target_language: String,
+    /// Scripts of the headwords, empty when unknown
+    pub headword_scripts: Vec<Script>,
+}
+
+impl Languages {
+    /// Languages from the options (`source`, `target`, empty when not set) with the detected ones as fallback
+    /// The headword scripts are those of a set source language, otherwise those of the sampled headwords,
+    /// otherwise those of the language named in the header
+    pub fn detect(source: &str, target: &str, header: &str, sampled_scripts: Vec<Script>) -> Self {
+        let (header_source, header_target) = languages_from_header(header);
+        let source_language = if source.is_empty() { header_source.unwrap_or_default().to_string() } else { source.to_string() };
+        let target_language = if target.is_empty() { header_target.unwrap_or_default().to_string() } else { target.to_string() };
+        let headword_scripts = match language_scripts(source) {
+            Some(scripts) => scripts,
+            None if !sampled_scripts.is_empty() => sampled_scripts,
+            None => language_scripts(&source_language).unwrap_or_default(),
+        };
+        Self { source_language, target_language, headword_scripts }
+    }
+
+    /// Whether a query written in `scripts` can be a headword of the dictionary, or with `in_text`
+    /// a word of its headwords or definitions. Unknown languages can contain anything
+    pub fn can_contain(&self, scripts: &[Script], in_text: bool) -> bool {
+        if scripts.is_empty() || self.headword_scripts.is_empty() {
+            return true;
+        }
+        let mut known = self.headword_scripts.clone();
+        if in_text {
+            match language_scripts(&self.target_language) {
+                Some(target_scripts) => known.extend(target_scripts),
+                None => return true,
+            }
+        }
+        scripts.iter().all(|script| known.contains(script))
+    }
+
+    /// Whether the headwords can be Chinese: the source language is Chinese, or it is unknown and the
+    /// headwords are written in Han characters without kana or Hangul. Unknown scripts can be anything
+    pub fn can_be_chinese(&self) -> bool {
+        if !self.source_language.is_empty() {
+            return self.source_language.split(['-', '_']).next().is_some_and(|primary| primary.eq_ignore_ascii_case("zh"));
+        }
+        self.headword_scripts.is_empty()
+            || (self.headword_scripts.contains(&Script::Han)
+                && !self.headword_scripts.iter().any(|script| matches!(script, Script::Kana | Script::Hangul)))
+    }
+
+    /// The form in which a headword query is looked up: romaji the headwords can't contain is read as
+    /// hiragana when they can be kana (like `japanese::query_forms`), other queries are looked up as they are
+    pub fn headword_query<'a>(&self, query: &'a str) -> Cow<'a, str> {
+        if !self.can_contain(&scripts_of(query), false)
+            && let Some(kana) = japanese::romaji_to_hiragana(query)
+            && self.can_contain(&scripts_of(&kana), false)
+        {
+            return Cow::Owned(kana);
+        }
+        Cow::Borrowed(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts() {
+        assert_eq!(scripts_of("DNA测序 2"), vec![Script::Latin, Script::Han]);
+        assert_eq!(scripts_of("食べる"), vec![Script::Kana, Script::Han]);
+        assert_eq!(scripts_of("привет!"), vec![Script::Cyrillic]);
+        assert_eq!(language_scripts("sr-Latn"), Some(vec![Script::Latin]));
+        assert_eq!(language_scripts("zh-Hant"), Some(vec![Script::Han]));
+        assert_eq!(language_scripts("xx"), None);
+    }
+
+    #[test]
+    fn test_languages_from_header() {
+        assert_eq!(languages_from_header("Oxford English-Chinese Dictionary"), (Some("en"), Some("zh")));
+        assert_eq!(languages_from_header("新世纪汉英大词典"), (Some("zh"), Some("en")));
+        assert_eq!(languages_from_header("Collins COBUILD"), (None, None));
+    }
+
+    #[test]
+    fn test_can_contain() {
+        let sampled = headword_scripts(["colour", "DNA", "naïve", "x-ray"]);
+        let languages = Languages::detect("", "", "English-Chinese Dictionary", sampled);
+        assert_eq!(languages.headword_scripts, vec![Script::Latin]);
+        assert!(languages.can_contain(&scripts_of("colour"), false));
+        assert!(!languages.can_contain(&scripts_of("цвет"), false));
+        assert!(!languages.can_contain(&scripts_of("颜色"), false));
+        assert!(languages.can_contain(&scripts_of("颜色"), true));
+        assert!(languages.can_contain(&scripts_of("123"), false));
+
+        let russian = Languages::detect("ru", "", "", Vec::new());
+        assert!(russian.can_contain(&scripts_of("цвет"), false));
+        assert!(russian.can_contain(&scripts_of("colour"), true));
+        assert!(Languages::default().can_contain(&scripts_of("цвет"), false));
+    }
+
+    #[test]
+    fn test_can_be_chinese() {
+        assert!(!Languages::detect("", "", "English-Chinese Dictionary", Vec::new()).can_be_chinese());
+        assert!(Languages::detect("", "", "新世纪汉英大词典", Vec::new()).can_be_chinese());
+        assert!(Languages::detect("zh-Hant", "", "", Vec::new()).can_be_chinese());
+        assert!(Languages::detect("", "", "", headword_scripts(["中国", "头发"])).can_be_chinese());
+        assert!(!Languages::detect("", "", "", headword_scripts(["食べる", "たべる"])).can_be_chinese());
+        assert!(Languages::default().can_be_chinese());
+    }
+
+    #[test]
+    fn test_headword_query() {
+        let japanese = Languages::detect("", "", "", headword_scripts(["食べる", "たべる"]));
+        assert_eq!(japanese.headword_query("taberu"), "たべる");
+        assert_eq!(japanese.headword_query("hello"), "hello");
+        let english = Languages::detect("", "", "English-Chinese Dictionary", headword_scripts(["colour"]));
+        assert_eq!(english.headword_query("taberu"), "taberu");
+        assert_eq!(Languages::default().headword_query("taberu"), "taberu");
+    }
+}
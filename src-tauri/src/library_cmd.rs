@@ -1,10 +1,14 @@
 // Library commands module - Tauri command implementations for library and group management
+use icu_locale::Locale;
 use serde::{Deserialize, Serialize};
 use tauri::command;
 
-use crate::error::{IntoStringResult, ZdbError};
-use crate::mdict_app::{with_read_access, with_write_access};
+use crate::error::{invalid_argument, profile_not_found, CommandResult, IntoCommandResult, ZdbError};
+use crate::mdict_app::{with_lookup_context, with_read_access, with_write_access};
+use crate::key_normalization::KeyNormalization;
+use crate::language::Languages;
 use crate::mdx_profile::ProfileId;
+use crate::session_mgr::SessionId;
 
 /// Collation options for rebuilding index
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,19 +24,19 @@ pub struct CollationOptions {
 
 /// Create a dictionary group
 #[command]
-pub async fn library_create_dict_group(group_name: String) -> std::result::Result<ProfileId, String> {
+pub async fn library_create_dict_group(group_name: String) -> CommandResult<ProfileId> {
     with_write_access(|app| {
         let group = app.library_manager.create_group(group_name);
         let group_id = group.profile_id;
         // Save to file
         app.library_manager.save_library()?;
         Ok(group_id)
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Delete a dictionary group
 #[command]
-pub async fn library_delete_dict_group(group_id: ProfileId) -> std::result::Result<bool, String> {
+pub async fn library_delete_dict_group(group_id: ProfileId) -> CommandResult<bool> {
     with_write_access(|app| {
         if app.library_manager.remove_profile(group_id) {
             app.library_manager.save_library()?;
@@ -40,12 +44,12 @@ pub async fn library_delete_dict_group(group_id: ProfileId) -> std::result::Resu
         } else {
             Ok(false)
         }
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Rename a dictionary group
 #[command]
-pub async fn library_rename_dict_group(group_id: ProfileId, new_name: String) -> std::result::Result<(), String> {
+pub async fn library_rename_dict_group(group_id: ProfileId, new_name: String) -> CommandResult<()> {
     with_write_access(|app| {
         if let Some(profile) = app.library_manager.get_group_mut(group_id) {
             profile.title = new_name;
@@ -53,52 +57,131 @@ pub async fn library_rename_dict_group(group_id: ProfileId, new_name: String) ->
             app.library_manager.save_library()?;
             Ok(())
         } else {
-            Err(ZdbError::invalid_parameter("配置文件或组不存在".to_string()))
+            Err(profile_not_found(group_id))
         }
-    }).into_string_result()
+    }).into_command_result()
+}
+
+/// Set how headwords are merged in search results, for the group itself (`profile_id` omitted)
+/// or for a library of the group. Takes effect when the group or library is opened again
+#[command]
+pub async fn library_set_key_normalization(
+    group_id: ProfileId,
+    profile_id: Option<ProfileId>,
+    key_normalization: KeyNormalization
+) -> CommandResult<()> {
+    with_write_access(|app| {
+        let group = app.library_manager.get_group_mut(group_id).ok_or_else(|| profile_not_found(group_id))?;
+        let profile = match profile_id {
+            Some(profile_id) => group.get_profile_mut(profile_id).ok_or_else(|| profile_not_found(profile_id))?,
+            None => group,
+        };
+        profile.options.key_normalization = key_normalization;
+        app.library_manager.save_library()
+    }).into_command_result()
+}
+
+/// Set the locale of the order of a group's merged key list (BCP 47, like "de" or "zh-u-co-pinyin"),
+/// empty for the root collation. Takes effect when the group is opened again, its index is then rebuilt
+#[command]
+pub async fn library_set_collation_locale(group_id: ProfileId, collation_locale: String) -> CommandResult<()> {
+    with_write_access(|app| {
+        if !collation_locale.is_empty() && collation_locale.parse::<Locale>().is_err() {
+            return Err(invalid_argument(format!("Invalid collation locale: {}", collation_locale)));
+        }
+        let group = app.library_manager.get_group_mut(group_id).ok_or_else(|| profile_not_found(group_id))?;
+        group.options.collation_locale = collation_locale;
+        app.library_manager.save_library()
+    }).into_command_result()
+}
+
+/// Set the weight of the full-text scores of a library of a group, `None` resets it to 1.0
+/// Takes effect when the group is opened again
+#[command]
+pub async fn library_set_fts_weight(group_id: ProfileId, profile_id: ProfileId, fts_weight: Option<f32>) -> CommandResult<()> {
+    with_write_access(|app| {
+        if let Some(weight) = fts_weight
+            && !(weight.is_finite() && weight >= 0.0)
+        {
+            return Err(invalid_argument(format!("Invalid full-text weight: {}", weight)));
+        }
+        let group = app.library_manager.get_group_mut(group_id).ok_or_else(|| profile_not_found(group_id))?;
+        let profile = group.get_profile_mut(profile_id).ok_or_else(|| profile_not_found(profile_id))?;
+        profile.options.fts_weight = fts_weight;
+        app.library_manager.save_library()
+    }).into_command_result()
+}
+
+/// Set the languages of the headwords and definitions of a library of a group (BCP 47 codes like "en"
+/// or "ru"), an empty language is detected from the dictionary. Takes effect when the group is opened again
+#[command]
+pub async fn library_set_languages(
+    group_id: ProfileId,
+    profile_id: ProfileId,
+    source_language: String,
+    target_language: String
+) -> CommandResult<()> {
+    with_write_access(|app| {
+        for language in [&source_language, &target_language] {
+            if !language.is_empty() && language.parse::<Locale>().is_err() {
+                return Err(invalid_argument(format!("Invalid language: {}", language)));
+            }
+        }
+        let group = app.library_manager.get_group_mut(group_id).ok_or_else(|| profile_not_found(group_id))?;
+        let profile = group.get_profile_mut(profile_id).ok_or_else(|| profile_not_found(profile_id))?;
+        profile.options.source_language = source_language;
+        profile.options.target_language = target_language;
+        app.library_manager.save_library()
+    }).into_command_result()
+}
+
+/// Languages of an opened library, as set in its options or detected, which route the lookups of its group
+#[command]
+pub async fn library_get_languages(profile_id: ProfileId, session_id: Option<SessionId>) -> CommandResult<Languages> {
+    with_lookup_context(session_id, |context| Ok(context.db.get_db(profile_id)?.languages().clone())).into_command_result()
 }
 
 /// Scan dictionary directory
 #[command]
-pub async fn library_refresh_library() -> std::result::Result<(), String> {
+pub async fn library_refresh_library() -> CommandResult<()> {
     with_write_access(|app| {
         let search_paths = app.get_lib_search_paths()?.to_vec();
         app.library_manager.refresh_library(&search_paths)?;
         Ok(())
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Get group for a given group_id
 #[command]
-pub async fn library_get_group(group_id: ProfileId) -> std::result::Result<serde_json::Value, String> {
+pub async fn library_get_group(group_id: ProfileId) -> CommandResult<serde_json::Value> {
     with_read_access(|app| {
         if let Some(group) = app.library_manager.get_group(group_id) {
             let group_value = serde_json::to_value(group)
                 .map_err(|e| ZdbError::invalid_data_format(e.to_string()))?;
             Ok(group_value)
         } else {
-            Err(ZdbError::invalid_parameter("词典组不存在".to_string()))
+            Err(profile_not_found(group_id))
         }
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Get profile for a given group_id and profile_id
 #[command]
-pub async fn library_get_profile(parent_group_id: ProfileId, profile_id: ProfileId) -> std::result::Result<serde_json::Value, String> {
+pub async fn library_get_profile(parent_group_id: ProfileId, profile_id: ProfileId) -> CommandResult<serde_json::Value> {
     with_read_access(|app| {
         if let Some(profile) = app.library_manager.get_profile(parent_group_id, profile_id) {
             let profile_value = serde_json::to_value(profile)
                 .map_err(|e| ZdbError::invalid_data_format(e.to_string()))?;
             Ok(profile_value)
         } else {
-            Err(ZdbError::invalid_parameter("词典不存在".to_string()))
+            Err(profile_not_found(profile_id))
         }
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// List all dictionary groups
 #[command]
-pub async fn library_list_groups() -> std::result::Result<serde_json::Value, String> {
+pub async fn library_list_groups() -> CommandResult<serde_json::Value> {
     with_read_access(|app| {
         // Get current main profile ID from main_db
         let current_main_profile_id = app.get_current_main_profile_id()?;
@@ -119,7 +202,7 @@ pub async fn library_list_groups() -> std::result::Result<serde_json::Value, Str
         }
         
         Ok(serde_json::Value::Array(enhanced_groups))
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Update profile disabled status
@@ -128,7 +211,7 @@ pub async fn library_update_profile_disabled_status(
     parent_group_id: ProfileId, 
     profile_id: ProfileId, 
     disabled: bool
-) -> std::result::Result<(), String> {
+) -> CommandResult<()> {
     with_write_access(|app| {
         if let Some(group) = app.library_manager.get_group_mut(parent_group_id) {
             if let Some(profile) = group.get_profile_mut(profile_id) {
@@ -137,12 +220,12 @@ pub async fn library_update_profile_disabled_status(
                 app.library_manager.save_library()?;
                 Ok(())
             } else {
-                Err(ZdbError::invalid_parameter("词典配置不存在".to_string()))
+                Err(profile_not_found(profile_id))
             }
         } else {
-            Err(ZdbError::invalid_parameter("词典组不存在".to_string()))
+            Err(profile_not_found(parent_group_id))
         }
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Adjust profile order within a group
@@ -151,41 +234,41 @@ pub async fn library_adjust_profile_order(
     parent_group_id: ProfileId, 
     profile_id: ProfileId, 
     new_index: usize
-) -> std::result::Result<(), String> {
+) -> CommandResult<()> {
     with_write_access(|app| {
         app.library_manager.adjust_profile_order(parent_group_id, profile_id, new_index);
         // Save to file
         app.library_manager.save_library()?;
         Ok(())
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Adjust group order
 #[command]
-pub async fn library_adjust_group_order(group_id: ProfileId, new_index: usize) -> std::result::Result<(), String> {
+pub async fn library_adjust_group_order(group_id: ProfileId, new_index: usize) -> CommandResult<()> {
     with_write_access(|app| {
         app.library_manager.adjust_group_order(group_id, new_index);
         // Save to file
         app.library_manager.save_library()?;
         Ok(())
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Open main database with the given profile
 #[command]
-pub async fn library_open_main_database(profile_id: ProfileId) -> std::result::Result<(), String> {
-    with_write_access(|app| app.open_main_db(profile_id)).into_string_result()
+pub async fn library_open_main_database(profile_id: ProfileId) -> CommandResult<()> {
+    with_write_access(|app| app.open_main_db(profile_id)).into_command_result()
 }
 
 /// Get current main profile ID
 #[command]
-pub async fn library_get_current_main_profile_id() -> std::result::Result<ProfileId, String> {
-    with_read_access(|app| app.get_current_main_profile_id()).into_string_result()
+pub async fn library_get_current_main_profile_id() -> CommandResult<ProfileId> {
+    with_read_access(|app| app.get_current_main_profile_id()).into_command_result()
 }
 
 /// Get main database profile (complete object)
 #[command]
-pub async fn library_get_main_db_profile() -> std::result::Result<Option<serde_json::Value>, String> {
+pub async fn library_get_main_db_profile() -> CommandResult<Option<serde_json::Value>> {
     with_read_access(|app| {
         match app.get_current_main_profile()? {
             Some(profile) => {
@@ -195,7 +278,7 @@ pub async fn library_get_main_db_profile() -> std::result::Result<Option<serde_j
             },
             None => Ok(None)
         }
-    }).into_string_result()
+    }).into_command_result()
 }
 
 /// Rebuild dictionary index with collation options
@@ -203,7 +286,7 @@ pub async fn library_get_main_db_profile() -> std::result::Result<Option<serde_j
 pub async fn library_rebuild_index(
     profile_id: ProfileId,
     options: CollationOptions
-) -> std::result::Result<(), String> {
+) -> CommandResult<()> {
     with_write_access(|_app| {
         log::info!("Rebuilding index for profile {} with options: {:?}", profile_id, options);
         
@@ -252,6 +335,6 @@ pub async fn library_rebuild_index(
         
         log::info!("Generated BCP 47 locale string: {}", locale_str);        
         Ok(())
-    }).into_string_result()
+    }).into_command_result()
 }
 
@@ -1,5 +1,6 @@
 use std::collections::{LinkedList, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use mime_guess::MimeGuess;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -8,7 +9,7 @@ use url::Url;
 use mdx::ZdbError;
 use mdx::utils::{scan_dir, get_decoded_path, replace_url_path, with_extension};
 
-use crate::error::Result;
+use crate::error::{profile_not_found, Result};
 use crate::mdx_profile::{self, MdxOptions, MdxProfile, ProfileId, DEFAULT_GROUP_ID};
 
 /// MDX库管理器
@@ -24,7 +25,7 @@ pub struct LibraryManager {
     library_file_path: String,
 
     #[serde(skip)]
-    icon_cache: HashMap<ProfileId, (Vec<u8>, String)>,
+    icon_cache: Mutex<HashMap<ProfileId, (Vec<u8>, String)>>,
 }
 
 impl LibraryManager {
@@ -33,7 +34,7 @@ impl LibraryManager {
             mdx_groups: LinkedList::new(),
             default_mdx_options: MdxOptions::default(),
             library_file_path: String::new(),
-            icon_cache: HashMap::new(),
+            icon_cache: Mutex::new(HashMap::new()),
         };
         manager.ensure_default_group();
         manager
@@ -337,7 +338,7 @@ impl LibraryManager {
             mdx_groups,
             default_mdx_options: MdxOptions::default(),
             library_file_path: String::new(),
-            icon_cache: HashMap::new(),
+            icon_cache: Mutex::new(HashMap::new()),
         };
         
         // Ensure the default "All" group exists
@@ -351,17 +352,16 @@ impl LibraryManager {
         Ok(json)
     }
 
-    pub fn get_icon_for_profile(&mut self, profile_id: ProfileId) -> Result<Option<(Vec<u8>, String)>> {
-        let profile = self.find_profile(profile_id).ok_or_else(|| ZdbError::invalid_parameter(format!("Profile {} not found", profile_id)))?;
-        let icon_data = self.icon_cache.get(&profile_id);
-        if icon_data.is_none() {
-            let data = load_logo_for_profile(profile)?;
-            if !data.0.is_empty() {
-                self.icon_cache.insert(profile_id, data.clone());
-            }
-            return Ok(Some(data.clone()));
+    pub fn get_icon_for_profile(&self, profile_id: ProfileId) -> Result<Option<(Vec<u8>, String)>> {
+        let profile = self.find_profile(profile_id).ok_or_else(|| profile_not_found(profile_id))?;
+        if let Some(icon_data) = self.icon_cache.lock().unwrap_or_else(|e| e.into_inner()).get(&profile_id) {
+            return Ok(Some(icon_data.clone()));
+        }
+        let data = load_logo_for_profile(profile)?;
+        if !data.0.is_empty() {
+            self.icon_cache.lock().unwrap_or_else(|e| e.into_inner()).insert(profile_id, data.clone());
         }
-        Ok(None)
+        Ok(Some(data))
     }
 }
 
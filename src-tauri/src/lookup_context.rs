@@ -0,0 +1,682 @@
+// Lookup context module - the open database and its cached search results
+// A LookupContext is cheap to clone, lookups run on a clone so that the global
+// app lock is only held long enough to fetch it
+use std::collections::LinkedList;
+use std::sync::{Arc, Mutex, RwLock};
+
+use mdx::storage::EntryNo;
+use once_cell::sync::OnceCell;
+
+use crate::api_types::{GroupEntry, MatchKind, ResultKey, Snippet, Suggestion};
+use crate::error::{invalid_argument, Result};
+use crate::fts_query::{self, Field, FtsQuery};
+use crate::html_text::html_to_text;
+use crate::key_pattern::KeyPattern;
+use crate::mdx_db::{MdxDb, MdxIndex};
+use crate::mdx_db_group::{GroupKeyCursor, MdxDbGroup, MdxGroupIndex};
+use crate::mdx_profile::{MdxProfile, ProfileId};
+use crate::japanese;
+use crate::language::Script;
+use crate::morphology::Morphology;
+use crate::pinyin;
+use crate::snippet;
+
+pub enum DbType {
+    MdxDb(MdxDb),
+    MdxDbGroup(MdxDbGroup),
+}
+
+impl DbType {
+    pub fn profile(&self) -> &MdxProfile {
+        match self {
+            DbType::MdxDb(db) => &db.profile,
+            DbType::MdxDbGroup(group_db) => &group_db.profile,
+        }
+    }
+
+    /// Normalize a keyword the way the database merges and compares headwords
+    pub fn normalize_keyword(&self, keyword: &str) -> String {
+        match self {
+            DbType::MdxDb(db) => db.normalize_keyword(keyword),
+            DbType::MdxDbGroup(group_db) => group_db.normalize_keyword(keyword),
+        }
+    }
+
+    /// Find the profile of a library in this database (the database itself or a library of the group)
+    pub fn find_profile(&self, profile_id: ProfileId) -> Option<&MdxProfile> {
+        match self {
+            DbType::MdxDb(db) => Some(&db.profile).filter(|p| p.profile_id == profile_id),
+            DbType::MdxDbGroup(group_db) => group_db.profile.get_profile(profile_id),
+        }
+    }
+
+    /// Get the library an index belongs to
+    pub fn get_db(&self, profile_id: ProfileId) -> Result<&MdxDb> {
+        match self {
+            DbType::MdxDb(db) if db.profile.profile_id == profile_id => Ok(db),
+            DbType::MdxDb(_) => Err(invalid_argument(format!("Library id not match {}", profile_id))),
+            DbType::MdxDbGroup(group_db) => group_db.get_db(profile_id),
+        }
+    }
+}
+
+/// Cached search results (normalized_key, display_key, group_indexes)
+pub type GroupSearchResults = LinkedList<(String, String, LinkedList<MdxGroupIndex>)>;
+
+/// Keys merged at a time when the key list of a group incremental search is continued
+const CONTINUATION_SIZE: usize = 100;
+
+/// Headwords listed before the full-text results when a combined search falls back to full-text search
+const NEAREST_KEYS: usize = 3;
+
+/// What is known about a cached result besides its key, by position in the results
+#[derive(Clone, Debug, Default)]
+pub struct ResultDetail {
+    /// Relevance of a full-text result, normalized across dictionaries
+    pub score: Option<f32>,
+    /// Snippet of a full-text result, made when the result is first listed
+    snippet: OnceCell<Option<Snippet>>,
+    /// How the result of a combined search matched
+    pub match_kind: Option<MatchKind>,
+}
+
+/// The open database together with its cached search results
+#[derive(Clone)]
+pub struct LookupContext {
+    pub db: Arc<DbType>,
+    group_search_results: Arc<RwLock<GroupSearchResults>>,
+    /// Details of the cached results in the same order, may be shorter than the results
+    result_details: Arc<RwLock<Vec<ResultDetail>>>,
+    /// Terms of the full-text query of the cached results, for their snippets
+    result_terms: Arc<RwLock<Vec<String>>>,
+    /// Continuation of the merged key list of a group incremental search, exhausted for other searches
+    group_cursor: Arc<Mutex<GroupKeyCursor>>,
+}
+
+impl LookupContext {
+    /// Create a context with empty search results, `db` may be shared with other contexts
+    pub fn new(db: Arc<DbType>) -> Self {
+        Self {
+            db,
+            group_search_results: Arc::new(RwLock::new(LinkedList::new())),
+            result_details: Arc::new(RwLock::new(Vec::new())),
+            result_terms: Arc::new(RwLock::new(Vec::new())),
+            group_cursor: Arc::new(Mutex::new(GroupKeyCursor::default())),
+        }
+    }
+
+    /// A context on the same database with its own empty results, searches on it leave the results
+    /// of this context (and of its session) as they are
+    pub fn detached(&self) -> Self {
+        Self::new(self.db.clone())
+    }
+
+    fn read_results(&self) -> std::sync::RwLockReadGuard<'_, GroupSearchResults> {
+        self.group_search_results.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_cursor(&self) -> std::sync::MutexGuard<'_, GroupKeyCursor> {
+        self.group_cursor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the cached results, a continued group key list stops here
+    fn set_results(&self, results: GroupSearchResults) {
+        self.set_results_with_details(results, Vec::new(), Vec::new());
+    }
+
+    fn set_results_with_details(&self, results: GroupSearchResults, details: Vec<ResultDetail>, terms: Vec<String>) {
+        *self.group_search_results.write().unwrap_or_else(|e| e.into_inner()) = results;
+        *self.result_details.write().unwrap_or_else(|e| e.into_inner()) = details;
+        *self.result_terms.write().unwrap_or_else(|e| e.into_inner()) = terms;
+        *self.lock_cursor() = GroupKeyCursor::default();
+    }
+
+    /// Whether the cached key list of a group incremental search can be continued
+    pub fn has_more_results(&self) -> bool {
+        !self.lock_cursor().is_exhausted()
+    }
+
+    /// Continue the merged key list of a group incremental search until it has `len` keys
+    /// or all keys are merged
+    fn extend_results(&self, len: usize) -> Result<()> {
+        let DbType::MdxDbGroup(group_db) = self.db.as_ref() else { return Ok(()) };
+        let mut cursor = self.lock_cursor();
+        let missing = len.saturating_sub(self.read_results().len());
+        if missing == 0 || cursor.is_exhausted() {
+            return Ok(());
+        }
+        let mut more = group_db.merge_keys(&mut cursor, missing.max(CONTINUATION_SIZE))?;
+        self.group_search_results.write().unwrap_or_else(|e| e.into_inner()).append(&mut more);
+        Ok(())
+    }
+
+    /// Without search results a group whose index is not ready yet is browsed from the merged key lists
+    /// of its libraries, continued like an incremental search, so that browsing never waits for the index
+    fn start_group_browse(&self) -> Result<()> {
+        let DbType::MdxDbGroup(group_db) = self.db.as_ref() else { return Ok(()) };
+        if group_db.ready_key_index().is_some() || !self.read_results().is_empty() {
+            return Ok(());
+        }
+        let (results, cursor) = group_db.first_keys(CONTINUATION_SIZE)?;
+        self.set_results(results);
+        *self.lock_cursor() = cursor;
+        Ok(())
+    }
+
+    /// Search for words in group mode and cache results
+    pub fn incremental_search(&self, query: &str, max_results_per_lib: usize) -> Result<Option<(EntryNo, usize)>> {
+        match self.db.as_ref() {
+            DbType::MdxDb(db) => {
+                // Clear cached grouped results for fresh incremental search
+                self.set_results(LinkedList::new());
+                // Find best matching entry
+                if let Some(best_match) = db.find_index(&db.languages().headword_query(query), true, true, true)? {
+                    // Get a list of entries starting from the best match
+                    let start_entry = best_match.key_index.entry_no;
+                    Ok(Some((start_entry, db.get_entry_count() as usize)))
+                } else {
+                    Ok(None)
+                }
+            }
+            DbType::MdxDbGroup(group_db) => {
+                // Once the group index is ready the group is searched by position like a single dictionary
+                if let Some(index) = group_db.ready_key_index()
+                    && !index.is_empty()
+                {
+                    self.set_results(LinkedList::new());
+                    let position = group_db.find_index_position(index, query)?;
+                    return Ok(Some((position as EntryNo, index.len())));
+                }
+                // Delegate grouped search to MdxDbGroup and cache results, the key list is
+                // continued from the cursor when it is scrolled past the first window
+                let (cached_results, cursor) = group_db.find_best_match_indexes(query, max_results_per_lib)?;
+                let total = cached_results.len();
+                self.set_results(cached_results);
+                *self.lock_cursor() = cursor;
+                if total == 0 {
+                    Ok(None)
+                } else {
+                    Ok(Some((0, total)))
+                }
+            }
+        }
+    }
+
+    /// Incremental search falling back to full-text search when no headword starts with the query
+    /// The fallback caches one list: the headwords nearest to the query tagged `Nearest`, then the
+    /// full-text results of the query as a phrase, from the dictionaries having a full-text index, tagged `FullText`
+    /// Without full-text results the incremental search results are kept, also when the full-text search
+    /// fails, which is told by the returned flag. The position and count are like `incremental_search`
+    pub fn combined_search(&self, query: &str, max_results_per_lib: usize) -> Result<(Option<(EntryNo, usize)>, bool)> {
+        let found = self.incremental_search(query, max_results_per_lib)?;
+        let nearest = match found {
+            Some((start, _)) => self.nearest_results(start.max(0) as usize)?,
+            None => GroupSearchResults::new(),
+        };
+        let normalized_query = self.db.normalize_keyword(query);
+        if nearest.front().is_some_and(|(normalized_key, _, _)| normalized_key.starts_with(&normalized_query)) {
+            return Ok((found, false));
+        }
+
+        let words = fts_query::words(query);
+        if words.is_empty() {
+            return Ok((found, false));
+        }
+        let phrase = FtsQuery::Phrase { field: Field::Any, words };
+        let fulltext = match self.db.as_ref() {
+            DbType::MdxDb(db) if !db.is_fts_available() => return Ok((found, false)),
+            DbType::MdxDb(db) => db.fulltext_ranked(&phrase, max_results_per_lib).map(|hits| {
+                let (scores, indexes): (Vec<f32>, Vec<MdxIndex>) = hits.into_iter().unzip();
+                (Self::single_db_results(db, indexes), scores)
+            }),
+            // Libraries without a full-text index are skipped by the group
+            DbType::MdxDbGroup(group_db) => group_db.fulltext_find(&phrase, max_results_per_lib),
+        };
+        match Self::combined_results(query, nearest, fulltext) {
+            (Some((results, details)), fulltext_failed) => {
+                let total = results.len();
+                self.set_results_with_details(results, details, phrase.terms());
+                Ok((Some((0, total)), fulltext_failed))
+            }
+            (None, fulltext_failed) => Ok((found, fulltext_failed)),
+        }
+    }
+
+    /// The result list of a combined search: the nearest headwords, then the full-text results with their scores
+    /// None when there are no full-text results or the full-text search failed, the flag tells the failure
+    fn combined_results(query: &str, nearest: GroupSearchResults, fulltext: Result<(GroupSearchResults, Vec<f32>)>) -> (Option<(GroupSearchResults, Vec<ResultDetail>)>, bool) {
+        let (fulltext_results, scores) = match fulltext {
+            Ok(fulltext) => fulltext,
+            Err(e) => {
+                log::warn!("Full-text search for {} failed: {}", query, e);
+                return (None, true);
+            }
+        };
+        if fulltext_results.is_empty() {
+            return (None, false);
+        }
+
+        // A headword that is also a full-text result is listed once, as full-text result
+        let mut results: GroupSearchResults = nearest.into_iter()
+            .filter(|(normalized_key, _, _)| !fulltext_results.iter().any(|(key, _, _)| key == normalized_key))
+            .collect();
+        let mut details = vec![ResultDetail { match_kind: Some(MatchKind::Nearest), ..Default::default() }; results.len()];
+        details.extend(scores.into_iter().map(|score| ResultDetail {
+            score: Some(score),
+            match_kind: Some(MatchKind::FullText),
+            ..Default::default()
+        }));
+        results.extend(fulltext_results);
+        (Some((results, details)), false)
+    }
+
+    /// Up to `NEAREST_KEYS` keys of the current result list starting at `start`
+    fn nearest_results(&self, start: usize) -> Result<GroupSearchResults> {
+        let mut results = GroupSearchResults::new();
+        for position in start..(start + NEAREST_KEYS).min(self.get_entry_count()) {
+            let group_indexes = self.get_group_indexes(position)?;
+            let Some(display_key) = group_indexes.front().map(|group_index| group_index.primary_key.clone()) else {
+                continue;
+            };
+            results.push_back((self.db.normalize_keyword(&display_key), display_key, group_indexes));
+        }
+        Ok(results)
+    }
+
+    /// Wrap the indexes found in a single dictionary as cached results, one per entry
+    fn single_db_results(db: &MdxDb, indexes: impl IntoIterator<Item = MdxIndex>) -> GroupSearchResults {
+        let mut group_results = GroupSearchResults::new();
+        for index in indexes {
+            let mut group_index=MdxGroupIndex {
+                profile_id: index.profile_id,
+                primary_key: index.key_index.key.clone(),
+                indexes: LinkedList::new(),
+            };
+            group_index.indexes.push_back(index.clone());
+            let mut group_indexes = LinkedList::new();
+            group_indexes.push_back(group_index);
+            group_results.push_back((db.normalize_keyword(&index.key_index.key), index.key_index.key.clone(), group_indexes));
+        }
+        group_results
+    }
+
+    /// Perform full-text search and cache grouped results, ranked by descending score
+    /// The query is parsed first, see `FtsQuery` for its syntax. Returns total result count
+    pub fn fulltext_search(&self, query: &str, max_results_per_lib: usize) -> Result<usize> {
+        let query = FtsQuery::parse(query)?;
+        let (group_results, scores) = match self.db.as_ref() {
+            DbType::MdxDb(db) => {
+                let (scores, indexes): (Vec<f32>, Vec<MdxIndex>) = db.fulltext_ranked(&query, max_results_per_lib)?.into_iter().unzip();
+                (Self::single_db_results(db, indexes), scores)
+            }
+            // Group dictionary mode: delegate to group's fulltext_find
+            DbType::MdxDbGroup(group_db) => group_db.fulltext_find(&query, max_results_per_lib)?,
+        };
+        let total = group_results.len();
+        let details = scores.into_iter()
+            .map(|score| ResultDetail { score: Some(score), ..Default::default() })
+            .collect();
+        self.set_results_with_details(group_results, details, query.terms());
+        Ok(total)
+    }
+
+    /// Plain text of an entry around the terms, None when the entry can't be read
+    pub fn entry_snippet(&self, index: &MdxIndex, terms: &[String]) -> Option<Snippet> {
+        match self.db.get_db(index.profile_id).and_then(|mdx_db| mdx_db.resolve_entry(index)) {
+            Ok((_, html)) => Some(snippet::make_snippet(&html_to_text(&html), terms)),
+            Err(e) => {
+                log::debug!("Failed to read entry {} for its snippet: {}", index.key_index.key, e);
+                None
+            }
+        }
+    }
+
+    /// Snippet of a cached full-text result from its first entry
+    fn result_snippet(&self, detail: &ResultDetail, group_indexes: &LinkedList<MdxGroupIndex>) -> Option<Snippet> {
+        detail.score?;
+        detail.snippet.get_or_init(|| {
+            let index = group_indexes.front()?.indexes.front()?;
+            let terms = self.result_terms.read().unwrap_or_else(|e| e.into_inner());
+            self.entry_snippet(index, &terms)
+        }).clone()
+    }
+
+    /// Search headwords with a wildcard (`*`, `?`) or regex pattern and cache grouped results
+    /// Returns total result count
+    pub fn pattern_search(&self, pattern: &KeyPattern, max_results_per_lib: usize) -> Result<usize> {
+        let group_results = match self.db.as_ref() {
+            DbType::MdxDb(db) => Self::single_db_results(db, db.find_pattern(pattern, max_results_per_lib)?),
+            DbType::MdxDbGroup(group_db) => group_db.find_pattern(pattern, max_results_per_lib)?,
+        };
+        let total = group_results.len();
+        self.set_results(group_results);
+        Ok(total)
+    }
+
+    /// Chinese headwords whose pinyin ("zhongguo", "zhong1guo2") starts with the query, grouped
+    fn pinyin_results(&self, query: &str, max_results_per_lib: usize) -> Result<GroupSearchResults> {
+        Ok(match self.db.as_ref() {
+            DbType::MdxDb(db) => Self::single_db_results(db, db.find_pinyin(query, max_results_per_lib)?),
+            DbType::MdxDbGroup(group_db) => group_db.find_pinyin(query, max_results_per_lib)?,
+        })
+    }
+
+    /// Incremental search falling back to pinyin search when no headword starts with the query, so that
+    /// "long" lists the English headwords when there are any and the pinyin indexes are only built when needed
+    /// The pinyin results replace the incremental search results when there are any. Returns like `incremental_search`
+    pub fn incremental_or_pinyin_search(&self, query: &str, max_results_per_lib: usize, max_pinyin_results: usize) -> Result<Option<(EntryNo, usize)>> {
+        let found = self.incremental_search(query, max_results_per_lib)?;
+        if pinyin::normalize_query(query).is_none() || self.is_prefix_match(query, found)? {
+            return Ok(found);
+        }
+        let pinyin_results = self.pinyin_results(query, max_pinyin_results)?;
+        if pinyin_results.is_empty() {
+            return Ok(found);
+        }
+        let total = pinyin_results.len();
+        self.set_results(pinyin_results);
+        Ok(Some((0, total)))
+    }
+
+    /// Whether the best match of an incremental search, `found`, is a headword starting with the query
+    fn is_prefix_match(&self, query: &str, found: Option<(EntryNo, usize)>) -> Result<bool> {
+        let Some((start, _)) = found else { return Ok(false) };
+        let normalized_query = self.db.normalize_keyword(query);
+        Ok(self.get_group_indexes(start.max(0) as usize)?
+            .front()
+            .is_some_and(|group_index| self.db.normalize_keyword(&group_index.primary_key).starts_with(&normalized_query)))
+    }
+
+    /// "Did you mean" suggestions for a headword that is not found, closest first
+    /// Keys differing only in case are merged and list every dictionary having them
+    pub fn suggest(&self, word: &str, max_results: usize) -> Result<Vec<Suggestion>> {
+        let candidates = match self.db.as_ref() {
+            DbType::MdxDb(db) => db.suggest(word, max_results)?,
+            DbType::MdxDbGroup(group_db) => group_db.suggest(word, max_results)?,
+        };
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for (distance, index) in candidates {
+            let key = index.key_index.key;
+            match suggestions.iter().position(|suggestion| suggestion.key.to_lowercase() == key.to_lowercase()) {
+                Some(position) => {
+                    let suggestion = &mut suggestions[position];
+                    if !suggestion.profile_ids.contains(&index.profile_id) {
+                        suggestion.profile_ids.push(index.profile_id);
+                    }
+                }
+                None if suggestions.len() < max_results => suggestions.push(Suggestion {
+                    key,
+                    distance,
+                    profile_ids: vec![index.profile_id],
+                }),
+                None => {}
+            }
+        }
+        Ok(suggestions)
+    }
+
+    /// Perform full-text search returning scored hits, ordered by descending score
+    /// Unlike `fulltext_search` the cached result list is left untouched
+    pub fn fulltext_hits(&self, query: &FtsQuery, max_results_per_lib: usize) -> Result<Vec<(f32, MdxIndex)>> {
+        match self.db.as_ref() {
+            DbType::MdxDb(db) => db.fulltext_ranked(query, max_results_per_lib),
+            DbType::MdxDbGroup(group_db) => group_db.fulltext_hits(query, max_results_per_lib),
+        }
+    }
+
+    /// Get HTML content for a single entry, resource links are rewritten against `base_url`
+    pub fn get_entry_html_by_index(&self, index: &MdxIndex, base_url: &str) -> Result<String> {
+        Ok(self.get_resolved_entry(index, base_url)?.1)
+    }
+
+    /// Get HTML content for a single entry following `@@@LINK=` redirects
+    /// Returns the entry actually shown together with its HTML
+    pub fn get_resolved_entry(&self, index: &MdxIndex, base_url: &str) -> Result<(MdxIndex, String)> {
+        let mdx_db = self.db.get_db(index.profile_id)?;
+        let index = mdx_db.get_index(index.key_index.entry_no)?;
+        mdx_db.get_resolved_html(&index, base_url)
+    }
+
+    /// Get the final key of a redirect entry, None for a normal entry or when it can't be resolved
+    pub fn resolve_key(&self, index: &MdxIndex) -> Option<String> {
+        self.db.get_db(index.profile_id)
+            .and_then(|mdx_db| mdx_db.resolve_key(index))
+            .unwrap_or_else(|e| {
+                log::debug!("Failed to resolve entry {}: {}", index.key_index.key, e);
+                None
+            })
+    }
+
+    /// Convert grouped indexes to API entries, filling in the target key of redirects
+    pub fn group_entries(&self, group_indexes: &LinkedList<MdxGroupIndex>) -> Vec<GroupEntry> {
+        group_indexes.iter()
+            .map(|group_index| {
+                let mut group_entry = GroupEntry::from(group_index);
+                for (entry, index) in group_entry.indexes.iter_mut().zip(group_index.indexes.iter()) {
+                    entry.resolved_key = self.resolve_key(index);
+                }
+                group_entry
+            })
+            .collect()
+    }
+
+    /// Key shown in result lists, "colour → color" when every entry of the key redirects to the same key
+    fn display_key(&self, key: &str, group_indexes: &LinkedList<MdxGroupIndex>) -> String {
+        let mut target: Option<String> = None;
+        for index in group_indexes.iter().flat_map(|group_index| group_index.indexes.iter()) {
+            match self.resolve_key(index) {
+                Some(resolved) if target.as_ref().is_none_or(|target| *target == resolved) => target = Some(resolved),
+                _ => return key.to_string(),
+            }
+        }
+        match target {
+            Some(target) => MdxDb::redirect_display_key(key, &target),
+            None => key.to_string(),
+        }
+    }
+
+    /// Get the total entry count for the current database
+    /// While the key list of a group is still merged (see `has_more_results`) this is the count merged so far
+    pub fn get_entry_count(&self) -> usize {
+        if let Err(e) = self.start_group_browse() {
+            log::error!("Failed to merge the key lists of the group: {}", e);
+        }
+        // Prefer cached grouped results if available (e.g., full-text/group searches)
+        let cached_count = self.read_results().len();
+        match self.db.as_ref() {
+            DbType::MdxDb(db) if cached_count == 0 => db.get_entry_count() as usize,
+            // Without search results a group is browsed in the order of its index
+            DbType::MdxDbGroup(group_db) if cached_count == 0 => group_db.ready_key_index().map_or(0, |index| index.len()),
+            // For group mode, return the count of cached search results
+            _ => cached_count,
+        }
+    }
+
+    /// Find the best matching entry index for a given key
+    /// Returns LinkedList<MdxGroupIndex> where:
+    /// - Single dictionary mode: returns single MdxGroupIndex with one result
+    /// - Group mode: returns MdxGroupIndex entries grouped by profile_id
+    pub fn find_index(&self, key: &str) -> Result<LinkedList<MdxGroupIndex>> {
+        match self.db.as_ref() {
+            DbType::MdxDb(db) => {
+                // Single dictionary mode: wrap MdxIndex in MdxGroupIndex and put in LinkedList
+                let mut result = LinkedList::new();
+                if let Some(mdx_index) = db.find_index(key, false, false, true)? {
+                    let mut group_index = MdxGroupIndex::default();
+                    group_index.profile_id = db.profile.profile_id;
+                    group_index.primary_key = key.to_string();
+                    group_index.indexes.push_back(mdx_index);
+                    result.push_back(group_index);
+                }
+                Ok(result)
+            }
+            // Group mode: search across all dictionaries and group by profile_id
+            DbType::MdxDbGroup(group_db) => group_db.find_index(key),
+        }
+    }
+
+    /// Find a headword, when it is missing the normalized Japanese forms (kana of romaji, the other
+    /// kana script, dictionary form of a conjugation) and the Hunspell stems ("mice" -> "mouse") are tried
+    /// Romaji is only read as kana when a dictionary with kana headwords is open
+    /// Returns the indexes and the form that matched, the form is None when the word itself was found
+    pub fn find_index_or_stem(&self, key: &str, morphology: &Morphology) -> Result<(LinkedList<MdxGroupIndex>, Option<String>)> {
+        let group_indexes = self.find_index(key)?;
+        if !group_indexes.is_empty() {
+            return Ok((group_indexes, None));
+        }
+        let mut candidates = japanese::query_forms(key, self.has_kana_headwords());
+        candidates.extend(morphology.stems(key));
+        for stem in candidates {
+            let group_indexes = self.find_index(&stem)?;
+            if !group_indexes.is_empty() {
+                return Ok((group_indexes, Some(stem)));
+            }
+        }
+        Ok((LinkedList::new(), None))
+    }
+
+    /// Whether the open dictionary, or a library of the open group, can have kana headwords
+    fn has_kana_headwords(&self) -> bool {
+        let has_kana = |db: &MdxDb| db.languages().can_contain(&[Script::Kana], false);
+        match self.db.as_ref() {
+            DbType::MdxDb(db) => has_kana(db),
+            DbType::MdxDbGroup(group_db) => group_db.mdx_dbs.values().any(has_kana),
+        }
+    }
+
+    /// Get entries starting from a specific index
+    /// Unified method that works for both single dictionary and group modes
+    /// Returns the keys with the number of dictionaries having them, and the score of full-text results
+    pub fn get_result_key_list(&self, start_index: i64, max_count: usize) -> Result<LinkedList<ResultKey>> {
+        self.start_group_browse()?;
+        self.extend_results(start_index.max(0) as usize + max_count)?;
+        // If we have cached grouped results (e.g., from full-text or grouped search), use them regardless of mode
+        let cached_results = self.read_results();
+        if !cached_results.is_empty() {
+            if start_index >= cached_results.len() as i64 {
+                return Err(invalid_argument(format!("Start index out of range: {} >= {}", start_index, cached_results.len())));
+            }
+            let details = self.result_details.read().unwrap_or_else(|e| e.into_inner());
+            let mut results = LinkedList::new();
+            for (position, (_, display_key, group_indexes)) in cached_results.iter().enumerate().skip(start_index as usize) {
+                // Entry count equals number of dictionaries (profile groups) contributing to this key
+                let detail = details.get(position);
+                results.push_back(ResultKey {
+                    key: self.display_key(display_key, group_indexes),
+                    count: group_indexes.len(),
+                    score: detail.and_then(|detail| detail.score),
+                    snippet: detail.and_then(|detail| self.result_snippet(detail, group_indexes)),
+                    match_kind: detail.and_then(|detail| detail.match_kind),
+                });
+                if results.len() >= max_count {
+                    break;
+                }
+            }
+            return Ok(results);
+        }
+        drop(cached_results);
+
+        match self.db.as_ref() {
+            DbType::MdxDb(db) => {
+                // Single dictionary mode: get entries directly from the dictionary
+                let indexes = db.get_indexes(start_index, max_count as u64)?;
+                let mut results = LinkedList::new();
+                for index in indexes {
+                    let keyword = match self.resolve_key(&index) {
+                        Some(target) => MdxDb::redirect_display_key(&index.key_index.key, &target),
+                        None => index.key_index.key.clone(),
+                    };
+                    // For single dictionary mode, group count is always 1
+                    results.push_back(ResultKey { key: keyword, count: 1, score: None, snippet: None, match_kind: None });
+                }
+                Ok(results)
+            }
+            // Group mode without cached results: get entries from the group index
+            DbType::MdxDbGroup(group_db) => {
+                let mut results = LinkedList::new();
+                let keys = group_db.get_index_keys(start_index.max(0) as usize, max_count)?.unwrap_or_default();
+                for (_, display_key, group_indexes) in keys {
+                    results.push_back(ResultKey {
+                        key: self.display_key(&display_key, &group_indexes),
+                        count: group_indexes.len(),
+                        score: None,
+                        snippet: None,
+                        match_kind: None,
+                    });
+                }
+                Ok(results)
+            }
+        }
+    }
+
+    /// Get indexes for a specific entry position, returning grouped data
+    /// Returns LinkedList<MdxGroupIndex>
+    pub fn get_group_indexes(&self, index_no: usize) -> Result<LinkedList<MdxGroupIndex>> {
+        self.start_group_browse()?;
+        self.extend_results(index_no + 1)?;
+        // If we have cached grouped results, return from cache regardless of mode
+        let cached_results = self.read_results();
+        if !cached_results.is_empty() {
+            if index_no >= cached_results.len() {
+                return Err(invalid_argument(format!("Index out of range: {} >= {}", index_no, cached_results.len())));
+            }
+            return Ok(cached_results.iter().nth(index_no).unwrap().2.clone());
+        }
+        drop(cached_results);
+
+        match self.db.as_ref() {
+            DbType::MdxDb(db) => {
+                // Single dictionary mode: return one MdxGroupIndex with single index
+                if index_no >= db.get_entry_count() as usize {
+                    return Err(invalid_argument(format!("Index out of range: {} >= {}", index_no, db.get_entry_count() as usize)));
+                }
+
+                let mdx_index = db.get_index(index_no as i64)?;
+                let mut indexes = LinkedList::new();
+                indexes.push_back(mdx_index.clone());
+
+                let group_index = MdxGroupIndex {
+                    profile_id: db.profile.profile_id,
+                    primary_key: mdx_index.key_index.key.clone(),
+                    indexes,
+                };
+                let mut result = LinkedList::new();
+                result.push_back(group_index);
+                Ok(result)
+            }
+            // Group mode without cache: the entry of the group index
+            DbType::MdxDbGroup(group_db) => group_db.get_index_keys(index_no, 1)?
+                .and_then(|mut keys| keys.pop_front())
+                .map(|(_, _, group_indexes)| group_indexes)
+                .ok_or_else(|| invalid_argument(format!("Index out of range: {} >= {}", index_no, self.get_entry_count()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(keys: &[&str]) -> GroupSearchResults {
+        keys.iter().map(|key| (key.to_string(), key.to_string(), LinkedList::new())).collect()
+    }
+
+    #[test]
+    fn test_combined_results() {
+        // A failed full-text search keeps the headword results and is told
+        let (combined, fulltext_failed) = LookupContext::combined_results("colr", results(&["color"]), Err(invalid_argument("No full-text index")));
+        assert!(combined.is_none() && fulltext_failed);
+        let (combined, fulltext_failed) = LookupContext::combined_results("colr", results(&["color"]), Ok((results(&[]), Vec::new())));
+        assert!(combined.is_none() && !fulltext_failed);
+
+        // The nearest headwords first, a headword also found in the text only once as full-text result
+        let fulltext = Ok((results(&["colour", "hue"]), vec![1.0, 0.5]));
+        let (combined, fulltext_failed) = LookupContext::combined_results("colr", results(&["color", "colour"]), fulltext);
+        let (combined, details) = combined.unwrap();
+        assert!(!fulltext_failed);
+        let keys: Vec<&str> = combined.iter().map(|(key, _, _)| key.as_str()).collect();
+        assert_eq!(keys, ["color", "colour", "hue"]);
+        let kinds: Vec<_> = details.iter().map(|detail| (detail.match_kind, detail.score)).collect();
+        assert_eq!(kinds, [(Some(MatchKind::Nearest), None), (Some(MatchKind::FullText), Some(1.0)), (Some(MatchKind::FullText), Some(0.5))]);
+    }
+}
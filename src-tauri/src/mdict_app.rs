@@ -1,26 +1,26 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{RwLock, Arc, Mutex};
-use std::collections::{HashMap, LinkedList};
+use std::collections::HashMap;
 use once_cell::sync::OnceCell;
 use rusqlite::Connection;
-use tauri::{Manager, path::BaseDirectory, Emitter};
 
-use mdx::storage::EntryNo;
 use mdx::utils::fix_windows_path_buf;
 
 use crate::app_config::{AppConfig, ConfigSection, ConfigKey};
-use crate::error::{Result, ZdbError};
+use crate::error::{invalid_argument, no_database_opened, profile_not_found, ErrorCode, Result, ZdbError};
+use crate::event_sink::EventSink;
 use crate::favorites::FavoritesManager;
 use crate::history::HistoryManager;
 use crate::library_mgr::LibraryManager;
-use crate::mdx_db::{MdxIndex, MdxDb};
-use crate::mdx_db_group::{MdxDbGroup, MdxGroupIndex};
+use crate::lookup_context::{DbType, LookupContext};
+use crate::mdx_db::MdxDb;
+use crate::mdx_db_group::MdxDbGroup;
 use crate::mdx_profile::{ProfileId, DEFAULT_GROUP_ID, INVALID_PROFILE_ID, MdxProfile};
+use crate::morphology::Morphology;
+use crate::chinese_conv::{ChineseConversion, ChineseConverter};
+use crate::session_mgr::{SessionId, SessionManager, DEFAULT_SESSION_ID};
 use crate::utils::log_if_err;
-pub enum DbType {
-    MdxDb(MdxDb),
-    MdxDbGroup(MdxDbGroup),
-}
+
 
 /// MDict应用全局单例结构体
 /// 参考libmdx中的MDictApp实现
@@ -37,11 +37,17 @@ pub struct MdictApp {
     pub history_manager: HistoryManager,
     /// 收藏管理器
     pub favorites_manager: FavoritesManager,
-    /// 主数据库
-    pub main_db: Option<DbType>,
-
-    /// Tauri app handle for emitting events
-    app_handle: tauri::AppHandle,
+    /// 查询会话，每个会话有自己打开的词典、搜索结果和浏览历史
+    pub session_manager: SessionManager,
+    /// Hunspell词形还原，查不到屈折形式时尝试其词干
+    morphology: Arc<Morphology>,
+    /// 简繁转换，由打开的词典共用
+    chinese_conversion: Arc<ChineseConversion>,
+
+    /// Sink for events sent to the frontend (or any other listener)
+    event_sink: Arc<dyn EventSink>,
+    /// assets目录，为None时按可执行文件位置推断
+    assets_dir: Option<PathBuf>,
 
     /// 程序数据根目录
     data_home_dir: String,
@@ -59,8 +65,6 @@ pub struct MdictApp {
     /// 库搜索路径列表
     lib_search_paths: Vec<String>,
 
-    /// Cached search results for group mode (normalized_key, display_key, group_indexes)
-    group_search_results: LinkedList<(String, String, LinkedList<MdxGroupIndex>)>,
     /// Asset files loaded into memory (filename -> content)
     assets: HashMap<String, Vec<u8>>,
 
@@ -68,17 +72,14 @@ pub struct MdictApp {
 
 }
 
-// SAFETY: MdictApp is protected by RwLock, and we ensure single-threaded access to MDX readers
-unsafe impl Send for MdictApp {}
-unsafe impl Sync for MdictApp {}
-
 impl MdictApp {
 
     /// 使用主目录初始化应用
     pub fn init_app_with_home_dir<P: AsRef<Path>>(
         data_home_dir: P, 
         extra_search_paths: Vec<String>,
-        app_handle: &tauri::AppHandle
+        assets_dir: Option<PathBuf>,
+        event_sink: Arc<dyn EventSink>,
     ) -> Result<Self> {
 
         let home_dir = fix_windows_path_buf(data_home_dir.as_ref().to_path_buf()).to_string_lossy().into_owned();
@@ -89,7 +90,7 @@ impl MdictApp {
         let res_dir = format!("{}/res", home_dir);
         let tmp_dir = format!("{}/tmp", home_dir);
         
-        Self::init_app(home_dir, doc_dir, audio_lib_dir, font_dir, res_dir, tmp_dir, extra_search_paths, app_handle)
+        Self::init_app(home_dir, doc_dir, audio_lib_dir, font_dir, res_dir, tmp_dir, extra_search_paths, assets_dir, event_sink)
     }
 
     /// 初始化应用
@@ -101,7 +102,8 @@ impl MdictApp {
         res_dir: String,
         tmp_dir: String,
         extra_search_paths: Vec<String>,
-        app_handle: &tauri::AppHandle,
+        assets_dir: Option<PathBuf>,
+        event_sink: Arc<dyn EventSink>,
     ) -> Result<Self> {
         let data_home_dir = Self::normalize_dir_path(data_home_dir);
         let doc_dir = Self::normalize_dir_path(doc_dir);
@@ -158,6 +160,9 @@ impl MdictApp {
         // Initialize history and favorites managers with shared connection
         let history_manager = HistoryManager::new(db_connection.clone())?;
         let favorites_manager = FavoritesManager::new(db_connection.clone())?;
+
+        let morphology = Arc::new(Self::load_morphology(&config, &data_home_dir));
+        let chinese_conversion = Arc::new(Self::load_chinese_conversion(&config, &data_home_dir));
         
         let mut app = Self {
             config,
@@ -172,21 +177,61 @@ impl MdictApp {
             _font_dir: font_dir,
             _audio_lib_dir: audio_lib_dir,
             lib_search_paths,
-            main_db: None,
-            group_search_results: LinkedList::new(),
+            session_manager: SessionManager::new(),
+            morphology,
+            chinese_conversion,
             assets: HashMap::new(),
-            app_handle: app_handle.clone(),
+            event_sink,
+            assets_dir,
             base_url: "mdx://mdict.cn/service/".to_string(),
         };
 
         // Load asset files into memory
-        app.load_assets(app_handle)?;
+        app.load_assets()?;
 
         // Auto-open last used profile or default group
         log_if_err(&app.auto_open_startup_profile());
         Ok(app)
     }
 
+    /// 加载Hunspell词典目录（配置为空时使用数据目录下的hunspell目录），加载失败时不使用词形还原
+    fn load_morphology(config: &AppConfig, data_home_dir: &str) -> Morphology {
+        let mut dir = config.get_config_with_default(ConfigSection::Global, ConfigKey::HunspellDictPath, String::new());
+        if dir.is_empty() {
+            dir = format!("{}hunspell", data_home_dir);
+        }
+        Morphology::load_dir(Path::new(&dir)).unwrap_or_else(|e| {
+            log::warn!("Failed to load Hunspell dictionaries from {}: {}", dir, e);
+            Morphology::default()
+        })
+    }
+
+    /// Reload the Hunspell dictionaries, e.g. after the directory setting changed
+    pub fn reload_morphology(&mut self) -> Result<()> {
+        self.morphology = Arc::new(Self::load_morphology(&self.config, &self.data_home_dir));
+        Ok(())
+    }
+
+    pub fn morphology(&self) -> Arc<Morphology> {
+        self.morphology.clone()
+    }
+
+    /// 加载数据目录下opencc目录中的简繁转换表（没有时使用内置的表），是否启用由配置决定
+    fn load_chinese_conversion(config: &AppConfig, data_home_dir: &str) -> ChineseConversion {
+        let dir = format!("{}opencc", data_home_dir);
+        let converter = ChineseConverter::load(Path::new(&dir)).unwrap_or_else(|e| {
+            log::warn!("Failed to load Chinese conversion tables from {}: {}", dir, e);
+            ChineseConverter::bundled()
+        });
+        let enabled = config.get_config_with_default(ConfigSection::Global, ConfigKey::ChineseConversion, true);
+        ChineseConversion::new(converter, enabled)
+    }
+
+    /// Turn the Simplified/Traditional conversion of lookups on or off, open databases follow at once
+    pub fn set_chinese_conversion(&self, enabled: bool) {
+        self.chinese_conversion.set_enabled(enabled);
+    }
+
     /// 规范化目录路径，确保以/结尾
     fn normalize_dir_path(mut path: String) -> String {
         if !path.is_empty() && !path.ends_with('/') && !path.ends_with('\\') {
@@ -219,11 +264,11 @@ impl MdictApp {
     }
 
     /// 加载assets目录下的文件到内存
-    fn load_assets(&mut self, app_handle: &tauri::AppHandle) -> Result<()> {
-        // 使用tauri的路径解析来获取assets目录
-        let assets_dir = match app_handle.path().resolve("assets", BaseDirectory::Resource) {
-            Ok(path) => path,
-            Err(_) => {
+    fn load_assets(&mut self) -> Result<()> {
+        // 优先使用调用方（如tauri的资源目录）提供的assets目录
+        let assets_dir = match self.assets_dir.clone() {
+            Some(path) => path,
+            None => {
                 // 如果无法解析resource目录，回退到传统方法
                 let fallback_dir = if let Ok(exe_path) = std::env::current_exe() {
                     if let Some(exe_dir) = exe_path.parent() {
@@ -294,21 +339,7 @@ impl MdictApp {
     }
 
     pub fn open_main_db(&mut self, profile_id: ProfileId) -> Result<()> {
-        self.main_db = None;
-        // Clear cached search results when switching databases
-        self.group_search_results.clear();
-        if profile_id != INVALID_PROFILE_ID {
-            let profile = self.library_manager.find_profile(profile_id).ok_or_else(|| ZdbError::invalid_parameter(format!("Profile {} not found", profile_id)))?;
-            if profile.is_group() {
-                let db = MdxDbGroup::new(&profile, &self.data_home_dir).map_err(|e| ZdbError::invalid_data_format(format!("Failed to open group database: {}: {}", profile_id, e)))?;
-                self.main_db = Some(DbType::MdxDbGroup(db));
-            } else {
-                let db = MdxDb::new(&profile, &self.data_home_dir).map_err(|e| ZdbError::invalid_data_format(format!("Failed to open database: {}: {}", profile_id, e)))?;
-                self.main_db = Some(DbType::MdxDb(db));
-            }
-        }else{
-            return Err(ZdbError::invalid_parameter(format!("Invalid profile ID: {}", profile_id)));
-        }
+        self.open_session_db(None, profile_id)?;
         // Update last_main_profile_id in config
         self.config.set_config(ConfigSection::Global, ConfigKey::LastMainProfileId, profile_id)?;
         self.config.save()?;
@@ -316,110 +347,65 @@ impl MdictApp {
         Ok(())
     }
 
-    /// Search for words in group mode and cache results
-    pub fn incremental_search(&mut self, query: &str, max_results_per_lib: usize) -> Result<Option<(EntryNo, usize)>> {
-        // Clear cached grouped results for fresh incremental search
-        self.group_search_results.clear();
-        if let Some(DbType::MdxDb(ref mut db)) = self.main_db {
-            // Find best matching entry
-            if let Some(best_match) = db.find_index(query, true, true, true)? {
-                // Get a list of entries starting from the best match
-                let start_entry = best_match.key_index.entry_no;
-                Ok(Some((start_entry, db.get_entry_count() as usize)))
-            } else {
-                Ok(None)
-            }
-        } else if let Some(DbType::MdxDbGroup(ref mut group_db)) = self.main_db {
-            // Delegate grouped search to MdxDbGroup and cache results
-            let cached_results = group_db.find_best_match_indexes(query, max_results_per_lib)?;
-            self.group_search_results = cached_results;
-            if self.group_search_results.is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some((0, self.group_search_results.len())))
-            }
-        } else {
-            Err(ZdbError::invalid_parameter("No database opened".to_string()))
+    /// Open a profile in a session (`None` is the default session)
+    /// A database already open in another session is shared
+    pub fn open_session_db(&mut self, session_id: Option<SessionId>, profile_id: ProfileId) -> Result<()> {
+        // Cached search results belong to the lookup context and are dropped with it
+        self.session_manager.clear_session_db(session_id)?;
+        if profile_id == INVALID_PROFILE_ID {
+            return Err(invalid_argument(format!("Invalid profile ID: {}", profile_id)));
         }
-    }
-
-    /// Perform full-text search and cache grouped results
-    /// Returns total result count
-    pub fn fulltext_search(&mut self, query: &str, max_results_per_lib: usize) -> Result<usize> {
-        // Reset previous cached results
-        self.group_search_results.clear();
-
-        match &mut self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                let mut group_results : LinkedList<(String, String, LinkedList<MdxGroupIndex>)> = LinkedList::new();
-                let results = db.fulltext_find(query, max_results_per_lib)?;
-                for (_score, index) in results.into_iter() {
-                    let mut group_index=MdxGroupIndex {
-                        profile_id: index.profile_id,
-                        primary_key: index.key_index.key.clone(),
-                        indexes: LinkedList::new(),
-                    };
-                    group_index.indexes.push_back(index.clone());
-                    let mut group_indexes = LinkedList::new();
-                    group_indexes.push_back(group_index);
-                    group_results.push_back((MdxDb::normalize_keyword(&index.key_index.key), index.key_index.key.clone(), group_indexes));
+        let profile = self.library_manager.find_profile(profile_id).ok_or_else(|| profile_not_found(profile_id))?;
+        let db = match self.session_manager.get_open_db(profile_id) {
+            // A database opened before the profile changed (members, options) is not shared, the new one replaces it
+            Some(db) if db.profile() == profile => db,
+            _ => {
+                if profile.is_group() {
+                    let index_dir = PathBuf::from(format!("{}group_index", self.data_home_dir));
+                    let db = MdxDbGroup::new(&profile, &self.data_home_dir, &index_dir, self.chinese_conversion.clone()).map_err(|e| ZdbError::invalid_data_format(format!("Failed to open group database: {}: {}", profile_id, e)))?;
+                    let db = Arc::new(DbType::MdxDbGroup(db));
+                    Self::open_group_index_in_background(&db);
+                    db
+                } else {
+                    let db = MdxDb::new(&profile, &self.data_home_dir, self.chinese_conversion.clone()).map_err(|e| ZdbError::invalid_data_format(format!("Failed to open database: {}: {}", profile_id, e)))?;
+                    Arc::new(DbType::MdxDb(db))
                 }
-
-                self.group_search_results = group_results;
-                Ok(self.group_search_results.len())
             }
-            Some(DbType::MdxDbGroup(group_db)) => {
-                // Group dictionary mode: delegate to group's fulltext_find
-                let results = group_db.fulltext_find(query, max_results_per_lib)?;
-                self.group_search_results = results;
-                Ok(self.group_search_results.len())
-            }
-            None => Err(ZdbError::invalid_parameter("No database opened".to_string())),
-        }
+        };
+        self.session_manager.set_session_db(session_id, db)
     }
 
-    /// Get HTML content for a single entry
-    pub fn get_entry_html_by_index(&mut self, index:&MdxIndex) -> Result<String> {
-        match &mut self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                let index = db.get_index(index.key_index.entry_no)?;
-                return db.get_html(&index, &self.base_url);
-            }
-            Some(DbType::MdxDbGroup(group_db)) => {
-                if let Some(mdx_db) = group_db.mdx_dbs.get_mut(&index.profile_id) {
-                    let index = mdx_db.get_index(index.key_index.entry_no)?;
-                    return mdx_db.get_html(&index, &self.base_url);
+    /// Open (or build, when the group changed) the merged key list of a group without blocking the lookups
+    /// Until it is ready incremental searches merge the key lists of the libraries on the fly
+    /// The build stops when no session uses the group anymore
+    fn open_group_index_in_background(db: &Arc<DbType>) {
+        let db = Arc::downgrade(db);
+        std::thread::spawn(move || {
+            let Some(db) = db.upgrade() else { return };
+            if let DbType::MdxDbGroup(group_db) = db.as_ref()
+                && let Err(e) = group_db.key_index_unless(|| Arc::strong_count(&db) == 1)
+            {
+                if ErrorCode::of(&e) == ErrorCode::Cancelled {
+                    log::info!("Stopped building the group index of {}, the group was closed", group_db.profile.title);
                 } else {
-                    return Err(ZdbError::invalid_parameter(format!("Library with profile_id {} not found in group", index.profile_id)));
+                    log::error!("Failed to build group index of {}: {}", group_db.profile.title, e);
                 }
             }
-            None => return Err(ZdbError::invalid_parameter("No database opened".to_string()))
-        }
+        });
     }
 
-    /// Get binary data from MDD file, returns (data, mime_type)
-    pub fn get_mdd_data(&mut self, profile_id: &ProfileId, file_path: &str) -> Result<Option<(Vec<u8>, String)>> {
-        if file_path=="/$MdxDictIcon" {
-            return self.library_manager.get_icon_for_profile(*profile_id);
-        }
-        
-        match &mut self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                if db.profile.profile_id != *profile_id {
-                    Err(ZdbError::invalid_parameter(format!("Library id not match {}", profile_id)))
-                } else {
-                    db.get_data(file_path)
-                }
-            }
-            Some(DbType::MdxDbGroup(group_db)) => {
-                if let Some(mdx_db) = group_db.mdx_dbs.get_mut(&profile_id) {
-                    mdx_db.get_data(file_path)
-                } else {
-                    Err(ZdbError::invalid_parameter(format!("Library with profile_id {} not found in group", profile_id)))
-                }
-            }
-            None => Err(ZdbError::invalid_parameter("No database opened".to_string()))
-        }
+    /// Get the open database and search results of a session (`None` is the default session)
+    /// Lookups on the returned context don't need the app lock
+    pub fn lookup_context(&self, session_id: Option<SessionId>) -> Result<LookupContext> {
+        self.session_manager.get_session(session_id)?
+            .lookup_context.clone()
+            .ok_or_else(no_database_opened)
+    }
+
+    /// Find an open database containing the library `profile_id`, in any session
+    pub fn find_db_for_profile(&self, profile_id: ProfileId) -> Result<Arc<DbType>> {
+        self.session_manager.find_db_for_profile(profile_id)
+            .ok_or_else(|| invalid_argument(format!("Library with profile_id {} is not open", profile_id)))
     }
 
     // NOTE: is_single_library_mode and is_group_mode methods have been moved to lib.rs
@@ -509,156 +495,70 @@ impl MdictApp {
 
     /// 获取当前主数据库的profile_id，如果没有则返回INVALID_PROFILE_ID
     pub fn get_current_main_profile_id(&self) -> Result<ProfileId> {
-        Ok(match &self.main_db {
-            Some(DbType::MdxDb(db)) => db.profile.profile_id,
-            Some(DbType::MdxDbGroup(db)) => db.profile.profile_id,
-            None => INVALID_PROFILE_ID,
-        })
+        Ok(self.session_manager.get_session(None)?.profile_id())
     }
 
     /// 获取当前主数据库的profile对象
     pub fn get_current_main_profile(&self) -> Result<Option<MdxProfile>> {
-        Ok(match &self.main_db {
-            Some(DbType::MdxDb(db)) => Some(db.profile.clone()),
-            Some(DbType::MdxDbGroup(db)) => Some(db.profile.clone()),
-            None => None,
-        })
+        Ok(self.session_manager.get_session(None)?
+            .lookup_context.as_ref()
+            .map(|context| context.db.profile().clone()))
     }
 
-    pub fn get_content_url(&self, index_no: usize) -> Result<String> {
-        if let Some(DbType::MdxDb(db)) = &self.main_db {
-            Ok(format!("{}entryx?profile_id={}&entry_no={}", self.base_url, db.profile.profile_id, index_no ))
-        } else if let Some(DbType::MdxDbGroup(_group_db)) = &self.main_db {
-            Ok(format!("{}union?index_no={}", self.base_url, index_no))
-        } else {
-            Err(ZdbError::invalid_parameter("No database opened".to_string()))
+    /// Get the content URL of a result list position in a session
+    /// Union pages refer to the session's cached results, so their URL carries the session id
+    pub fn get_content_url(&self, session_id: Option<SessionId>, index_no: usize) -> Result<String> {
+        match self.lookup_context(session_id)?.db.as_ref() {
+            DbType::MdxDb(db) => Ok(format!("{}entryx?profile_id={}&entry_no={}", self.base_url, db.profile.profile_id, index_no )),
+            DbType::MdxDbGroup(_group_db) => match session_id {
+                Some(session_id) if session_id != DEFAULT_SESSION_ID => Ok(format!("{}union?index_no={}&session_id={}", self.base_url, index_no, session_id)),
+                _ => Ok(format!("{}union?index_no={}", self.base_url, index_no)),
+            },
         }
     }
 
-    /// Get the total entry count for the current database
-    pub fn get_entry_count(&self) -> Result<usize> {
-        // Prefer cached grouped results if available (e.g., full-text/group searches)
-        if !self.group_search_results.is_empty() {
-            return Ok(self.group_search_results.len());
-        }
-
-        match &self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                Ok(db.get_entry_count() as usize)
-            }
-            Some(DbType::MdxDbGroup(_group_db)) => {
-                // For group mode, return the count of cached search results
-                Ok(self.group_search_results.len())
-            }
-            None => Err(ZdbError::invalid_parameter("No database opened".to_string()))
-        }
+    /// Navigate a session to a URL, it is added to the session history and sent to the frontend
+    pub fn navigate_to(&mut self, session_id: Option<SessionId>, url: &str) -> Result<()> {
+        self.session_manager.get_session_mut(session_id)?.navigation.navigate(url);
+        self.emit_navigation(session_id, url)
     }
 
-    /// Find the best matching entry index for a given key
-    /// Returns LinkedList<MdxGroupIndex> where:
-    /// - Single dictionary mode: returns single MdxGroupIndex with one result
-    /// - Group mode: returns MdxGroupIndex entries grouped by profile_id
-    pub fn find_index(&mut self, key: &str) -> Result<LinkedList<MdxGroupIndex>> {
-        let mut result = LinkedList::new();
-        
-        match &mut self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                // Single dictionary mode: wrap MdxIndex in MdxGroupIndex and put in LinkedList
-                if let Some(mdx_index) = db.find_index(key, false, false, true)? {
-                        let mut group_index = MdxGroupIndex::default();
-                        group_index.profile_id = db.profile.profile_id;
-                        group_index.primary_key = key.to_string();
-                        group_index.indexes = LinkedList::new();
-                        group_index.indexes.push_back(mdx_index);
-                        result.push_back(group_index);
-                }
-                Ok(result)
-            }
-            Some(DbType::MdxDbGroup(group_db)) => {
-                // Group mode: search across all dictionaries and group by profile_id
-                let mut profile_groups = std::collections::HashMap::<ProfileId, MdxGroupIndex>::new();
-                
-                for mdx_db in group_db.mdx_dbs.values_mut() {
-                    if let Some(mdx_index) = mdx_db.find_index(key, false,   false, true)? {
-                        let profile_id = mdx_index.profile_id;
-                        
-                        // Get or create MdxGroupIndex for this profile_id
-                        let group_index = profile_groups.entry(profile_id).or_insert_with(|| {
-                            MdxGroupIndex {
-                                profile_id,
-                                primary_key: key.to_string(),
-                                indexes: LinkedList::new(),
-                            }
-                        });
-                        
-                        // Add the found index to this profile's group
-                        group_index.indexes.push_back(mdx_index);
-                    }
-                }
-                
-                // Convert HashMap to LinkedList, sorted by profile order
-                if let Some(profiles) = group_db.profile.get_profiles() {
-                    for profile in profiles.iter() {
-                        if let Some(group_index) = profile_groups.remove(&profile.profile_id) {
-                            result.push_back(group_index);
-                        }
-                    }
-                }
-                
-                Ok(result)
-            }
-            None => Ok(result),
+    /// Go back in the history of a session, returns the URL shown
+    pub fn navigate_back(&mut self, session_id: Option<SessionId>) -> Result<Option<String>> {
+        let url = self.session_manager.get_session_mut(session_id)?.navigation.go_back();
+        if let Some(url) = &url {
+            self.emit_navigation(session_id, url)?;
         }
+        Ok(url)
     }
 
-
-    /// Get entries starting from a specific index
-    /// Unified method that works for both single dictionary and group modes
-    /// Returns Vec of (keyword, entry_count)
-    pub fn get_result_key_list(&mut self, start_index: i64, max_count: usize) -> Result<LinkedList<(String, usize)>> {
-        // If we have cached grouped results (e.g., from full-text or grouped search), use them regardless of mode
-        if !self.group_search_results.is_empty() {
-            if start_index >= self.group_search_results.len() as i64 {
-                return Err(ZdbError::invalid_parameter(format!("Start index out of range: {} >= {}", start_index, self.group_search_results.len())));
-            }
-            let mut results = LinkedList::new();
-            for (_, display_key, group_indexes) in self.group_search_results.iter().skip(start_index as usize) {
-                // Entry count equals number of dictionaries (profile groups) contributing to this key
-                let group_count = group_indexes.len();
-                results.push_back((display_key.clone(), group_count));
-                if results.len() >= max_count {
-                    break;
-                }
-            }
-            return Ok(results);
+    /// Go forward in the history of a session, returns the URL shown
+    pub fn navigate_forward(&mut self, session_id: Option<SessionId>) -> Result<Option<String>> {
+        let url = self.session_manager.get_session_mut(session_id)?.navigation.go_forward();
+        if let Some(url) = &url {
+            self.emit_navigation(session_id, url)?;
         }
+        Ok(url)
+    }
 
-        match &mut self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                // Single dictionary mode: get entries directly from the dictionary
-                let indexes = db.get_indexes(start_index, max_count as u64)?;
-                let mut results = LinkedList::new();
-                for index in indexes {
-                    let keyword = index.key_index.key.clone();
-                    // For single dictionary mode, group count is always 1
-                    results.push_back((keyword, 1));
-                }
-                Ok(results)
-            }
-            Some(DbType::MdxDbGroup(_group_db)) => {
-                // Group mode: get entries from cached search results
-                // If we reached here, it means we have no cached grouped results
-                return Ok(LinkedList::new());  
+    /// Emit a navigation event: `navigate_to` with the URL for the default session,
+    /// `session_navigate_to` with the session id and URL for the others
+    fn emit_navigation(&self, session_id: Option<SessionId>, url: &str) -> Result<()> {
+        log::info!("Navigating to URL: {}", url);
+        match session_id {
+            Some(session_id) if session_id != DEFAULT_SESSION_ID => {
+                self.event_sink.emit("session_navigate_to", serde_json::json!({
+                    "session_id": session_id,
+                    "url": url,
+                }))
             }
-            None => Err(ZdbError::invalid_parameter("No database opened".to_string())),
+            _ => self.event_sink.emit("navigate_to", serde_json::Value::String(url.to_string())),
         }
     }
 
-    /// Navigate to a URL by emitting an event to the frontend
-    pub fn navigate_to(&self, url: &str) -> Result<()> {
-        log::info!("Navigating to URL: {}", url);
-        self.app_handle.emit("navigate_to", url).map_err(|e| ZdbError::invalid_data_format(format!("Failed to emit navigate_to event: {}", e)))?;
-        Ok(())
+    /// Get the event sink used to notify the frontend
+    pub fn get_event_sink(&self) -> Arc<dyn EventSink> {
+        self.event_sink.clone()
     }
 
     /// Reload resources (assets and HTML templates)
@@ -669,57 +569,18 @@ impl MdictApp {
         self.assets.clear();
         
         // Reload assets
-        self.load_assets(&self.app_handle.clone())?;        
+        self.load_assets()?;        
         log::info!("Resources reloaded successfully. {} assets", self.assets.len());
         Ok(())
     }
-
-    /// Get indexes for a specific entry position, returning grouped data
-    /// Returns LinkedList<MdxGroupIndex>
-    pub fn get_group_indexes(&mut self, index_no: usize) -> Result<LinkedList<MdxGroupIndex>> {        
-        // If we have cached grouped results, return from cache regardless of mode
-        if !self.group_search_results.is_empty() {
-            if index_no >= self.group_search_results.len() {
-                return Err(ZdbError::invalid_parameter(format!("Index out of range: {} >= {}", index_no, self.group_search_results.len())));
-            }
-            return Ok(self.group_search_results.iter().nth(index_no).unwrap().2.clone());
-        }
-
-        match &mut self.main_db {
-            Some(DbType::MdxDb(db)) => {
-                // Single dictionary mode: return one MdxGroupIndex with single index
-                if index_no >= db.get_entry_count() as usize {
-                    return Err(ZdbError::invalid_parameter(format!("Index out of range: {} >= {}", index_no, db.get_entry_count() as usize)));
-                }
-                
-                let mdx_index = db.get_index(index_no as i64)?;
-                let mut indexes = LinkedList::new();
-                indexes.push_back(mdx_index.clone());
-                
-                let group_index = MdxGroupIndex {
-                    profile_id: db.profile.profile_id,
-                    primary_key: mdx_index.key_index.key.clone(),
-                    indexes,
-                };
-                let mut result = LinkedList::new();
-                result.push_back(group_index);
-                return Ok(result);
-            }
-            Some(DbType::MdxDbGroup(_group_db)) => {
-                // Group mode without cache
-                return Err(ZdbError::invalid_parameter("No grouped search results available".to_string()));
-            }
-            None => return Err(ZdbError::invalid_parameter("No database opened".to_string()))
-        }        
-    }
 }
 
 /// 全局MdictApp实例
 static GLOBAL_MDICT_APP: OnceCell<RwLock<MdictApp>> = OnceCell::new();
 
 /// 初始化全局MdictApp
-pub fn init_mdict_app<P: AsRef<Path>>(app_home_dir: P, extra_search_paths: Vec<String>, app_handle: &tauri::AppHandle) -> Result<()> {
-    let app = MdictApp::init_app_with_home_dir( app_home_dir, extra_search_paths, app_handle)?;
+pub fn init_mdict_app<P: AsRef<Path>>(app_home_dir: P, extra_search_paths: Vec<String>, assets_dir: Option<PathBuf>, event_sink: Arc<dyn EventSink>) -> Result<()> {
+    let app = MdictApp::init_app_with_home_dir( app_home_dir, extra_search_paths, assets_dir, event_sink)?;
     let app_lock = RwLock::new(app);
     
     GLOBAL_MDICT_APP.set(app_lock)
@@ -760,6 +621,33 @@ where
     operation(&mut app)
 }
 
+/// 在会话（None为默认会话）打开的词典上执行查询，查询期间不持有全局锁
+pub fn with_lookup_context<F, R>(session_id: Option<SessionId>, operation: F) -> Result<R>
+where
+    F: FnOnce(&LookupContext) -> Result<R>,
+{
+    let context = with_read_access(|app| app.lookup_context(session_id))?;
+    operation(&context)
+}
+
+/// 同with_lookup_context，同时提供词形还原，用于查找屈折形式的词头
+pub fn with_lookup_morphology<F, R>(session_id: Option<SessionId>, operation: F) -> Result<R>
+where
+    F: FnOnce(&LookupContext, &Morphology) -> Result<R>,
+{
+    let (context, morphology) = with_read_access(|app| Ok((app.lookup_context(session_id)?, app.morphology())))?;
+    operation(&context, &morphology)
+}
+
+/// 在包含指定词典的已打开数据库上执行操作，不持有全局锁
+pub fn with_profile_db<F, R>(profile_id: ProfileId, operation: F) -> Result<R>
+where
+    F: FnOnce(&MdxDb) -> Result<R>,
+{
+    let db = with_read_access(|app| app.find_db_for_profile(profile_id))?;
+    operation(db.get_db(profile_id)?)
+}
+
 /// 简化的模板方法：统一处理配置相关的读操作
 pub fn with_config_read<F, R>(operation: F) -> Result<R>
 where
@@ -847,3 +735,48 @@ where
         .map_err(|_| ZdbError::invalid_parameter("无法获取MdictApp写锁".to_string()))?;
     operation(&mut app.favorites_manager)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Event sink recording emitted events, so the app can be tested without a webview
+    #[derive(Default)]
+    struct RecordingEventSink {
+        events: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl EventSink for RecordingEventSink {
+        fn emit(&self, event: &str, payload: serde_json::Value) -> Result<()> {
+            self.events.lock().unwrap().push((event.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_headless_app() {
+        let home_dir = std::env::temp_dir().join("test_mdict_app_headless");
+        let _ = std::fs::remove_dir_all(&home_dir);
+
+        let sink = Arc::new(RecordingEventSink::default());
+        let mut app = MdictApp::init_app_with_home_dir(&home_dir, Vec::new(), Some(home_dir.join("assets")), sink.clone()).unwrap();
+
+        // No dictionaries in an empty data directory, so nothing is opened
+        assert_eq!(app.get_current_main_profile_id().unwrap(), INVALID_PROFILE_ID);
+        assert!(home_dir.join("doc/config.json").exists());
+
+        app.navigate_to(None, "mdx://mdict.cn/service/entryx?profile_id=1&entry_no=0").unwrap();
+        let session_id = app.session_manager.create_session();
+        app.navigate_to(Some(session_id), "mdx://mdict.cn/service/entryx?profile_id=2&entry_no=0").unwrap();
+        assert!(app.lookup_context(Some(session_id)).is_err());
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, "navigate_to");
+        assert_eq!(events[1].0, "session_navigate_to");
+        assert_eq!(events[1].1["session_id"], session_id);
+
+        drop(app);
+        let _ = std::fs::remove_dir_all(&home_dir);
+    }
+}
@@ -1,11 +1,24 @@
-use std::collections::LinkedList;
+use std::cell::RefCell;
+use std::collections::{HashMap, LinkedList};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::SystemTime;
+use once_cell::sync::OnceCell;
 use serde::{Serialize, Deserialize};
 use url::Url;
 
-use mdx::{Result, MdxReader};
+use mdx::{Result, MdxReader, ZdbError};
 use mdx::storage::{KeyIndex, EntryNo};
-use mdx::utils::MdxHtmlRewriter;
+use mdx::utils::{with_extension, MdxHtmlRewriter};
 
+use crate::fts_query::{EntryWords, Field, FtsQuery};
+use crate::html_text::html_to_text;
+use crate::key_pattern::KeyPattern;
+use crate::language::{self, Languages};
+use crate::chinese_conv::{self, ChineseConversion};
+use crate::pinyin::PinyinIndex;
+use crate::spelling;
 use crate::mdx_profile::{MdxProfile, ProfileId};
 
 
@@ -15,46 +28,173 @@ pub struct MdxIndex {
     pub key_index: KeyIndex,
 }
 
+/// Content of alias entries is only `@@@LINK=target`
+const LINK_PREFIX: &str = "@@@LINK=";
+/// Maximum number of redirects followed for one entry
+const MAX_LINK_DEPTH: usize = 8;
+
+/// Number of headwords read at a time by key scans
+const SCAN_CHUNK_SIZE: u64 = 1024;
+
+/// Candidate entries read for each full-text result, the query is checked on every candidate
+const FTS_CANDIDATE_FACTOR: usize = 4;
+
+/// Chunks of headwords sampled to detect the scripts of a dictionary, and headwords per chunk
+const LANGUAGE_SAMPLE_CHUNKS: u64 = 16;
+const LANGUAGE_SAMPLE_CHUNK_SIZE: u64 = 16;
+
+/// Maximum number of readers opened for one database
+/// Each reader can serve one lookup at a time, so this limits the parallel lookups per database
+const MAX_READERS_PER_DB: usize = 4;
+
+thread_local! {
+    /// Databases the current thread has borrowed a reader of, by address
+    static BORROWED_READERS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Idle readers and the number of readers opened so far
+struct ReaderPool {
+    idle: Vec<MdxReader>,
+    open_count: usize,
+}
+
+/// Reader borrowed from the pool, returned to it when dropped
+struct PooledReader<'a> {
+    db: &'a MdxDb,
+    reader: Option<MdxReader>,
+}
+
+impl Deref for PooledReader<'_> {
+    type Target = MdxReader;
+    fn deref(&self) -> &MdxReader {
+        self.reader.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledReader<'_> {
+    fn deref_mut(&mut self) -> &mut MdxReader {
+        self.reader.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        let address = self.db as *const MdxDb as usize;
+        BORROWED_READERS.with_borrow_mut(|borrowed| borrowed.retain(|db| *db != address));
+        if let Some(reader) = self.reader.take() {
+            let mut pool = self.db.pool.lock().unwrap_or_else(|e| e.into_inner());
+            pool.idle.push(reader);
+            self.db.reader_returned.notify_one();
+        }
+    }
+}
+
+/// A dictionary database
+/// Lookups take `&self`, each one borrows a reader from a small pool so that
+/// several threads can read the same database at the same time
+/// Readers are only reached through the pool mutex, which hands each one to a single `PooledReader` at a time,
+/// so `MdxDb` is Sync as long as `MdxReader` is Send
 pub struct MdxDb {
     pub profile: MdxProfile,
-    pub mdx_reader: MdxReader,
-
+    device_id: String,
+    db_name: String,
+    description: String,
+    entry_count: u64,
+    pool: Mutex<ReaderPool>,
+    reader_returned: Condvar,
+    /// Built on the first pinyin search
+    pinyin_index: OnceCell<PinyinIndex>,
+    /// Detected on the first lookup routed by language
+    languages: OnceCell<Languages>,
+    /// Results of `resolve_key` by entry, so listing a key again doesn't read its content again
+    resolved_keys: Mutex<HashMap<EntryNo, Option<String>>>,
+    /// Simplified/Traditional conversion of the app, for lookups and merge keys
+    chinese_conversion: Arc<ChineseConversion>,
 }
 
 impl MdxDb {
-    pub fn new(profile: &MdxProfile, device_id: &str) -> Result<Self> {
-        let mdx_reader = MdxReader::from_url(
-            &Url::parse(&profile.url)?,
-            device_id,
-        )?;
+    pub fn new(profile: &MdxProfile, device_id: &str, chinese_conversion: Arc<ChineseConversion>) -> Result<Self> {
+        let mdx_reader = Self::open_reader(profile, device_id)?;
         
         Ok(Self {
             profile: profile.clone(),
-            mdx_reader,            
+            device_id: device_id.to_string(),
+            db_name: mdx_reader.db_name.clone(),
+            description: mdx_reader.content_db.meta.db_info.description.clone(),
+            entry_count: mdx_reader.get_entry_count(),
+            pool: Mutex::new(ReaderPool { idle: vec![mdx_reader], open_count: 1 }),
+            reader_returned: Condvar::new(),
+            pinyin_index: OnceCell::new(),
+            languages: OnceCell::new(),
+            resolved_keys: Mutex::new(HashMap::new()),
+            chinese_conversion,
         })
     }
+
+    fn open_reader(profile: &MdxProfile, device_id: &str) -> Result<MdxReader> {
+        MdxReader::from_url(
+            &Url::parse(&profile.url)?,
+            device_id,
+        )
+    }
+
+    /// Borrow a reader, opening a new one if all are busy and the pool is not full,
+    /// otherwise wait until one is returned
+    /// A thread holds at most one reader of a database: with all readers borrowed, waiting for a second one
+    /// while holding the first could wait forever, so borrowing again fails instead (release the reader
+    /// before calling methods that borrow one, e.g. don't look up entries in a `scan_keys` visitor)
+    fn reader(&self) -> Result<PooledReader<'_>> {
+        let address = self as *const MdxDb as usize;
+        if BORROWED_READERS.with_borrow(|borrowed| borrowed.contains(&address)) {
+            return Err(ZdbError::from(std::io::Error::other(format!("A reader of {} is already borrowed by this thread", self.db_name))));
+        }
+        let mut pool = self.pool.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(reader) = pool.idle.pop() {
+                return Ok(self.lend(reader));
+            }
+            if pool.open_count < MAX_READERS_PER_DB {
+                pool.open_count += 1;
+                drop(pool);
+                return match Self::open_reader(&self.profile, &self.device_id) {
+                    Ok(reader) => Ok(self.lend(reader)),
+                    Err(e) => {
+                        self.pool.lock().unwrap_or_else(|e| e.into_inner()).open_count -= 1;
+                        self.reader_returned.notify_one();
+                        Err(e)
+                    }
+                };
+            }
+            pool = self.reader_returned.wait(pool).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn lend(&self, reader: MdxReader) -> PooledReader<'_> {
+        BORROWED_READERS.with_borrow_mut(|borrowed| borrowed.push(self as *const MdxDb as usize));
+        PooledReader { db: self, reader: Some(reader) }
+    }
     
     pub fn get_title(&self) -> &String{
-        &self.mdx_reader.db_name
+        &self.db_name
     }
 
     pub fn get_description(&self) -> String{
-        self.mdx_reader.content_db.meta.db_info.description.clone()
+        self.description.clone()
     }
 
     pub fn get_entry_count(&self) -> u64 {
-        self.mdx_reader.get_entry_count()
+        self.entry_count
     }
 
-    pub fn get_index(&mut self, entry_no: EntryNo) -> Result<MdxIndex> {
+    pub fn get_index(&self, entry_no: EntryNo) -> Result<MdxIndex> {
         Ok(MdxIndex {
             profile_id: self.profile.profile_id,
-            key_index: self.mdx_reader.get_index(entry_no)?.into(),
+            key_index: self.reader()?.get_index(entry_no)?.into(),
         })
     }
 
-    pub fn get_indexes(&mut self, start_entry_no: EntryNo, max_count: u64) -> Result<LinkedList<MdxIndex>> {
-        let indexes = self.mdx_reader.get_indexes(start_entry_no, max_count)?;
+    pub fn get_indexes(&self, start_entry_no: EntryNo, max_count: u64) -> Result<LinkedList<MdxIndex>> {
+        let indexes = self.reader()?.get_indexes(start_entry_no, max_count)?;
         let mut mdx_indexes = LinkedList::new();
         for index in indexes {
             mdx_indexes.push_back(MdxIndex {
@@ -65,8 +205,68 @@ impl MdxDb {
         Ok(mdx_indexes)
     }
 
-    pub fn find_index(&mut self, key: &str, prefix_match: bool, partial_match: bool, best_match: bool) -> Result<Option<MdxIndex>> {
-        let key_index = self.mdx_reader.find_index(key, prefix_match, partial_match, best_match)?;
+    /// Languages of the dictionary: those set in the options, otherwise detected from the title and
+    /// description, with the scripts of a sample of headwords spread over the whole key list
+    pub fn languages(&self) -> &Languages {
+        self.languages.get_or_init(|| {
+            let options = &self.profile.options;
+            let sampled_scripts = match self.sample_keys() {
+                Ok(keys) => language::headword_scripts(keys.iter().map(String::as_str)),
+                Err(e) => {
+                    log::warn!("Failed to sample the headwords of {}: {}", self.db_name, e);
+                    Vec::new()
+                }
+            };
+            let header = format!("{}\n{}", self.db_name, self.description);
+            let languages = Languages::detect(&options.source_language, &options.target_language, &header, sampled_scripts);
+            log::info!("Languages of {}: {:?}", self.db_name, languages);
+            languages
+        })
+    }
+
+    /// Headwords of `LANGUAGE_SAMPLE_CHUNKS` evenly spaced chunks of the key list
+    fn sample_keys(&self) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let step = (self.entry_count / LANGUAGE_SAMPLE_CHUNKS).max(1);
+        let mut reader = self.reader()?;
+        for chunk in 0..LANGUAGE_SAMPLE_CHUNKS {
+            let start = chunk * step;
+            if start >= self.entry_count {
+                break;
+            }
+            let count = LANGUAGE_SAMPLE_CHUNK_SIZE.min(self.entry_count - start);
+            keys.extend(reader.get_indexes(start as EntryNo, count)?.into_iter().map(|index| index.key));
+        }
+        Ok(keys)
+    }
+
+    /// Find the entry of `key`, with Chinese conversion enabled a key written in the other script
+    /// (Simplified/Traditional) is found when the key itself is not
+    pub fn find_index(&self, key: &str, prefix_match: bool, partial_match: bool, best_match: bool) -> Result<Option<MdxIndex>> {
+        let mdx_index = self.find_index_as_written(key, prefix_match, partial_match, best_match)?;
+        let Some(converter) = self.chinese_conversion.converter() else { return Ok(mdx_index) };
+        if mdx_index.as_ref().is_some_and(|index| Self::key_matches(&index.key_index.key, key, prefix_match)) {
+            return Ok(mdx_index);
+        }
+        for variant in converter.variants(key) {
+            if let Some(index) = self.find_index_as_written(&variant, prefix_match, partial_match, best_match)?
+                && Self::key_matches(&index.key_index.key, &variant, prefix_match)
+            {
+                return Ok(Some(index));
+            }
+        }
+        Ok(mdx_index)
+    }
+
+    /// Whether a found headword is the key (or starts with it for prefix matches), ignoring case
+    fn key_matches(found_key: &str, key: &str, prefix_match: bool) -> bool {
+        let found_key = found_key.to_lowercase();
+        let key = key.to_lowercase();
+        if prefix_match { found_key.starts_with(&key) } else { found_key == key }
+    }
+
+    fn find_index_as_written(&self, key: &str, prefix_match: bool, partial_match: bool, best_match: bool) -> Result<Option<MdxIndex>> {
+        let key_index = self.reader()?.find_index(key, prefix_match, partial_match, best_match)?;
         if let Some(key_index) = key_index {
             Ok(Some(MdxIndex {
                 profile_id: self.profile.profile_id,
@@ -77,25 +277,24 @@ impl MdxDb {
         }
     }
 
-    pub fn normalize_keyword(keyword: &str) -> String {
-        keyword.chars()
-            .filter(|c| c.is_alphabetic())
-            .map(|c| c.to_lowercase().collect::<String>())
-            .collect::<String>()
+    /// Key used to merge headwords, following the key normalization policy of the profile
+    pub fn normalize_keyword(&self, keyword: &str) -> String {
+        self.profile.options.key_normalization.normalize(keyword, self.chinese_conversion.converter())
     }
 
-    pub fn find_similar_index(&mut self, key: &str, prefix_match: bool, partial_match: bool, best_match: bool, max_results: usize) -> Result<LinkedList<MdxIndex>> {
-        let key_index = self.mdx_reader.find_index(key, prefix_match, partial_match, best_match)?;
+    pub fn find_similar_index(&self, key: &str, prefix_match: bool, partial_match: bool, best_match: bool, max_results: usize) -> Result<LinkedList<MdxIndex>> {
+        let mut reader = self.reader()?;
+        let key_index = reader.find_index(key, prefix_match, partial_match, best_match)?;
         let mut mdx_indexes = LinkedList::new();
         if let Some(key_index) = key_index {
             mdx_indexes.push_back(MdxIndex {
                 profile_id: self.profile.profile_id,
                 key_index: key_index.clone(),
             });
-            let normalized_key = Self::normalize_keyword(&key_index.key);
+            let normalized_key = self.normalize_keyword(&key_index.key);
             for i in 1 .. std::cmp::min(max_results as u64, self.get_entry_count() - key_index.entry_no as u64) {
-                let index = self.mdx_reader.get_index(key_index.entry_no + i as EntryNo)?;
-                if normalized_key == Self::normalize_keyword(&index.key) {
+                let index = reader.get_index(key_index.entry_no + i as EntryNo)?;
+                if normalized_key == self.normalize_keyword(&index.key) {
                     mdx_indexes.push_back(MdxIndex {
                         profile_id: self.profile.profile_id,
                         key_index: index.into(),
@@ -108,36 +307,361 @@ impl MdxDb {
         Ok(mdx_indexes)
     }
     
-    //Get the contents of the entry
-    pub fn get_html(&mut self, entry: &MdxIndex, base_url:&str)->Result<String> {
-        let key_index: KeyIndex = entry.key_index.clone().into();
-        MdxHtmlRewriter::rewrite_html_with_base_url(&self.mdx_reader.get_html(&key_index)?, self.profile.profile_id, base_url)
+    /// Find the headwords matching a wildcard or regex pattern, in key order
+    /// With a literal prefix only the range of keys starting with it is scanned, otherwise all keys are
+    pub fn find_pattern(&self, pattern: &KeyPattern, max_results: usize) -> Result<LinkedList<MdxIndex>> {
+        let mut results = LinkedList::new();
+        let prefix = pattern.literal_prefix().to_lowercase();
+        let mut start_entry_no: EntryNo = 0;
+        if !prefix.is_empty() {
+            match self.find_index(pattern.literal_prefix(), true, false, true)? {
+                Some(index) => start_entry_no = index.key_index.entry_no,
+                None => return Ok(results),
+            }
+        }
+
+        self.scan_keys(start_entry_no, |key_index| {
+            if !prefix.is_empty() && !key_index.key.to_lowercase().starts_with(&prefix) {
+                // Left the range of keys starting with the prefix
+                return false;
+            }
+            if pattern.is_match(&key_index.key) {
+                results.push_back(MdxIndex {
+                    profile_id: self.profile.profile_id,
+                    key_index,
+                });
+            }
+            results.len() < max_results
+        })?;
+        Ok(results)
     }
 
-    pub fn get_data(&mut self, file_path:&str) -> Result<Option<(Vec<u8>, String)>> {
-        self.mdx_reader.get_data(file_path)
+    /// Spelling suggestions for `word`: headwords within a small weighted edit distance,
+    /// returns (distance, index) pairs ordered by distance
+    pub fn suggest(&self, word: &str, max_results: usize) -> Result<Vec<(f32, MdxIndex)>> {
+        let word = word.to_lowercase();
+        let max_distance = spelling::max_distance(&word);
+        let word_len = word.chars().count();
+        let mut candidates = Vec::new();
+        self.scan_keys(0, |key_index| {
+            let key = key_index.key.to_lowercase();
+            // Cheap length filter before computing the distance
+            if key.chars().count().abs_diff(word_len) as f32 <= max_distance && key != word {
+                let distance = spelling::edit_distance(&word, &key);
+                if distance <= max_distance {
+                    candidates.push((distance, MdxIndex {
+                        profile_id: self.profile.profile_id,
+                        key_index,
+                    }));
+                }
+            }
+            true
+        })?;
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.key_index.key.cmp(&b.1.key_index.key)));
+        candidates.truncate(max_results);
+        Ok(candidates)
     }
 
-    /// Perform full-text search on the database content
-    /// Returns a vector of MdxIndex results with their search scores
-    pub fn fulltext_find(&mut self, query: &str, max_results: usize) -> Result<Vec<(f32, MdxIndex)>> {
-        let search_results = self.mdx_reader.fts_search(query, max_results)?;
-        let mut mdx_results = Vec::new();
-        
-        for (score, entry_no, _key) in search_results {
-            let key_index = self.mdx_reader.get_index(entry_no)?;
-            let mdx_index = MdxIndex {
+    /// Find the Chinese headwords whose pinyin starts with `query`, exact readings first
+    /// A dictionary whose headwords can't be Chinese has no results, and no pinyin index is built for it
+    pub fn find_pinyin(&self, query: &str, max_results: usize) -> Result<LinkedList<MdxIndex>> {
+        let mut results = LinkedList::new();
+        if !self.languages().can_be_chinese() {
+            return Ok(results);
+        }
+        for entry_no in self.pinyin_index()?.search(query, max_results) {
+            results.push_back(self.get_index(entry_no as EntryNo)?);
+        }
+        Ok(results)
+    }
+
+    /// The pinyin index, loaded from the cache file next to the .mdx file or built and cached
+    fn pinyin_index(&self) -> Result<&PinyinIndex> {
+        self.pinyin_index.get_or_try_init(|| {
+            let cache_path = self.pinyin_cache_path();
+            if let Some(path) = &cache_path
+                && Self::is_cache_current(path, &self.profile.url)
+            {
+                match PinyinIndex::load(path, self.entry_count) {
+                    Ok(Some(index)) => return Ok(index),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Failed to load pinyin index {:?}: {}", path, e),
+                }
+            }
+
+            let mut headwords = Vec::new();
+            self.scan_keys(0, |key_index| {
+                if key_index.key.chars().any(chinese_conv::is_han) {
+                    headwords.push((key_index.entry_no as u64, key_index.key));
+                }
+                true
+            })?;
+            let index = PinyinIndex::build(headwords);
+            log::info!("Built pinyin index of {} ({} readings)", self.db_name, index.len());
+            if let Some(path) = &cache_path
+                && let Err(e) = index.save(path, self.entry_count)
+            {
+                log::warn!("Failed to save pinyin index {:?}: {}", path, e);
+            }
+            Ok(index)
+        })
+    }
+
+    /// Cache file of the pinyin index, beside the .mdx file like the fulltext .idx
+    /// None for dictionaries that are not local files
+    fn pinyin_cache_path(&self) -> Option<PathBuf> {
+        let url = Url::parse(&self.profile.url).ok()?;
+        with_extension(&url, "pinyin").ok()?.to_file_path().ok()
+    }
+
+    /// Modification time of the dictionary file, None for dictionaries that are not local files
+    pub fn file_modified(&self) -> Option<SystemTime> {
+        let path = Url::parse(&self.profile.url).ok()?.to_file_path().ok()?;
+        std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// Whether the cache file is newer than the dictionary
+    fn is_cache_current(cache_path: &Path, mdx_url: &str) -> bool {
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        let mdx_path = Url::parse(mdx_url).ok().and_then(|url| url.to_file_path().ok());
+        match (modified(cache_path), mdx_path.as_deref().and_then(modified)) {
+            (Some(cache_time), Some(mdx_time)) => cache_time >= mdx_time,
+            _ => false,
+        }
+    }
+
+    /// Read the headwords in key order starting at `entry_no`, until `visit` returns false
+    fn scan_keys<F>(&self, mut entry_no: EntryNo, mut visit: F) -> Result<()>
+    where
+        F: FnMut(KeyIndex) -> bool,
+    {
+        let mut reader = self.reader()?;
+        while (entry_no as u64) < self.entry_count {
+            let mut read_count = 0;
+            for key_index in reader.get_indexes(entry_no, SCAN_CHUNK_SIZE)? {
+                read_count += 1;
+                entry_no = key_index.entry_no + 1;
+                if !visit(key_index.into()) {
+                    return Ok(());
+                }
+            }
+            if read_count == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Target key of a redirect entry (`@@@LINK=target`), None for a normal entry
+    pub fn link_target(html: &str) -> Option<&str> {
+        html.trim_start()
+            .strip_prefix(LINK_PREFIX)
+            .map(|target| target.trim_matches(|c: char| c.is_whitespace() || c == '\0'))
+    }
+
+    /// Key shown for a redirect entry in result lists, like "colour → color"
+    pub fn redirect_display_key(key: &str, target: &str) -> String {
+        format!("{} \u{2192} {}", key, target)
+    }
+
+    /// Follow `@@@LINK=` redirects starting at `entry`
+    /// Returns the final entry and its raw content, see `follow_links`
+    pub fn resolve_entry(&self, entry: &MdxIndex) -> Result<(MdxIndex, String)> {
+        let reader = RefCell::new(self.reader()?);
+        Self::follow_links(
+            entry,
+            |entry| reader.borrow_mut().get_html(&entry.key_index),
+            |key| Ok(reader.borrow_mut().find_index(key, false, false, false)?.map(|key_index| MdxIndex {
                 profile_id: self.profile.profile_id,
                 key_index: key_index.into(),
+            })),
+        )
+    }
+
+    /// Follow `@@@LINK=` redirects starting at `entry`, `read` gives the stored content of an entry
+    /// and `find` the entry of a redirect target in the same dictionary
+    /// A missing target, a loop or a too deep chain is logged and the last entry reached is returned with its link
+    pub fn follow_links<R, F>(entry: &MdxIndex, mut read: R, mut find: F) -> Result<(MdxIndex, String)>
+    where
+        R: FnMut(&MdxIndex) -> Result<String>,
+        F: FnMut(&str) -> Result<Option<MdxIndex>>,
+    {
+        let mut entry = entry.clone();
+        let mut html = read(&entry)?;
+        let mut visited = vec![entry.key_index.entry_no];
+        while let Some(target) = Self::link_target(&html) {
+            if visited.len() > MAX_LINK_DEPTH {
+                log::warn!("Too many redirects from entry {}, stopped at {}", visited[0], entry.key_index.key);
+                break;
+            }
+            let Some(next) = find(target)? else {
+                log::warn!("Redirect target {} of {} not found", target, entry.key_index.key);
+                break;
             };
-            mdx_results.push((score, mdx_index));
+            if visited.contains(&next.key_index.entry_no) {
+                log::warn!("Redirect loop at {} from entry {}", target, visited[0]);
+                break;
+            }
+            visited.push(next.key_index.entry_no);
+            html = read(&next)?;
+            entry = next;
+        }
+        Ok((entry, html))
+    }
+
+    /// Stored content of an entry, redirects are not followed
+    pub fn get_stored_html(&self, entry: &MdxIndex) -> Result<String> {
+        self.reader()?.get_html(&entry.key_index)
+    }
+
+    /// Get the final key of a redirect entry, None if `entry` is not a redirect
+    /// Result lists show the target of every listed key, so the result is cached for each entry
+    pub fn resolve_key(&self, entry: &MdxIndex) -> Result<Option<String>> {
+        let entry_no = entry.key_index.entry_no;
+        if let Some(resolved_key) = self.resolved_keys.lock().unwrap_or_else(|e| e.into_inner()).get(&entry_no) {
+            return Ok(resolved_key.clone());
+        }
+        let (resolved, _) = self.resolve_entry(entry)?;
+        let resolved_key = Some(resolved.key_index.key).filter(|_| resolved.key_index.entry_no != entry_no);
+        self.resolved_keys.lock().unwrap_or_else(|e| e.into_inner()).insert(entry_no, resolved_key.clone());
+        Ok(resolved_key)
+    }
+
+    /// Get the contents of the entry, redirects are followed
+    /// Returns the entry actually shown together with its HTML
+    pub fn get_resolved_html(&self, entry: &MdxIndex, base_url: &str) -> Result<(MdxIndex, String)> {
+        let (resolved, html) = self.resolve_entry(entry)?;
+        let html = MdxHtmlRewriter::rewrite_html_with_base_url(&html, self.profile.profile_id, base_url)?;
+        Ok((resolved, html))
+    }
+
+    //Get the contents of the entry
+    pub fn get_html(&self, entry: &MdxIndex, base_url:&str)->Result<String> {
+        Ok(self.get_resolved_html(entry, base_url)?.1)
+    }
+
+    pub fn get_data(&self, file_path:&str) -> Result<Option<(Vec<u8>, String)>> {
+        self.reader()?.get_data(file_path)
+    }
+
+    /// Perform full-text search on the database content
+    /// The full-text index (and the headwords for `headword:` terms) gives candidate entries,
+    /// each of them is checked against the whole query, see `FtsQuery`
+    /// Returns the matching entries with their search scores, best first
+    pub fn fulltext_find(&self, query: &FtsQuery, max_results: usize) -> Result<Vec<(f32, MdxIndex)>> {
+        let limit = max_results.saturating_mul(FTS_CANDIDATE_FACTOR);
+        // Sets that are intersected are read whole, the best candidates are taken after intersecting
+        let mut candidates: Vec<(EntryNo, f32)> = query
+            .candidates(&mut |field: Field, phrase: &[String], complete: bool| {
+                self.fts_candidates(field, phrase, if complete { self.entry_count as usize } else { limit })
+            })?
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(limit);
+
+        let mut reader = self.reader()?;
+        let mut mdx_results = Vec::new();
+        for (entry_no, score) in candidates {
+            if mdx_results.len() >= max_results {
+                break;
+            }
+            let key_index: KeyIndex = reader.get_index(entry_no)?.into();
+            let html = reader.get_html(&key_index)?;
+            let body = if Self::link_target(&html).is_some() { String::new() } else { html_to_text(&html) };
+            if query.matches(&EntryWords::new(&key_index.key, &body)) {
+                mdx_results.push((score, MdxIndex {
+                    profile_id: self.profile.profile_id,
+                    key_index,
+                }));
+            }
         }
-        
         Ok(mdx_results)
     }
 
+    /// Entries that may have `phrase` in `field` with their search scores, at most `limit`
+    /// Headwords are scanned for `headword:` terms and score 0, the others are looked up in the full-text index
+    fn fts_candidates(&self, field: Field, phrase: &[String], limit: usize) -> Result<HashMap<EntryNo, f32>> {
+        let mut found = HashMap::new();
+        if field == Field::Headword {
+            let headword_query = FtsQuery::Phrase { field, words: phrase.to_vec() };
+            self.scan_keys(0, |key_index| {
+                if headword_query.matches(&EntryWords::new(&key_index.key, "")) {
+                    found.insert(key_index.entry_no, 0.0);
+                }
+                found.len() < limit
+            })?;
+        } else {
+            for (score, entry_no, _key) in self.reader()?.fts_search(&phrase.join(" "), limit)? {
+                let best = found.entry(entry_no).or_insert(score);
+                *best = best.max(score);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Full-text search with scores comparable between dictionaries, ordered by descending score
+    /// See `normalize_scores`
+    pub fn fulltext_ranked(&self, query: &FtsQuery, max_results: usize) -> Result<Vec<(f32, MdxIndex)>> {
+        let mut hits = self.fulltext_find(query, max_results)?;
+        Self::normalize_scores(&mut hits, self.profile.options.fts_weight());
+        Ok(hits)
+    }
+
+    /// Scale the scores so that the best hit scores `weight` and sort them by descending score
+    /// Raw scores depend on the size and vocabulary of each dictionary and can't be compared
+    fn normalize_scores(hits: &mut [(f32, MdxIndex)], weight: f32) {
+        let best = hits.iter().map(|(score, _)| *score).fold(0.0, f32::max);
+        for (score, _) in hits.iter_mut() {
+            *score = if best > 0.0 { *score / best * weight } else { weight };
+        }
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+    }
+
     /// Check if full-text search is available for this database
     pub fn is_fts_available(&self) -> bool {
-        self.mdx_reader.is_fts_available()
+        self.reader().map(|reader| reader.is_fts_available()).unwrap_or(false)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_target() {
+        assert_eq!(MdxDb::link_target("@@@LINK=color\r\n\0"), Some("color"));
+        assert_eq!(MdxDb::link_target("<b>colour</b>"), None);
+        assert_eq!(MdxDb::redirect_display_key("colour", "color"), "colour \u{2192} color");
+    }
+
+    #[test]
+    fn test_follow_links() {
+        let entries = [("colour", "@@@LINK=color"), ("color", "<b>color</b>"), ("hue", "@@@LINK=tint"), ("loop", "@@@LINK=loop")];
+        let index = |entry_no: usize| MdxIndex {
+            profile_id: 1,
+            key_index: KeyIndex { entry_no: entry_no as EntryNo, key: entries[entry_no].0.to_string(), ..Default::default() },
+        };
+        let follow = |entry_no: usize| MdxDb::follow_links(
+            &index(entry_no),
+            |entry| Ok(entries[entry.key_index.entry_no as usize].1.to_string()),
+            |key| Ok(entries.iter().position(|(entry_key, _)| *entry_key == key).map(index)),
+        ).map(|(entry, html)| (entry.key_index.key, html)).unwrap();
+        assert_eq!(follow(0), ("color".to_string(), "<b>color</b>".to_string()));
+        // A dangling target or a loop leaves the link itself
+        assert_eq!(follow(2), ("hue".to_string(), "@@@LINK=tint".to_string()));
+        assert_eq!(follow(3), ("loop".to_string(), "@@@LINK=loop".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_scores() {
+        let hit = |score: f32, entry_no: EntryNo| (score, MdxIndex {
+            profile_id: 1,
+            key_index: KeyIndex { entry_no, ..Default::default() },
+        });
+        let mut hits = vec![hit(2.0, 0), hit(8.0, 1), hit(4.0, 2)];
+        MdxDb::normalize_scores(&mut hits, 0.5);
+        let ranked: Vec<(f32, EntryNo)> = hits.iter().map(|(score, index)| (*score, index.key_index.entry_no)).collect();
+        assert_eq!(ranked, vec![(0.5, 1), (0.25, 2), (0.125, 0)]);
+    }
+}
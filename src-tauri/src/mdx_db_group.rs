@@ -1,7 +1,24 @@
-use std::collections::{HashMap, LinkedList};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, LinkedList, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
+use icu_collator::options::{CollatorOptions, Strength};
+use icu_collator::{Collator, CollatorBorrowed};
+use icu_locale::Locale;
 use mdx::{Result, ZdbError};
+use mdx::storage::EntryNo;
+use once_cell::sync::OnceCell;
 
+use crate::chinese_conv::ChineseConversion;
+use crate::error::{cancelled, invalid_argument};
+use crate::fts_query::FtsQuery;
+use crate::group_index::GroupKeyIndex;
+use crate::japanese;
+use crate::key_pattern::KeyPattern;
+use crate::language::{scripts_of, Languages};
 use crate::mdx_db::{MdxDb, MdxIndex};
 use crate::mdx_profile::{MdxProfile, ProfileId};
 
@@ -12,103 +29,423 @@ pub struct MdxGroupIndex {
     pub indexes: LinkedList<MdxIndex>,
 }
 
+/// Merged keys in order: (normalized_key, display_key, one MdxGroupIndex per library having the key)
+pub type MergedKeys = LinkedList<(String, String, LinkedList<MdxGroupIndex>)>;
+
+/// Keys read from a library at a time while merging
+const MERGE_CHUNK_SIZE: u64 = 64;
+/// Merged keys written to the group index at a time while it is built
+const INDEX_CHUNK_SIZE: usize = 1024;
+
+/// Where a merge of the libraries' key lists stopped, so that it can be continued
+#[derive(Debug, Clone, Default)]
+pub struct GroupKeyCursor {
+    /// First entry not merged yet of each library, exhausted libraries are removed
+    next_entry_nos: HashMap<ProfileId, EntryNo>,
+}
+
+impl GroupKeyCursor {
+    pub fn is_exhausted(&self) -> bool {
+        self.next_entry_nos.is_empty()
+    }
+}
+
+/// Key list of a library as read by the merge
+trait KeySource {
+    fn profile_id(&self) -> ProfileId;
+    fn entry_count(&self) -> u64;
+    fn read_keys(&self, start_entry_no: EntryNo, max_count: u64) -> Result<LinkedList<MdxIndex>>;
+}
+
+impl KeySource for MdxDb {
+    fn profile_id(&self) -> ProfileId {
+        self.profile.profile_id
+    }
+
+    fn entry_count(&self) -> u64 {
+        self.get_entry_count()
+    }
+
+    fn read_keys(&self, start_entry_no: EntryNo, max_count: u64) -> Result<LinkedList<MdxIndex>> {
+        self.get_indexes(start_entry_no, max_count)
+    }
+}
+
+/// Keys of one library in key order, read ahead in chunks
+struct KeyStream<'a> {
+    source: &'a dyn KeySource,
+    buffer: VecDeque<MdxIndex>,
+    next_read: EntryNo,
+    next_unmerged: EntryNo,
+}
+
+impl<'a> KeyStream<'a> {
+    fn new(source: &'a dyn KeySource, entry_no: EntryNo) -> Self {
+        Self { source, buffer: VecDeque::new(), next_read: entry_no, next_unmerged: entry_no }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.next_unmerged as u64 >= self.source.entry_count()
+    }
+
+    fn peek(&mut self) -> Result<Option<&MdxIndex>> {
+        if self.buffer.is_empty() && (self.next_read as u64) < self.source.entry_count() {
+            self.buffer.extend(self.source.read_keys(self.next_read, MERGE_CHUNK_SIZE)?);
+            self.next_read = match self.buffer.back() {
+                Some(index) => index.key_index.entry_no + 1,
+                None => self.source.entry_count() as EntryNo,
+            };
+        }
+        Ok(self.buffer.front())
+    }
+
+    fn pop(&mut self) -> Option<MdxIndex> {
+        let index = self.buffer.pop_front()?;
+        self.next_unmerged = index.key_index.entry_no + 1;
+        Some(index)
+    }
+}
+
 pub struct MdxDbGroup {
     pub profile: MdxProfile,
     pub mdx_dbs: HashMap<ProfileId, MdxDb>,
+    /// Order of the merged key lists, from the `collation_locale` option of the group
+    collator: CollatorBorrowed<'static>,
+    /// The same order ignoring case and accents, so that the floor of a search for "Apple" keeps "apple"
+    floor_collator: CollatorBorrowed<'static>,
+    /// File of the persistent merged key list
+    index_path: PathBuf,
+    /// Opened or built on first use, see `key_index`
+    key_index: OnceCell<GroupKeyIndex>,
+    /// Simplified/Traditional conversion of the app, shared with the libraries
+    chinese_conversion: Arc<ChineseConversion>,
 }
 
 impl MdxDbGroup {
-    pub fn new(profile: &MdxProfile, device_id: &str) -> Result<Self> {
+    /// The merged key list of the group is kept in `index_dir`
+    pub fn new(profile: &MdxProfile, device_id: &str, index_dir: &Path, chinese_conversion: Arc<ChineseConversion>) -> Result<Self> {
         if !profile.as_union || profile.get_profiles().is_none() {
             return Err(ZdbError::invalid_parameter("Not a union group or no sub profiles"));
         }
         let mut mdx_dbs = HashMap::new();
         for profile in profile.get_profiles().unwrap().iter() {
             if !profile.disabled {
-                let mdx_db = MdxDb::new(profile, device_id)?;
+                let mdx_db = MdxDb::new(profile, device_id, chinese_conversion.clone())?;
                 mdx_dbs.insert(profile.profile_id, mdx_db);
             }
         }
-        Ok(Self { profile: profile.clone(), mdx_dbs })
+        let collator = Self::collator(&profile.options.collation_locale, None);
+        let floor_collator = Self::collator(&profile.options.collation_locale, Some(Strength::Primary));
+        Ok(Self {
+            profile: profile.clone(),
+            mdx_dbs,
+            collator,
+            floor_collator,
+            index_path: index_dir.join(format!("{}.db", profile.profile_id)),
+            key_index: OnceCell::new(),
+            chinese_conversion,
+        })
     }
 
-    pub fn find_best_match_indexes(&mut self, query: &str, max_results_per_lib: usize) -> Result<LinkedList<(String, String, LinkedList<MdxGroupIndex>)>> {
-        // Map normalized_key -> (display_key, Map<profile_id, LinkedList<MdxIndex>>)
-        let mut merged_results = std::collections::BTreeMap::<
-            String,
-            (
-                String,
-                std::collections::BTreeMap<crate::mdx_profile::ProfileId, LinkedList<crate::mdx_db::MdxIndex>>,
-            ),
-        >::new();
+    /// Collator for a BCP 47 locale like "de" or "zh-u-co-pinyin", the root collation when it is empty or invalid
+    /// `strength` is the default (tertiary) when None
+    fn collator(locale: &str, strength: Option<Strength>) -> CollatorBorrowed<'static> {
+        let mut options = CollatorOptions::default();
+        options.strength = strength;
+        let parsed = if locale.is_empty() { Ok(Locale::UNKNOWN) } else { locale.parse::<Locale>() };
+        let locale = parsed.unwrap_or_else(|e| {
+            log::warn!("Invalid collation locale {}: {}", locale, e);
+            Locale::UNKNOWN
+        });
+        Collator::try_new(locale.clone().into(), options)
+            .or_else(|e| {
+                log::warn!("No collation data for {}: {}", locale, e);
+                Collator::try_new(Locale::UNKNOWN.into(), options)
+            })
+            .expect("root collation data is compiled in")
+    }
 
-        let normalized_query = MdxDb::normalize_keyword(query);
-        // Search in each library within the group
-        for mdx_db in self.mdx_dbs.values_mut() {
-            if let Some(best_match) = mdx_db.find_index(query, true, true, true)? {
-                let start_entry = best_match.key_index.entry_no;
-                if let Ok(indexes) = mdx_db.get_indexes(start_entry, max_results_per_lib as u64) {
-                    for index in indexes {
-                        // Normalize keyword for merging (ignore case and non-alphabetic characters)
-                        let normalized_key = MdxDb::normalize_keyword(&index.key_index.key);
-                        let original_key = index.key_index.key.clone();
-
-                        if normalized_key<normalized_query {
-                            continue;
-                        }else if merged_results.len() > max_results_per_lib && &normalized_key>merged_results.last_entry().unwrap().key()  {
-                            break;
-                        }
-                        // Ensure entry for normalized key exists with display key
-                        let entry = merged_results
-                            .entry(normalized_key)
-                            .or_insert_with(|| (original_key, std::collections::BTreeMap::new()));
-
-                        // Group indexes by profile_id under this key
-                        let per_profile_indexes = entry
-                            .1
-                            .entry(index.profile_id)
-                            .or_insert_with(|| LinkedList::new());
-                        per_profile_indexes.push_back(index);
-
-                        if merged_results.len() > max_results_per_lib{
-                            merged_results.pop_last();
-                        }
+    /// Run `operation` on every library of the group in parallel
+    /// Results are returned in no particular order
+    fn map_dbs_parallel<R, F>(&self, operation: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(&MdxDb) -> R + Sync,
+    {
+        Self::map_parallel(self.mdx_dbs.values(), operation)
+    }
+
+    fn map_parallel<'a, R, F>(mdx_dbs: impl Iterator<Item = &'a MdxDb>, operation: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(&MdxDb) -> R + Sync,
+    {
+        let operation = &operation;
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = mdx_dbs
+                .map(|mdx_db| scope.spawn(move || operation(mdx_db)))
+                .collect();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        })
+    }
+
+    /// Run `operation` in parallel on the libraries that can contain a query, `accepts` tells it from their
+    /// languages (see `Languages::can_contain`). All libraries are searched when none of them can
+    fn map_dbs_for_query<R, F, A>(&self, accepts: A, operation: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(&MdxDb) -> R + Sync,
+        A: Fn(&Languages) -> bool + Sync,
+    {
+        let routed: Vec<ProfileId> = self.map_dbs_parallel(|mdx_db| {
+            accepts(mdx_db.languages()).then_some(mdx_db.profile.profile_id)
+        }).into_iter().flatten().collect();
+        if routed.is_empty() {
+            return self.map_dbs_parallel(operation);
+        }
+        Self::map_parallel(routed.iter().filter_map(|profile_id| self.mdx_dbs.get(profile_id)), operation)
+    }
+
+    /// Key used to merge the headwords of the libraries, following the key normalization policy of the group
+    pub fn normalize_keyword(&self, keyword: &str) -> String {
+        self.profile.options.key_normalization.normalize(keyword, self.chinese_conversion.converter())
+    }
+
+    /// Find the entries of `key` in all libraries of the group
+    /// Returns one MdxGroupIndex per library having the key, in the group's profile order
+    /// Only the libraries whose headwords can be written like the key are searched
+    pub fn find_index(&self, key: &str) -> Result<LinkedList<MdxGroupIndex>> {
+        let mut profile_groups = HashMap::<ProfileId, MdxGroupIndex>::new();
+        let lib_results = self.map_dbs_for_query(Self::headword_of(key), |mdx_db| mdx_db.find_index(key, false, false, true));
+        for lib_result in lib_results {
+            if let Some(mdx_index) = lib_result? {
+                let profile_id = mdx_index.profile_id;
+
+                // Get or create MdxGroupIndex for this profile_id
+                let group_index = profile_groups.entry(profile_id).or_insert_with(|| {
+                    MdxGroupIndex {
+                        profile_id,
+                        primary_key: key.to_string(),
+                        indexes: LinkedList::new(),
                     }
+                });
+                group_index.indexes.push_back(mdx_index);
+            }
+        }
+
+        // Convert HashMap to LinkedList, sorted by profile order
+        let mut result = LinkedList::new();
+        if let Some(profiles) = self.profile.get_profiles() {
+            for profile in profiles.iter() {
+                if let Some(group_index) = profile_groups.remove(&profile.profile_id) {
+                    result.push_back(group_index);
                 }
             }
         }
+        Ok(result)
+    }
 
-        // Build results: each key maps to a LinkedList of MdxGroupIndex,
-        // where each MdxGroupIndex contains indexes from a single profile_id
-        // Sort by profile order in the group's profile list
+    /// Incremental search: merge the key lists of the libraries starting at the best match of `query`
+    /// Returns the first `max_results` merged keys and the cursor to continue the merge with `merge_keys`
+    pub fn find_best_match_indexes(&self, query: &str, max_results: usize) -> Result<(MergedKeys, GroupKeyCursor)> {
+        // Start each library that can contain the query at its best match, in parallel
+        let lib_results = self.map_dbs_for_query(Self::headword_of(query), |mdx_db| -> Result<Option<(ProfileId, EntryNo)>> {
+            Ok(mdx_db.find_index(&mdx_db.languages().headword_query(query), true, true, true)?
+                .map(|best_match| (mdx_db.profile.profile_id, best_match.key_index.entry_no)))
+        });
+        let mut cursor = GroupKeyCursor::default();
+        for lib_result in lib_results {
+            if let Some((profile_id, entry_no)) = lib_result? {
+                cursor.next_entry_nos.insert(profile_id, entry_no);
+            }
+        }
+        // A best match may be a key before the query, those are skipped
+        let results = self.merge(&mut cursor, max_results, Some(query))?;
+        Ok((results, cursor))
+    }
+
+    /// The first `max_results` keys of the merged key list of all libraries, and the cursor to continue it
+    /// Browses a group whose index is not ready yet without waiting for it
+    pub fn first_keys(&self, max_results: usize) -> Result<(MergedKeys, GroupKeyCursor)> {
+        let mut cursor = self.start_cursor();
+        let results = self.merge(&mut cursor, max_results, None)?;
+        Ok((results, cursor))
+    }
+
+    /// Cursor at the first key of every library
+    fn start_cursor(&self) -> GroupKeyCursor {
+        GroupKeyCursor {
+            next_entry_nos: self.mdx_dbs.keys().map(|profile_id| (*profile_id, 0)).collect(),
+        }
+    }
+
+    /// Continue a merge of the key lists, returns up to `max_results` more keys in collation order
+    pub fn merge_keys(&self, cursor: &mut GroupKeyCursor, max_results: usize) -> Result<MergedKeys> {
+        self.merge(cursor, max_results, None)
+    }
+
+    /// K-way merge of the libraries' key lists: the smallest head key by the group's collation is taken
+    /// together with the head keys of all libraries that normalize to the same key
+    fn merge(&self, cursor: &mut GroupKeyCursor, max_results: usize, floor: Option<&str>) -> Result<MergedKeys> {
+        let sources: Vec<&dyn KeySource> = self.mdx_dbs.values().map(|mdx_db| mdx_db as &dyn KeySource).collect();
+        self.merge_sources(&sources, cursor, max_results, floor)
+    }
+
+    fn merge_sources(&self, sources: &[&dyn KeySource], cursor: &mut GroupKeyCursor, max_results: usize, floor: Option<&str>) -> Result<MergedKeys> {
+        let mut streams: Vec<KeyStream> = cursor.next_entry_nos.iter()
+            .filter_map(|(profile_id, entry_no)| {
+                sources.iter().find(|source| source.profile_id() == *profile_id).map(|source| KeyStream::new(*source, *entry_no))
+            })
+            .collect();
         let mut results = LinkedList::new();
-        for (normalized_key, (display_key, per_profile_map)) in merged_results {
-            let mut group_index_list = LinkedList::new();
-            
-            // Sort profile_ids by their order in the group's profiles list
-            if let Some(profiles) = self.profile.get_profiles() {
-                for profile in profiles.iter() {
-                    if let Some(indexes) = per_profile_map.get(&profile.profile_id) {
-                        let group_index = MdxGroupIndex {
-                            profile_id: profile.profile_id,
-                            primary_key: display_key.clone(),
-                            indexes: indexes.clone(),
-                        };
-                        group_index_list.push_back(group_index);
+        while results.len() < max_results {
+            let mut smallest: Option<String> = None;
+            for stream in streams.iter_mut() {
+                if let Some(head) = stream.peek()?
+                    && smallest.as_ref().is_none_or(|key| self.collator.compare(&head.key_index.key, key) == Ordering::Less)
+                {
+                    smallest = Some(head.key_index.key.clone());
+                }
+            }
+            let Some(display_key) = smallest else { break };
+
+            let normalized_key = self.normalize_keyword(&display_key);
+            let mut per_profile = HashMap::<ProfileId, LinkedList<MdxIndex>>::new();
+            for stream in streams.iter_mut() {
+                loop {
+                    match stream.peek()? {
+                        Some(head) if self.normalize_keyword(&head.key_index.key) == normalized_key => {}
+                        _ => break,
+                    }
+                    if let Some(index) = stream.pop() {
+                        per_profile.entry(index.profile_id).or_default().push_back(index);
                     }
                 }
             }
-            
-            results.push_back((normalized_key, display_key, group_index_list));
+            if floor.is_some_and(|floor| self.floor_collator.compare(&display_key, floor) == Ordering::Less) {
+                continue;
+            }
+            let group_indexes = self.in_profile_order(&per_profile, &display_key);
+            results.push_back((normalized_key, display_key, group_indexes));
         }
 
+        cursor.next_entry_nos = streams.iter()
+            .filter(|stream| !stream.is_exhausted())
+            .map(|stream| (stream.source.profile_id(), stream.next_unmerged))
+            .collect();
         Ok(results)
     }
-    
-    /// Perform full-text search across all libraries in the group
-    /// Behavior is similar to `MdxDbGroup::find_index`, but uses `MdxDb::search_fulltext`
-    /// to collect results per library, then merges them by normalized key
-    pub fn fulltext_find(&mut self, query: &str, max_results_per_lib: usize) -> Result<LinkedList<(String, String, LinkedList<MdxGroupIndex>)>> {
+
+    /// The persistent merged key list of all libraries, opened from the data directory or built
+    /// when the libraries, their files or the merge options changed
+    /// Building reads every key of the group, it is done once per change
+    pub fn key_index(&self) -> Result<&GroupKeyIndex> {
+        self.key_index_unless(|| false)
+    }
+
+    /// Like `key_index`, a build fails with a `cancelled` error as soon as `stop` returns true
+    pub fn key_index_unless(&self, stop: impl Fn() -> bool) -> Result<&GroupKeyIndex> {
+        self.key_index.get_or_try_init(|| {
+            let signature = self.index_signature();
+            match GroupKeyIndex::open(&self.index_path, &signature) {
+                Ok(Some(index)) => return Ok(index),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to open group index {:?}: {}", self.index_path, e),
+            }
+            let mut cursor = self.start_cursor();
+            let index = GroupKeyIndex::build(&self.index_path, &signature, || {
+                if stop() {
+                    return Err(cancelled("Building the group index"));
+                }
+                let keys = self.merge(&mut cursor, INDEX_CHUNK_SIZE, None)?;
+                Ok(keys.into_iter()
+                    .map(|(normalized_key, display_key, group_indexes)| {
+                        let indexes = group_indexes.into_iter().flat_map(|group_index| group_index.indexes).collect();
+                        (normalized_key, display_key, indexes)
+                    })
+                    .collect())
+            })?;
+            log::info!("Built group index of {} ({} keys)", self.profile.title, index.len());
+            Ok(index)
+        })
+    }
+
+    /// The merged key list if it is already opened, never waits for it to be built
+    pub fn ready_key_index(&self) -> Option<&GroupKeyIndex> {
+        self.key_index.get()
+    }
+
+    /// What the group index is built from: the libraries and their files, the merge and sort options
+    /// and the Simplified/Traditional conversion, which folds the merged keys
+    fn index_signature(&self) -> String {
+        let mut libraries: Vec<String> = self.mdx_dbs.values()
+            .map(|mdx_db| {
+                let modified = mdx_db.file_modified()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |duration| duration.as_secs());
+                format!("{}:{}:{}:{}", mdx_db.profile.profile_id, mdx_db.profile.url, mdx_db.get_entry_count(), modified)
+            })
+            .collect();
+        libraries.sort();
+        format!(
+            "{:?}|{}|{}|{}",
+            self.profile.options.key_normalization,
+            self.profile.options.collation_locale,
+            self.chinese_conversion.is_enabled(),
+            libraries.join("|")
+        )
+    }
+
+    /// Keys of the group index starting at `position`, like the results of `merge_keys`
+    /// None while the index is being built, this never waits for it
+    pub fn get_index_keys(&self, position: usize, max_results: usize) -> Result<Option<MergedKeys>> {
+        let Some(index) = self.ready_key_index() else { return Ok(None) };
+        let mut results = LinkedList::new();
+        for (normalized_key, display_key, indexes) in index.get_keys(position, max_results)? {
+            let mut per_profile = HashMap::<ProfileId, LinkedList<MdxIndex>>::new();
+            for index in indexes {
+                per_profile.entry(index.profile_id).or_default().push_back(index);
+            }
+            let group_indexes = self.in_profile_order(&per_profile, &display_key);
+            results.push_back((normalized_key, display_key, group_indexes));
+        }
+        Ok(Some(results))
+    }
+
+    /// Position of the first key of the group index not ordered before `query`, the last key when all are
+    /// The query is looked up in the form all libraries agree on, e.g. romaji as kana when all are written in kana
+    pub fn find_index_position(&self, index: &GroupKeyIndex, query: &str) -> Result<usize> {
+        let mut forms = self.mdx_dbs.values().map(|mdx_db| mdx_db.languages().headword_query(query));
+        let query = match forms.next() {
+            Some(form) if forms.all(|other| other == form) => form,
+            _ => Cow::Borrowed(query),
+        };
+        let position = index.lower_bound(|key| self.collator.compare(key, &query))?;
+        Ok(position.min(index.len().saturating_sub(1)))
+    }
+
+    /// One MdxGroupIndex per library, in the group's profile order
+    fn in_profile_order(&self, per_profile: &HashMap<ProfileId, LinkedList<MdxIndex>>, display_key: &str) -> LinkedList<MdxGroupIndex> {
+        let mut group_index_list = LinkedList::new();
+        if let Some(profiles) = self.profile.get_profiles() {
+            for profile in profiles.iter() {
+                if let Some(indexes) = per_profile.get(&profile.profile_id) {
+                    group_index_list.push_back(MdxGroupIndex {
+                        profile_id: profile.profile_id,
+                        primary_key: display_key.to_string(),
+                        indexes: indexes.clone(),
+                    });
+                }
+            }
+        }
+        group_index_list
+    }
+
+    /// Merge the indexes found in the libraries by normalized key
+    /// Each key maps to one MdxGroupIndex per library, in the group's profile order
+    fn merge_by_key(&self, indexes: impl IntoIterator<Item = MdxIndex>) -> MergedKeys {
         // Map normalized_key -> (display_key, Map<profile_id, LinkedList<MdxIndex>>)
         let mut merged_results = std::collections::BTreeMap::<
             String,
@@ -118,29 +455,22 @@ impl MdxDbGroup {
             ),
         >::new();
 
-        // Search in each library within the group using full-text search when available
-        for mdx_db in self.mdx_dbs.values_mut() {
-            if !mdx_db.is_fts_available() { continue; }
-
-            if let Ok(results) = mdx_db.fulltext_find(query, max_results_per_lib) {
-                for (_score, index) in results {
-                    // Normalize keyword for merging (ignore case and non-alphabetic characters)
-                    let normalized_key = MdxDb::normalize_keyword(&index.key_index.key);
-                    let original_key = index.key_index.key.clone();
-
-                    // Ensure entry for normalized key exists with display key
-                    let entry = merged_results
-                        .entry(normalized_key)
-                        .or_insert_with(|| (original_key, std::collections::BTreeMap::new()));
-
-                    // Group indexes by profile_id under this key
-                    let per_profile_indexes = entry
-                        .1
-                        .entry(index.profile_id)
-                        .or_insert_with(|| LinkedList::new());
-                    per_profile_indexes.push_back(index);
-                }
-            }
+        for index in indexes {
+            // Normalize keyword for merging, see `KeyNormalization`
+            let normalized_key = self.normalize_keyword(&index.key_index.key);
+            let original_key = index.key_index.key.clone();
+
+            // Ensure entry for normalized key exists with display key
+            let entry = merged_results
+                .entry(normalized_key)
+                .or_insert_with(|| (original_key, std::collections::BTreeMap::new()));
+
+            // Group indexes by profile_id under this key
+            let per_profile_indexes = entry
+                .1
+                .entry(index.profile_id)
+                .or_default();
+            per_profile_indexes.push_back(index);
         }
 
         // Build results: each key maps to a LinkedList of MdxGroupIndex,
@@ -165,17 +495,117 @@ impl MdxDbGroup {
             }
             
             // Use normalized value of display_key as the map key similar to find_index
-            let normalized_key = MdxDb::normalize_keyword(&display_key);
+            let normalized_key = self.normalize_keyword(&display_key);
             results.push_back((normalized_key, display_key, group_index_list));
         }
+        results
+    }
 
-        Ok(results)
+    /// Perform full-text search across all libraries in the group
+    /// Behavior is similar to `MdxDbGroup::find_index`, but uses `MdxDb::fulltext_ranked`
+    /// to collect results per library, then merges them by normalized key
+    /// Keys are ranked by the best score of their entries, returned with the scores in the same order
+    pub fn fulltext_find(&self, query: &FtsQuery, max_results_per_lib: usize) -> Result<(MergedKeys, Vec<f32>)> {
+        // Search in each library within the group using full-text search when available, in parallel
+        // Libraries whose headwords and definitions cannot contain the terms are skipped
+        let lib_results = self.map_dbs_for_query(Self::text_of(query), |mdx_db| {
+            if !mdx_db.is_fts_available() {
+                return None;
+            }
+            mdx_db.fulltext_ranked(query, max_results_per_lib)
+                .inspect_err(|e| log::warn!("Full-text search failed in {}: {}", mdx_db.profile.title, e))
+                .ok()
+        });
+        let hits: Vec<(f32, MdxIndex)> = lib_results.into_iter().flatten().flatten().collect();
+
+        let mut key_scores = HashMap::<String, f32>::new();
+        for (score, index) in &hits {
+            let key_score = key_scores.entry(self.normalize_keyword(&index.key_index.key)).or_insert(*score);
+            *key_score = key_score.max(*score);
+        }
+        let mut ranked: Vec<(f32, _)> = self
+            .merge_by_key(hits.into_iter().map(|(_score, index)| index))
+            .into_iter()
+            .map(|result| (key_scores.get(&result.0).copied().unwrap_or_default(), result))
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| self.collator.compare(&a.1.1, &b.1.1)));
+        Ok(ranked.into_iter().map(|(score, result)| (result, score)).unzip())
+    }
+
+    /// Find the headwords matching a wildcard or regex pattern in all libraries of the group
+    /// Results are merged by normalized key like `fulltext_find`
+    pub fn find_pattern(&self, pattern: &KeyPattern, max_results_per_lib: usize) -> Result<MergedKeys> {
+        // The literal start of the pattern routes it, "*ing" searches all libraries
+        let mut indexes = Vec::new();
+        for lib_result in self.map_dbs_for_query(Self::headword_of(pattern.literal_prefix()), |mdx_db| mdx_db.find_pattern(pattern, max_results_per_lib)) {
+            indexes.extend(lib_result?);
+        }
+        Ok(self.merge_by_key(indexes))
     }
     
-    pub fn get_html(&mut self, entry: &MdxIndex, base_url: &str)->Result<String> {
-        let mdx_db = self.mdx_dbs.get_mut(&entry.profile_id).unwrap();
+    /// Find the Chinese headwords whose pinyin starts with `query` in the libraries of the group whose
+    /// headwords can be Chinese (see `Languages::can_be_chinese`), merged by normalized key like `find_pattern`
+    pub fn find_pinyin(&self, query: &str, max_results_per_lib: usize) -> Result<MergedKeys> {
+        let mut indexes = Vec::new();
+        for lib_result in self.map_dbs_parallel(|mdx_db| mdx_db.find_pinyin(query, max_results_per_lib)) {
+            indexes.extend(lib_result?);
+        }
+        Ok(self.merge_by_key(indexes))
+    }
+
+    /// Spelling suggestions from all libraries in the group, (distance, index) pairs ordered by distance
+    pub fn suggest(&self, word: &str, max_results_per_lib: usize) -> Result<Vec<(f32, MdxIndex)>> {
+        let mut candidates = Vec::new();
+        for lib_result in self.map_dbs_for_query(Self::headword_of(word), |mdx_db| mdx_db.suggest(word, max_results_per_lib)) {
+            candidates.extend(lib_result?);
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.key_index.key.cmp(&b.1.key_index.key)));
+        Ok(candidates)
+    }
+
+    /// Full-text search across all libraries in the group without merging by key
+    /// Returns (score, index) pairs ordered by descending score, scores are normalized per library
+    pub fn fulltext_hits(&self, query: &FtsQuery, max_results_per_lib: usize) -> Result<Vec<(f32, MdxIndex)>> {
+        let mut hits = Vec::new();
+        let lib_results = self.map_dbs_for_query(Self::text_of(query), |mdx_db| {
+            if mdx_db.is_fts_available() { mdx_db.fulltext_ranked(query, max_results_per_lib) } else { Ok(Vec::new()) }
+        });
+        for lib_result in lib_results {
+            hits.extend(lib_result?);
+        }
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(hits)
+    }
+
+    /// Whether a library can have `key` as a headword
+    /// Romaji is looked up as kana (see `Languages::headword_query`), so a key that converts to kana also
+    /// goes to the libraries written in kana
+    fn headword_of(key: &str) -> impl Fn(&Languages) -> bool + Sync {
+        let scripts = scripts_of(key);
+        let kana_scripts = japanese::romaji_to_hiragana(key).map(|kana| scripts_of(&kana));
+        move |languages| {
+            languages.can_contain(&scripts, false)
+                || kana_scripts.as_ref().is_some_and(|kana_scripts| languages.can_contain(kana_scripts, false))
+        }
+    }
+
+    /// Whether a library can have any of the terms of a full-text query in its headwords or definitions
+    /// A query without terms (only excluded words) searches every library
+    fn text_of(query: &FtsQuery) -> impl Fn(&Languages) -> bool + Sync {
+        let term_scripts: Vec<_> = query.terms().iter().map(|term| scripts_of(term)).collect();
+        move |languages| term_scripts.is_empty() || term_scripts.iter().any(|scripts| languages.can_contain(scripts, true))
+    }
+
+    pub fn get_html(&self, entry: &MdxIndex, base_url: &str)->Result<String> {
+        let mdx_db = self.get_db(entry.profile_id)?;
         mdx_db.get_html(entry, base_url)
     }
+
+    /// Get a library of the group by profile id
+    pub fn get_db(&self, profile_id: ProfileId) -> Result<&MdxDb> {
+        self.mdx_dbs.get(&profile_id)
+            .ok_or_else(|| invalid_argument(format!("Library with profile_id {} not found in group", profile_id)))
+    }
     
     pub fn is_fts_available(&self) -> bool {
         for mdx_db in self.mdx_dbs.values() {
@@ -185,4 +615,151 @@ impl MdxDbGroup {
         }
         false
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mdx::storage::KeyIndex;
+    use crate::chinese_conv::ChineseConverter;
+
+    /// Sorted key list of a library
+    struct TestKeys {
+        profile_id: ProfileId,
+        keys: Vec<String>,
+    }
+
+    impl KeySource for TestKeys {
+        fn profile_id(&self) -> ProfileId {
+            self.profile_id
+        }
+
+        fn entry_count(&self) -> u64 {
+            self.keys.len() as u64
+        }
+
+        fn read_keys(&self, start_entry_no: EntryNo, max_count: u64) -> Result<LinkedList<MdxIndex>> {
+            Ok(self.keys.iter().enumerate()
+                .skip(start_entry_no as usize)
+                .take(max_count as usize)
+                .map(|(entry_no, key)| MdxIndex {
+                    profile_id: self.profile_id,
+                    key_index: KeyIndex { entry_no: entry_no as EntryNo, key: key.clone(), ..Default::default() },
+                })
+                .collect())
+        }
+    }
+
+    fn test_keys(profile_id: ProfileId, keys: &[&str]) -> TestKeys {
+        TestKeys { profile_id, keys: keys.iter().map(|key| key.to_string()).collect() }
+    }
+
+    /// A group of the libraries 1 and 2 without opened databases, the key lists are given to `merge_sources`
+    fn test_group(collation_locale: &str) -> MdxDbGroup {
+        let mut profile = MdxProfile::new_group("Test".to_string(), 100);
+        profile.options.collation_locale = collation_locale.to_string();
+        for profile_id in [1, 2] {
+            profile.get_profiles_mut().unwrap().push_back(MdxProfile::new_profile(profile_id.to_string(), String::new(), profile_id));
+        }
+        MdxDbGroup {
+            collator: MdxDbGroup::collator(collation_locale, None),
+            floor_collator: MdxDbGroup::collator(collation_locale, Some(Strength::Primary)),
+            profile,
+            mdx_dbs: HashMap::new(),
+            index_path: PathBuf::new(),
+            key_index: OnceCell::new(),
+            chinese_conversion: Arc::new(ChineseConversion::new(ChineseConverter::default(), false)),
+        }
+    }
+
+    fn start(sources: &[&dyn KeySource]) -> GroupKeyCursor {
+        GroupKeyCursor { next_entry_nos: sources.iter().map(|source| (source.profile_id(), 0)).collect() }
+    }
+
+    /// Display keys of the merged results with the libraries having them
+    fn merged_keys(results: MergedKeys) -> Vec<(String, Vec<ProfileId>)> {
+        results.into_iter()
+            .map(|(_, display_key, group_indexes)| (display_key, group_indexes.iter().map(|group_index| group_index.profile_id).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_order() {
+        let group = test_group("");
+        let first = test_keys(1, &["apple", "Banana", "cherry"]);
+        let second = test_keys(2, &["banana", "date"]);
+        let sources: [&dyn KeySource; 2] = [&second, &first];
+        let mut cursor = start(&sources);
+        let results = group.merge_sources(&sources, &mut cursor, 10, None).unwrap();
+        let keys = merged_keys(results);
+        // Keys normalizing to the same key are merged, the libraries in profile order
+        assert_eq!(keys, vec![
+            ("apple".to_string(), vec![1]),
+            ("banana".to_string(), vec![1, 2]),
+            ("cherry".to_string(), vec![1]),
+            ("date".to_string(), vec![2]),
+        ]);
+        assert!(cursor.is_exhausted());
+
+        // Keys before the floor are skipped
+        let mut cursor = start(&sources);
+        let results = group.merge_sources(&sources, &mut cursor, 2, Some("b")).unwrap();
+        assert_eq!(merged_keys(results), vec![("banana".to_string(), vec![1, 2]), ("cherry".to_string(), vec![1])]);
+
+        // A headword differing from the floor only in case or accents is kept
+        for floor in ["Apple", "Äpple"] {
+            let mut cursor = start(&sources);
+            let results = group.merge_sources(&sources, &mut cursor, 1, Some(floor)).unwrap();
+            assert_eq!(merged_keys(results), vec![("apple".to_string(), vec![1])]);
+        }
+    }
+
+    #[test]
+    fn test_merge_collation() {
+        let first = test_keys(1, &["ask", "öl"]);
+        let second = test_keys(2, &["zebra"]);
+        let sources: [&dyn KeySource; 2] = [&first, &second];
+        let order = |locale: &str| -> Vec<String> {
+            let results = test_group(locale).merge_sources(&sources, &mut start(&sources), 10, None).unwrap();
+            merged_keys(results).into_iter().map(|(key, _)| key).collect()
+        };
+        assert_eq!(order(""), vec!["ask", "öl", "zebra"]);
+        // Swedish sorts ö after z
+        assert_eq!(order("sv"), vec!["ask", "zebra", "öl"]);
+    }
+
+    #[test]
+    fn test_merge_continuation() {
+        let group = test_group("");
+        // More keys than a chunk read at a time, some in both libraries
+        let first: Vec<String> = (0..150).filter(|n| n % 2 == 0 || n % 5 == 0).map(|n| format!("k{:03}", n)).collect();
+        let second: Vec<String> = (0..150).filter(|n| n % 2 == 1).map(|n| format!("k{:03}", n)).collect();
+        let first = TestKeys { profile_id: 1, keys: first };
+        let second = TestKeys { profile_id: 2, keys: second };
+        let sources: [&dyn KeySource; 2] = [&first, &second];
+
+        let all = merged_keys(group.merge_sources(&sources, &mut start(&sources), usize::MAX, None).unwrap());
+        assert_eq!(all.len(), 150);
+        assert_eq!(all[5], ("k005".to_string(), vec![1, 2]));
+
+        let mut cursor = start(&sources);
+        let mut continued = Vec::new();
+        while !cursor.is_exhausted() {
+            let window = merged_keys(group.merge_sources(&sources, &mut cursor, 7, None).unwrap());
+            assert!(!window.is_empty() && window.len() <= 7);
+            continued.extend(window);
+        }
+        assert_eq!(continued, all);
+    }
+
+    #[test]
+    fn test_headword_of() {
+        let japanese = Languages::detect("ja", "", "", Vec::new());
+        let english = Languages::detect("en", "", "", Vec::new());
+        assert!(MdxDbGroup::headword_of("tabemashita")(&japanese));
+        assert!(MdxDbGroup::headword_of("tabemashita")(&english));
+        assert!(MdxDbGroup::headword_of("食べる")(&japanese));
+        assert!(!MdxDbGroup::headword_of("食べる")(&english));
+        assert!(!MdxDbGroup::headword_of("hello")(&japanese));
+    }
+}
@@ -4,6 +4,8 @@ use url::Url;
 
 use mdx::utils::{get_decoded_file_stem, with_extension};
 
+use crate::key_normalization::KeyNormalization;
+
 
 pub type ProfileId = i32;
 
@@ -11,13 +13,36 @@ pub const DEFAULT_GROUP_ID: ProfileId = 1;
 pub const INVALID_PROFILE_ID: ProfileId = -1;
 
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct MdxOptions {
     pub font_file_path: String,
+    /// How headwords are merged in search results, for a group it applies to all its libraries
+    #[serde(default)]
+    pub key_normalization: KeyNormalization,
+    /// BCP 47 locale of the order of a group's merged key list, like "de" or "zh-u-co-pinyin"
+    /// Empty for the root collation
+    #[serde(default)]
+    pub collation_locale: String,
+    /// Multiplies the full-text scores of a library of a group, 1.0 when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fts_weight: Option<f32>,
+    /// BCP 47 language of the headwords, like "en" or "ru". Empty to detect it from the dictionary
+    #[serde(default)]
+    pub source_language: String,
+    /// BCP 47 language of the definitions, empty to detect it from the dictionary
+    #[serde(default)]
+    pub target_language: String,
+}
+
+impl MdxOptions {
+    /// Weight of the full-text scores, see `fts_weight`
+    pub fn fts_weight(&self) -> f32 {
+        self.fts_weight.unwrap_or(1.0)
+    }
 }
 
-#[derive(Deserialize, Default, Clone)]
+#[derive(Deserialize, Default, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct MdxProfile {
     pub title: String,
@@ -1,4 +1,4 @@
-use crate::error::{Result, ZdbError};
+use crate::error::{invalid_argument, Result};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
 #[repr(i32)]
@@ -22,6 +22,8 @@ pub enum MdxAction {
     ViewImage,
     Info,
     Notify,
+    Lookup,
+    Union,
 }
 
 impl MdxAction {
@@ -44,6 +46,8 @@ impl MdxAction {
             "debug" => MdxAction::Debug,
             "notify" => MdxAction::Notify,
             "info" => MdxAction::Info,
+            "lookup" => MdxAction::Lookup,
+            "union" => MdxAction::Union,
             _ => MdxAction::Unknown,
         }
     }
@@ -67,6 +71,6 @@ pub fn parse_mdx_url(url: &str, base_url: &str) -> Result<MdxAction> {
         // 只返回解析到的action
         Ok(action)
     } else {
-        Err(ZdbError::invalid_parameter(format!("Invalid action: {}", action_str)))
+        Err(invalid_argument(format!("Invalid action: {}", action_str)))
     }
 }
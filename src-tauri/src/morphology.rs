@@ -0,0 +1,329 @@
+// Morphology module - stems of inflected forms from Hunspell dictionaries (.aff/.dic)
+// Only the parts of the format needed to undo affixes are read: PFX/SFX rules, FLAG, AF aliases,
+// SET encoding and `st:` stem fields of the word list (used for irregular forms like went -> go)
+use std::collections::HashMap;
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_8};
+use regex::Regex;
+
+use crate::error::{Result, ZdbError};
+
+/// How affix flags are written in the .aff and .dic files
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FlagType {
+    /// One character per flag (default)
+    Char,
+    /// Two characters per flag
+    Long,
+    /// Comma separated numbers
+    Num,
+}
+
+impl FlagType {
+    fn parse_flags(&self, flags: &str) -> Vec<String> {
+        match self {
+            FlagType::Char => flags.chars().map(String::from).collect(),
+            FlagType::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                chars.chunks(2).map(|flag| flag.iter().collect()).collect()
+            }
+            FlagType::Num => flags.split(',').map(|flag| flag.trim().to_string()).filter(|flag| !flag.is_empty()).collect(),
+        }
+    }
+}
+
+/// A prefix or suffix rule: `add` replaces `strip` on a stem matching `condition`
+#[derive(Debug)]
+struct AffixRule {
+    flag: String,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Regex,
+}
+
+impl AffixRule {
+    /// Stem of `word` if it ends with this suffix
+    fn strip_suffix(&self, word: &str) -> Option<String> {
+        let base = word.strip_suffix(self.add.as_str()).filter(|base| !base.is_empty())?;
+        let stem = format!("{}{}", base, self.strip);
+        self.condition.is_match(&stem).then_some(stem)
+    }
+
+    /// Stem of `word` if it starts with this prefix
+    fn strip_prefix(&self, word: &str) -> Option<String> {
+        let base = word.strip_prefix(self.add.as_str()).filter(|base| !base.is_empty())?;
+        let stem = format!("{}{}", self.strip, base);
+        self.condition.is_match(&stem).then_some(stem)
+    }
+}
+
+/// A word of the .dic file, homonyms have several entries
+#[derive(Debug, Default)]
+struct DicEntry {
+    flags: Vec<String>,
+    /// Stems from `st:` fields
+    stems: Vec<String>,
+}
+
+/// One Hunspell dictionary
+#[derive(Debug)]
+pub struct HunspellDict {
+    pub name: String,
+    prefixes: Vec<AffixRule>,
+    suffixes: Vec<AffixRule>,
+    words: HashMap<String, Vec<DicEntry>>,
+}
+
+impl HunspellDict {
+    /// Load `name.aff` and `name.dic`, the encoding is taken from the SET line of the .aff file
+    pub fn load(aff_path: &Path, dic_path: &Path) -> Result<Self> {
+        let aff_data = std::fs::read(aff_path)?;
+        let dic_data = std::fs::read(dic_path)?;
+        let encoding = Self::encoding(&aff_data);
+        let name = aff_path.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        Self::parse(&name, &encoding.decode(&aff_data).0, &encoding.decode(&dic_data).0)
+    }
+
+    /// Encoding named by the `SET` directive, UTF-8 without it
+    fn encoding(aff_data: &[u8]) -> &'static Encoding {
+        String::from_utf8_lossy(aff_data)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SET "))
+            .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+            .unwrap_or(UTF_8)
+    }
+
+    pub fn parse(name: &str, aff: &str, dic: &str) -> Result<Self> {
+        let mut flag_type = FlagType::Char;
+        let mut aliases = Vec::new();
+        let mut alias_count_read = false;
+        let mut prefixes = Vec::new();
+        let mut suffixes = Vec::new();
+        // Cross product setting of each affix flag, from the rule headers
+        let mut cross_products = HashMap::new();
+
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => flag_type = FlagType::Long,
+                ["FLAG", "num", ..] => flag_type = FlagType::Num,
+                // Alias table, the first AF line is the count
+                ["AF", flags, ..] => {
+                    if alias_count_read {
+                        aliases.push(flag_type.parse_flags(flags));
+                    }
+                    alias_count_read = true;
+                }
+                ["PFX" | "SFX", flag, cross_product, count] if count.parse::<usize>().is_ok() => {
+                    cross_products.insert(flag.to_string(), *cross_product == "Y");
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let condition = rest.first().copied().unwrap_or(".");
+                    let rule = AffixRule {
+                        flag: flag.to_string(),
+                        cross_product: cross_products.get(*flag).copied().unwrap_or(false),
+                        strip: if *strip == "0" { String::new() } else { strip.to_string() },
+                        // Continuation flags after `/` are not followed
+                        add: match add.split('/').next() {
+                            Some("0") | None => String::new(),
+                            Some(add) => add.to_string(),
+                        },
+                        condition: Self::condition_regex(condition, *kind == "SFX")?,
+                    };
+                    if *kind == "SFX" { suffixes.push(rule) } else { prefixes.push(rule) }
+                }
+                _ => {}
+            }
+        }
+
+        let mut words: HashMap<String, Vec<DicEntry>> = HashMap::new();
+        // The first line is the word count
+        for line in dic.lines().skip(1) {
+            let mut fields = line.split_whitespace();
+            let Some(word_field) = fields.next() else { continue };
+            let (word, flags) = match word_field.split_once('/') {
+                Some((word, flags)) => {
+                    let flags = match flags.parse::<usize>() {
+                        Ok(alias) if !aliases.is_empty() => aliases.get(alias.wrapping_sub(1)).cloned().unwrap_or_default(),
+                        _ => flag_type.parse_flags(flags),
+                    };
+                    (word, flags)
+                }
+                None => (word_field, Vec::new()),
+            };
+            let stems = fields.filter_map(|field| field.strip_prefix("st:")).map(String::from).collect();
+            words.entry(word.to_string()).or_default().push(DicEntry { flags, stems });
+        }
+
+        Ok(Self { name: name.to_string(), prefixes, suffixes, words })
+    }
+
+    /// Affix conditions are a subset of regex: literal characters, `.` and `[...]`/`[^...]` classes
+    fn condition_regex(condition: &str, suffix: bool) -> Result<Regex> {
+        let mut pattern = String::new();
+        let mut in_class = false;
+        for c in condition.chars() {
+            match c {
+                '[' if !in_class => { in_class = true; pattern.push(c); }
+                ']' if in_class => { in_class = false; pattern.push(c); }
+                '^' if in_class && pattern.ends_with('[') => pattern.push(c),
+                '.' if !in_class => pattern.push(c),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        let pattern = if suffix { format!("(?:{})$", pattern) } else { format!("^(?:{})", pattern) };
+        Regex::new(&pattern)
+            .map_err(|e| ZdbError::invalid_data_format(format!("Invalid affix condition {}: {}", condition, e)))
+    }
+
+    fn has_flag(&self, word: &str, flag: &str) -> bool {
+        self.words.get(word)
+            .is_some_and(|entries| entries.iter().any(|entry| entry.flags.iter().any(|f| f == flag)))
+    }
+
+    /// Candidate stems of `word`, the word itself is not included
+    pub fn stems(&self, word: &str) -> Vec<String> {
+        let mut stems = Vec::new();
+        if let Some(entries) = self.words.get(word) {
+            stems.extend(entries.iter().flat_map(|entry| entry.stems.iter().cloned()));
+        }
+        for suffix in &self.suffixes {
+            let Some(stem) = suffix.strip_suffix(word) else { continue };
+            if self.has_flag(&stem, &suffix.flag) {
+                stems.push(stem.clone());
+            }
+            if suffix.cross_product {
+                for prefix in self.prefixes.iter().filter(|prefix| prefix.cross_product) {
+                    if let Some(stem) = prefix.strip_prefix(&stem)
+                        && self.has_flag(&stem, &prefix.flag)
+                        && self.has_flag(&stem, &suffix.flag)
+                    {
+                        stems.push(stem);
+                    }
+                }
+            }
+        }
+        for prefix in &self.prefixes {
+            if let Some(stem) = prefix.strip_prefix(word)
+                && self.has_flag(&stem, &prefix.flag)
+            {
+                stems.push(stem);
+            }
+        }
+        stems.retain(|stem| stem != word);
+        stems
+    }
+}
+
+/// All Hunspell dictionaries of the morphology directory
+#[derive(Debug, Default)]
+pub struct Morphology {
+    dicts: Vec<HunspellDict>,
+}
+
+impl Morphology {
+    /// Load every `name.aff` having a `name.dic` beside it, a missing directory gives no dictionaries
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let mut dicts = Vec::new();
+        if !dir.is_dir() {
+            return Ok(Self { dicts });
+        }
+        let mut aff_paths: Vec<_> = std::fs::read_dir(dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("aff")))
+            .collect();
+        aff_paths.sort();
+        for aff_path in aff_paths {
+            let dic_path = aff_path.with_extension("dic");
+            if !dic_path.exists() {
+                continue;
+            }
+            match HunspellDict::load(&aff_path, &dic_path) {
+                Ok(dict) => {
+                    log::info!("Loaded Hunspell dictionary {} ({} words)", dict.name, dict.words.len());
+                    dicts.push(dict);
+                }
+                Err(e) => log::warn!("Failed to load Hunspell dictionary {:?}: {}", aff_path, e),
+            }
+        }
+        Ok(Self { dicts })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dicts.is_empty()
+    }
+
+    /// Candidate stems of an inflected form from all dictionaries, most likely first
+    /// The lowercase form is also tried for capitalized words
+    pub fn stems(&self, word: &str) -> Vec<String> {
+        let lowercase = word.to_lowercase();
+        let mut stems: Vec<String> = Vec::new();
+        for form in [word, lowercase.as_str()] {
+            for dict in &self.dicts {
+                for stem in dict.stems(form) {
+                    if stem != word && stem != lowercase && !stems.contains(&stem) {
+                        stems.push(stem);
+                    }
+                }
+            }
+        }
+        stems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8
+PFX A Y 1
+PFX A 0 re .
+
+SFX D Y 2
+SFX D 0 ed [^y]
+SFX D y ied [^aeiou]y
+
+SFX S Y 2
+SFX S y ies [^aeiou]y
+SFX S 0 s [^y]
+";
+
+    const DIC: &str = "5
+walk/DS
+try/DS
+do/A
+apply/ADS
+went st:go
+";
+
+    #[test]
+    fn test_stems() {
+        let dict = HunspellDict::parse("en", AFF, DIC).unwrap();
+        assert_eq!(dict.stems("walked"), vec!["walk"]);
+        assert_eq!(dict.stems("tried"), vec!["try"]);
+        assert_eq!(dict.stems("redo"), vec!["do"]);
+        assert_eq!(dict.stems("went"), vec!["go"]);
+        // Prefix and suffix together
+        assert_eq!(dict.stems("reapplied"), vec!["apply"]);
+        // The condition of "ied" requires a consonant before y
+        assert!(dict.stems("walkied").is_empty());
+        assert!(dict.stems("walk").is_empty());
+    }
+
+    #[test]
+    fn test_flag_types() {
+        let aff = "FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n";
+        let dict = HunspellDict::parse("x", aff, "1\ncat/AaBb\n").unwrap();
+        assert_eq!(dict.stems("cats"), vec!["cat"]);
+
+        let aff = "FLAG num\nAF 1\nAF 101,102\nSFX 101 Y 1\nSFX 101 0 s .\n";
+        let dict = HunspellDict::parse("x", aff, "1\ndog/1\n").unwrap();
+        assert_eq!(dict.stems("dogs"), vec!["dog"]);
+
+        let morphology = Morphology { dicts: vec![HunspellDict::parse("en", AFF, DIC).unwrap()] };
+        assert_eq!(morphology.stems("Walks"), vec!["walk"]);
+    }
+}
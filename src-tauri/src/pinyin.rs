@@ -0,0 +1,263 @@
+// Pinyin module - pinyin search for Chinese headwords
+// Headwords are indexed by their readings with tone numbers ("zhong1guo2"), queries may leave out
+// any of the tones ("zhongguo", "zhong1guo"). The index of a dictionary is built from the bundled
+// character reading table and cached on disk next to the .mdx file
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use once_cell::sync::Lazy;
+
+use crate::chinese_conv::is_han;
+use crate::error::{Result, ZdbError};
+
+/// Mandarin readings of the CJK characters from pinyin-data (MIT, see pinyin/NOTICE), tones written as marks
+const BUNDLED_READINGS: &str = include_str!("pinyin/pinyin.txt");
+/// First line of the cache file, changes when the format changes, followed by `READINGS_HASH`
+const INDEX_FILE_HEADER: &str = "mdict-pinyin-index 1";
+/// Combinations of polyphonic characters indexed per headword
+const MAX_READINGS_PER_KEY: usize = 8;
+
+static READINGS: Lazy<HashMap<char, Vec<String>>> = Lazy::new(|| parse_readings(BUNDLED_READINGS));
+
+/// Hash of the reading table, cached indexes are rebuilt when the table changes
+static READINGS_HASH: Lazy<u64> = Lazy::new(|| fnv1a(BUNDLED_READINGS.as_bytes()));
+
+/// 64-bit FNV-1a, stable across builds unlike the std hashers
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Lines like "U+4E2D: zhōng,zhòng  # 中", comments start with '#'
+fn parse_readings(table: &str) -> HashMap<char, Vec<String>> {
+    table.lines()
+        .filter_map(|line| {
+            let (code, rest) = line.strip_prefix("U+")?.split_once(':')?;
+            let character = char::from_u32(u32::from_str_radix(code, 16).ok()?)?;
+            let readings = rest.split('#').next()?;
+            Some((character, readings.split(',').map(str::trim).filter(|reading| !reading.is_empty()).map(numbered).collect()))
+        })
+        .collect()
+}
+
+/// Convert a syllable with a tone mark to tone number notation: "zhōng" -> "zhong1", "lǜ" -> "lv4"
+/// Syllables without a mark get the neutral tone 5
+pub fn numbered(syllable: &str) -> String {
+    let mut tone = 5;
+    let mut result = String::with_capacity(syllable.len() + 1);
+    for c in syllable.chars() {
+        let (base, mark) = match c {
+            'ā' => ('a', 1), 'á' => ('a', 2), 'ǎ' => ('a', 3), 'à' => ('a', 4),
+            'ē' => ('e', 1), 'é' => ('e', 2), 'ě' => ('e', 3), 'è' => ('e', 4),
+            'ī' => ('i', 1), 'í' => ('i', 2), 'ǐ' => ('i', 3), 'ì' => ('i', 4),
+            'ō' => ('o', 1), 'ó' => ('o', 2), 'ǒ' => ('o', 3), 'ò' => ('o', 4),
+            'ū' => ('u', 1), 'ú' => ('u', 2), 'ǔ' => ('u', 3), 'ù' => ('u', 4),
+            'ǖ' => ('v', 1), 'ǘ' => ('v', 2), 'ǚ' => ('v', 3), 'ǜ' => ('v', 4),
+            'ü' => ('v', 0),
+            'ń' => ('n', 2), 'ň' => ('n', 3), 'ǹ' => ('n', 4), 'ḿ' => ('m', 2),
+            c => (c.to_ascii_lowercase(), 0),
+        };
+        if mark > 0 {
+            tone = mark;
+        }
+        result.push(base);
+    }
+    result.push(char::from(b'0' + tone));
+    result
+}
+
+/// Readings of a headword in tone number notation, empty unless its Chinese characters are all
+/// in the reading table. Other characters (letters, digits, punctuation) are skipped: "卡拉OK" -> "ka3la1"
+pub fn headword_readings(key: &str) -> Vec<String> {
+    let mut readings = vec![String::new()];
+    let mut has_characters = false;
+    for c in key.chars() {
+        if !is_han(c) {
+            continue;
+        }
+        let Some(syllables) = READINGS.get(&c) else { return Vec::new() };
+        has_characters = true;
+        readings = readings.iter()
+            .flat_map(|reading| syllables.iter().map(move |syllable| format!("{}{}", reading, syllable)))
+            .take(MAX_READINGS_PER_KEY)
+            .collect();
+    }
+    if has_characters { readings } else { Vec::new() }
+}
+
+/// Normalize a pinyin query: lowercase, without spaces and apostrophes, ü written as v
+/// None if the query is not pinyin with optional tone numbers
+pub fn normalize_query(query: &str) -> Option<String> {
+    let query = query.trim().to_lowercase().replace("u:", "v").replace('ü', "v");
+    let query: String = query.chars().filter(|c| !c.is_whitespace() && *c != '\'' && *c != '-').collect();
+    let valid = query.starts_with(|c: char| c.is_ascii_lowercase())
+        && query.chars().all(|c| c.is_ascii_lowercase() || ('1'..='5').contains(&c));
+    valid.then_some(query)
+}
+
+/// Whether `toned` (like "zhong1guo2") starts with `query`, tones missing from the query match any tone
+fn matches_prefix(toned: &str, query: &str) -> bool {
+    let mut record = toned.chars().peekable();
+    for q in query.chars() {
+        if q.is_ascii_digit() {
+            if record.next() != Some(q) {
+                return false;
+            }
+        } else {
+            // Skip the tone of the previous syllable when the query leaves it out
+            while record.next_if(|r| r.is_ascii_digit()).is_some() {}
+            if record.next() != Some(q) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[derive(Debug, Clone)]
+struct PinyinEntry {
+    toneless: String,
+    toned: String,
+    entry_no: u64,
+}
+
+/// Pinyin index of one dictionary, sorted by reading without tones
+#[derive(Debug, Default)]
+pub struct PinyinIndex {
+    entries: Vec<PinyinEntry>,
+}
+
+impl PinyinIndex {
+    /// Build from (entry_no, headword) pairs, headwords not written in Chinese characters are left out
+    pub fn build(headwords: impl IntoIterator<Item = (u64, String)>) -> Self {
+        let entries = headwords.into_iter()
+            .flat_map(|(entry_no, key)| {
+                headword_readings(&key).into_iter().map(move |toned| Self::entry(toned, entry_no))
+            })
+            .collect();
+        Self::sorted(entries)
+    }
+
+    fn entry(toned: String, entry_no: u64) -> PinyinEntry {
+        PinyinEntry {
+            toneless: toned.chars().filter(|c| !c.is_ascii_digit()).collect(),
+            toned,
+            entry_no,
+        }
+    }
+
+    fn sorted(mut entries: Vec<PinyinEntry>) -> Self {
+        entries.sort_by(|a, b| a.toneless.cmp(&b.toneless).then(a.entry_no.cmp(&b.entry_no)));
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries whose reading starts with the query, exact readings first, then shorter readings first
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<u64> {
+        let Some(query) = normalize_query(query) else { return Vec::new() };
+        let toneless: String = query.chars().filter(|c| !c.is_ascii_digit()).collect();
+        let start = self.entries.partition_point(|entry| entry.toneless < toneless);
+        let mut matches: Vec<&PinyinEntry> = self.entries[start..].iter()
+            .take_while(|entry| entry.toneless.starts_with(&toneless))
+            .filter(|entry| matches_prefix(&entry.toned, &query))
+            .collect();
+        matches.sort_by_key(|entry| entry.toneless.len());
+        let mut entry_nos = Vec::new();
+        for entry in matches {
+            if !entry_nos.contains(&entry.entry_no) {
+                entry_nos.push(entry.entry_no);
+                if entry_nos.len() >= max_results {
+                    break;
+                }
+            }
+        }
+        entry_nos
+    }
+
+    /// First line of the cache file of a dictionary with `entry_count` entries
+    fn header(entry_count: u64) -> String {
+        format!("{} {:016x}\t{}", INDEX_FILE_HEADER, *READINGS_HASH, entry_count)
+    }
+
+    /// Load a cached index, None if the file was written for a different entry count, format or reading table
+    pub fn load(path: &Path, entry_count: u64) -> Result<Option<Self>> {
+        let data = std::fs::read_to_string(path)?;
+        let mut lines = data.lines();
+        if lines.next() != Some(Self::header(entry_count).as_str()) {
+            return Ok(None);
+        }
+        let mut entries = Vec::new();
+        for line in lines {
+            let (toned, entry_no) = line.split_once('\t')
+                .ok_or_else(|| ZdbError::invalid_data_format(format!("Invalid pinyin index line: {}", line)))?;
+            let entry_no = entry_no.parse()
+                .map_err(|_| ZdbError::invalid_data_format(format!("Invalid pinyin index line: {}", line)))?;
+            entries.push(Self::entry(toned.to_string(), entry_no));
+        }
+        Ok(Some(Self::sorted(entries)))
+    }
+
+    pub fn save(&self, path: &Path, entry_count: u64) -> Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "{}", Self::header(entry_count))?;
+        for entry in &self.entries {
+            writeln!(file, "{}\t{}", entry.toned, entry.entry_no)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readings() {
+        assert_eq!(numbered("zhōng"), "zhong1");
+        assert_eq!(numbered("lǜ"), "lv4");
+        assert_eq!(numbered("de"), "de5");
+        assert_eq!(headword_readings("中国"), vec!["zhong1guo2", "zhong4guo2"]);
+        assert_eq!(headword_readings("中國"), vec!["zhong1guo2", "zhong4guo2"]);
+        assert!(headword_readings("China").is_empty());
+        assert_eq!(headword_readings("卡拉OK"), headword_readings("卡拉"));
+        assert!(!headword_readings("卡拉OK").is_empty());
+        assert_eq!(normalize_query("Zhong1 guo'"), Some("zhong1guo".to_string()));
+        assert_eq!(normalize_query("nü3"), Some("nv3".to_string()));
+        assert_eq!(normalize_query("中国"), None);
+    }
+
+    #[test]
+    fn test_search() {
+        let index = PinyinIndex::build([
+            (0, "中国".to_string()),
+            (1, "中国人".to_string()),
+            (2, "钟鼓".to_string()),
+            (3, "种果".to_string()),
+            (4, "apple".to_string()),
+        ]);
+        assert_eq!(index.search("zhongguo", 10), vec![0, 3, 1]);
+        assert_eq!(index.search("zhong1guo2", 10), vec![0, 1]);
+        assert_eq!(index.search("zhong1gu", 10), vec![2, 0, 1]);
+        assert_eq!(index.search("zhongguo", 2), vec![0, 3]);
+        assert!(index.search("apple", 10).is_empty());
+
+        let dir = std::env::temp_dir().join(format!("mdict-pinyin-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.pinyin");
+        index.save(&path, 5).unwrap();
+        assert!(PinyinIndex::load(&path, 6).unwrap().is_none());
+        std::fs::write(&path, format!("{}\t5\nzhong1guo2\t0\n", INDEX_FILE_HEADER)).unwrap();
+        assert!(PinyinIndex::load(&path, 5).unwrap().is_none());
+        index.save(&path, 5).unwrap();
+        let loaded = PinyinIndex::load(&path, 5).unwrap().unwrap();
+        assert_eq!(loaded.search("zhongguo", 10), index.search("zhongguo", 10));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
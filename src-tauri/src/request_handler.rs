@@ -1,11 +1,13 @@
 // 处理webview的URL请求
 use log::debug;
-use tauri::http::{Request, Response, StatusCode};
+use http::{Request, Response};
 use url::Url;
 
 use crate::action_handlers::get_action_handler;
-use crate::error::{Result, ZdbError};
+use crate::error::{invalid_argument, ErrorCode, Result};
 use crate::error_printer::format_error;
+use crate::html_text::escape_html;
+use crate::json_api::status_of;
 use crate::mdx_url_parser::parse_mdx_url;
 
 /// 处理MDX URL请求的函数
@@ -15,9 +17,9 @@ fn handle_mdx_url(url: &str, base_url: &str) -> Result<Response<Vec<u8>>> {
     
     // 使用action handler处理请求
     let handler = get_action_handler(action)
-        .ok_or_else(|| ZdbError::invalid_parameter(format!("Unsupported action: {:?}", action)))?;
+        .ok_or_else(|| invalid_argument(format!("Unsupported action: {:?}", action)))?;
     
-    let response = handler.handle(&Url::parse(url)?, action)?;
+    let response = handler.handle(&Url::parse(url)?, action, base_url)?;
     
     Ok(response)
 }
@@ -39,20 +41,18 @@ pub fn handle_request(request: &Request<Vec<u8>>, base_url: &str) -> std::result
             Ok(response)
         },
         Err(error) => {
-            let error_message = format_error(&error);
-            
             // Determine HTTP status code based on error type
-            let status_code = if let ZdbError::KeyNotFound { .. } = &error {
-                log::info!("Key not found: {}", uri);
-                StatusCode::NOT_FOUND
+            let status_code = status_of(ErrorCode::of(&error));
+            if status_code.is_server_error() {
+                log::error!("Error handling MDX URL {}: {}", uri, format_error(&error));
             } else {
-                log::error!("Error handling MDX URL {}: {}", uri, error_message);
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
+                log::info!("Request {} failed: {}", uri, error);
+            }
             
+            // The page only shows the plain message, colors and backtrace go to the log
             let error_html = format!(
                 "<html><body><h1>Error</h1><p>Failed to load content: {}</p></body></html>", 
-                error_message
+                escape_html(&error.to_string())
             );
             let mut response = Response::new(error_html.into_bytes());
             *response.status_mut() = status_code;
@@ -1,21 +1,28 @@
 // Search commands module - Tauri command implementations for search and content retrieval
+// All commands take an optional session id, the default session is used when it is omitted
 use std::collections::LinkedList;
 use tauri::command;
 
-use crate::error::IntoStringResult;
-use crate::mdict_app::{with_read_access, with_write_access};
+use crate::api_types::{GroupEntry, IndexResponse, ResultKey, Suggestion, API_VERSION};
+use crate::error::{CommandResult, IntoCommandResult};
+use crate::json_api;
+use crate::key_pattern::KeyPattern;
+use crate::mdict_app::{with_lookup_context, with_read_access};
+use crate::session_mgr::SessionId;
 
 /// Incremental search (index search)
+/// In group mode `has_more` tells that the key list continues past `total_count` when it is scrolled
 #[command]
-pub async fn search_search_incremental(query: String, max_results: Option<usize>) -> std::result::Result<serde_json::Value, String> {
+pub async fn search_search_incremental(query: String, max_results: Option<usize>, session_id: Option<SessionId>) -> CommandResult<serde_json::Value> {
     let max_results = max_results.unwrap_or(50);
     
-    with_write_access(|app| {
-        match app.incremental_search(&query, max_results)? {
+    with_lookup_context(session_id, |context| {
+        match context.incremental_search(&query, max_results)? {
             Some((start_entry, total_count)) => {
                 Ok(serde_json::json!({
                     "start_entry_no": start_entry,
-                    "total_count": total_count
+                    "total_count": total_count,
+                    "has_more": context.has_more_results()
                 }))
             }
             None => {
@@ -26,85 +33,140 @@ pub async fn search_search_incremental(query: String, max_results: Option<usize>
                 }))
             }
         }
-    }).into_string_result()
+    }).into_command_result()
+}
+
+/// Incremental search falling back to full-text search when no headword starts with the query
+/// The results are then one cached list, each key tagged with `match_kind` in the key list
+/// `fulltext_failed` tells that the fallback failed and only headwords are listed
+#[command]
+pub async fn search_combined_search(query: String, max_results: Option<usize>, session_id: Option<SessionId>) -> CommandResult<serde_json::Value> {
+    let max_results = max_results.unwrap_or(50);
+    with_lookup_context(session_id, |context| {
+        match context.combined_search(&query, max_results)? {
+            (Some((start_entry, total_count)), fulltext_failed) => Ok(serde_json::json!({
+                "start_entry_no": start_entry,
+                "total_count": total_count,
+                "has_more": context.has_more_results(),
+                "fulltext_failed": fulltext_failed
+            })),
+            (None, fulltext_failed) => Ok(serde_json::json!({
+                "start_entry_no": -1,
+                "total_count": 0,
+                "fulltext_failed": fulltext_failed
+            })),
+        }
+    }).into_command_result()
 }
 
 /// Get content URL for an entry by index number
 #[command]
-pub async fn search_get_content_url(index_no: usize) -> std::result::Result<String, String> {
-    with_read_access(|app| app.get_content_url(index_no)).into_string_result()
+pub async fn search_get_content_url(index_no: usize, session_id: Option<SessionId>) -> CommandResult<String> {
+    with_read_access(|app| app.get_content_url(session_id, index_no)).into_command_result()
 }
 
 /// Get total entry count
+/// `has_more` tells that the key list of a group is still merged and the count is partial, it grows when scrolled
 #[command]
-pub async fn search_get_entry_count() -> std::result::Result<usize, String> {
-    with_read_access(|app| app.get_entry_count()).into_string_result()
+pub async fn search_get_entry_count(session_id: Option<SessionId>) -> CommandResult<serde_json::Value> {
+    with_lookup_context(session_id, |context| {
+        Ok(serde_json::json!({
+            "total_count": context.get_entry_count(),
+            "has_more": context.has_more_results()
+        }))
+    }).into_command_result()
 }
 
-/// Find index by keyword
+/// Whether the key list of a group incremental search continues past the entry count
 #[command]
-pub async fn search_find_index(key: String) -> std::result::Result<serde_json::Value, String> {
-    with_write_access(|app| {
-        let group_indexes = app.find_index(&key)?;
-        
-        // Convert LinkedList<MdxGroupIndex> to JSON array
-        let mut result = Vec::new();
-        for group_index in group_indexes {
-            result.push(serde_json::json!({
-                "profile_id": group_index.profile_id,
-                "primary_key": group_index.primary_key,
-                "indexes": group_index.indexes.iter().map(|idx| serde_json::json!({
-                    "profile_id": idx.profile_id,
-                    "entry_no": idx.key_index.entry_no,
-                    "key": idx.key_index.key
-                })).collect::<Vec<_>>()
-            }));
-        }
-        
-        Ok(serde_json::Value::Array(result))
-    }).into_string_result()
+pub async fn search_has_more_results(session_id: Option<SessionId>) -> CommandResult<bool> {
+    with_lookup_context(session_id, |context| Ok(context.has_more_results())).into_command_result()
+}
+
+/// Find index by keyword, an inflected form missing from the dictionaries is looked up by its stem
+/// `stem` is then set and is the `primary_key` of the results
+#[command]
+pub async fn search_find_index(key: String, session_id: Option<SessionId>) -> CommandResult<IndexResponse> {
+    // No database opened is not an error here, there are just no results
+    let context = with_read_access(|app| Ok(app.lookup_context(session_id).ok().map(|context| (context, app.morphology()))))
+        .into_command_result()?;
+    match context {
+        Some((context, morphology)) => json_api::index_lookup(&context, &morphology, &key).into_command_result(),
+        None => Ok(IndexResponse {
+            api_version: API_VERSION.to_string(),
+            query: key,
+            stem: None,
+            results: Vec::new(),
+        }),
+    }
 }
 
 /// Fulltext search (across single database or dictionary group)
 #[command]
-pub async fn search_fulltext_search(query: String, max_results: Option<usize>) -> std::result::Result<serde_json::Value, String> {
+pub async fn search_fulltext_search(query: String, max_results: Option<usize>, session_id: Option<SessionId>) -> CommandResult<serde_json::Value> {
     let max_results = max_results.unwrap_or(200);
-    with_write_access(|app| {
-        let total = app.fulltext_search(&query, max_results)?;
+    with_lookup_context(session_id, |context| {
+        let total = context.fulltext_search(&query, max_results)?;
+        Ok(serde_json::json!({
+            "start_entry_no": 0,
+            "total_count": total
+        }))
+    }).into_command_result()
+}
+
+/// Headword search with `*` and `?` wildcards, or a regular expression when `regex` is true
+#[command]
+pub async fn search_pattern_search(query: String, regex: Option<bool>, max_results: Option<usize>, session_id: Option<SessionId>) -> CommandResult<serde_json::Value> {
+    let max_results = max_results.unwrap_or(1000);
+    with_lookup_context(session_id, |context| {
+        let pattern = if regex.unwrap_or(false) { KeyPattern::regex(&query)? } else { KeyPattern::wildcard(&query)? };
+        let total = context.pattern_search(&pattern, max_results)?;
         Ok(serde_json::json!({
             "start_entry_no": 0,
             "total_count": total
         }))
-    }).into_string_result()
+    }).into_command_result()
+}
+
+/// Incremental search falling back to pinyin search ("zhongguo", "zhong1guo2", tones are optional)
+/// when no headword starts with the query, returns like `search_search_incremental`
+#[command]
+pub async fn search_pinyin_search(query: String, max_results: Option<usize>, session_id: Option<SessionId>) -> CommandResult<serde_json::Value> {
+    let max_results = max_results.unwrap_or(1000);
+    with_lookup_context(session_id, |context| {
+        match context.incremental_or_pinyin_search(&query, 50, max_results)? {
+            Some((start_entry, total_count)) => Ok(serde_json::json!({
+                "start_entry_no": start_entry,
+                "total_count": total_count,
+                "has_more": context.has_more_results()
+            })),
+            None => Ok(serde_json::json!({
+                "start_entry_no": -1,
+                "total_count": 0
+            })),
+        }
+    }).into_command_result()
+}
+
+/// "Did you mean" suggestions for a headword that is not found, closest first
+#[command]
+pub async fn search_suggest(word: String, max_results: Option<usize>, session_id: Option<SessionId>) -> CommandResult<Vec<Suggestion>> {
+    let max_results = max_results.unwrap_or(10);
+    with_lookup_context(session_id, |context| context.suggest(&word, max_results)).into_command_result()
 }
 
 /// Get result key list (paginated search results)
 #[command]
-pub async fn search_get_result_key_list(start_index_no: i64, max_count: usize) -> std::result::Result<LinkedList<(String, usize)>, String> {
-    with_write_access(|app| app.get_result_key_list(start_index_no, max_count)).into_string_result()
+pub async fn search_get_result_key_list(start_index_no: i64, max_count: usize, session_id: Option<SessionId>) -> CommandResult<LinkedList<ResultKey>> {
+    with_lookup_context(session_id, |context| context.get_result_key_list(start_index_no, max_count)).into_command_result()
 }
 
 /// Get group indexes for a given index number
 #[command]
-pub async fn search_get_group_indexes(index_no: usize) -> std::result::Result<serde_json::Value, String> {
-    with_write_access(|app| {
-        let group_indexes = app.get_group_indexes(index_no)?;
-        
-        // Convert LinkedList<MdxGroupIndex> to JSON array
-        let mut result = Vec::new();
-        for group_index in group_indexes {
-            result.push(serde_json::json!({
-                "profile_id": group_index.profile_id,
-                "primary_key": group_index.primary_key,
-                "indexes": group_index.indexes.iter().map(|idx| serde_json::json!({
-                    "profile_id": idx.profile_id,
-                    "entry_no": idx.key_index.entry_no,
-                    "key": idx.key_index.key
-                })).collect::<Vec<_>>()
-            }));
-        }
-        
-        Ok(serde_json::Value::Array(result))
-    }).into_string_result()
+pub async fn search_get_group_indexes(index_no: usize, session_id: Option<SessionId>) -> CommandResult<Vec<GroupEntry>> {
+    with_lookup_context(session_id, |context| {
+        let group_indexes = context.get_group_indexes(index_no)?;
+        Ok(context.group_entries(&group_indexes))
+    }).into_command_result()
 }
 
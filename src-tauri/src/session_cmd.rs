@@ -0,0 +1,59 @@
+// Session commands module - Tauri command implementations for lookup sessions
+// A session is used by a window, tab or split view to look up in its own dictionary
+use tauri::command;
+
+use crate::error::{CommandResult, IntoCommandResult};
+use crate::mdict_app::{with_read_access, with_write_access};
+use crate::mdx_profile::ProfileId;
+use crate::session_mgr::{SessionId, SessionInfo};
+
+/// Create a session, optionally opening a profile in it
+#[command]
+pub async fn session_create(profile_id: Option<ProfileId>) -> CommandResult<SessionId> {
+    with_write_access(|app| {
+        let session_id = app.session_manager.create_session();
+        if let Some(profile_id) = profile_id {
+            if let Err(e) = app.open_session_db(Some(session_id), profile_id) {
+                let _ = app.session_manager.close_session(session_id);
+                return Err(e);
+            }
+        }
+        Ok(session_id)
+    }).into_command_result()
+}
+
+/// Close a session, its database is closed when no other session uses it
+#[command]
+pub async fn session_close(session_id: SessionId) -> CommandResult<()> {
+    with_write_access(|app| app.session_manager.close_session(session_id)).into_command_result()
+}
+
+/// Open a profile (dictionary or group) in a session
+#[command]
+pub async fn session_open_profile(session_id: SessionId, profile_id: ProfileId) -> CommandResult<()> {
+    with_write_access(|app| app.open_session_db(Some(session_id), profile_id)).into_command_result()
+}
+
+/// List all sessions
+#[command]
+pub async fn session_list() -> CommandResult<Vec<SessionInfo>> {
+    with_read_access(|app| Ok(app.session_manager.list_sessions())).into_command_result()
+}
+
+/// Navigate a session to a URL
+#[command]
+pub async fn session_navigate(session_id: SessionId, url: String) -> CommandResult<()> {
+    with_write_access(|app| app.navigate_to(Some(session_id), &url)).into_command_result()
+}
+
+/// Go back in the history of a session, returns the URL shown
+#[command]
+pub async fn session_go_back(session_id: SessionId) -> CommandResult<Option<String>> {
+    with_write_access(|app| app.navigate_back(Some(session_id))).into_command_result()
+}
+
+/// Go forward in the history of a session, returns the URL shown
+#[command]
+pub async fn session_go_forward(session_id: SessionId) -> CommandResult<Option<String>> {
+    with_write_access(|app| app.navigate_forward(Some(session_id))).into_command_result()
+}
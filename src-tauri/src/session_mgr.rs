@@ -0,0 +1,236 @@
+// Session manager module - lookup sessions for windows, tabs and split views
+// Each session has its own open profile, cached search results and navigation history,
+// databases opened by several sessions are shared
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use serde::Serialize;
+
+use crate::error::{session_not_found, Result, ZdbError};
+use crate::lookup_context::{DbType, LookupContext};
+use crate::mdx_profile::{ProfileId, INVALID_PROFILE_ID};
+
+pub type SessionId = u32;
+
+/// The session used when a command doesn't name one, it always exists
+pub const DEFAULT_SESSION_ID: SessionId = 0;
+
+/// Back/forward history of the pages shown in a session
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct NavigationState {
+    pub current_url: Option<String>,
+    back: Vec<String>,
+    forward: Vec<String>,
+}
+
+impl NavigationState {
+    /// Show a new page, the forward history is dropped
+    pub fn navigate(&mut self, url: &str) {
+        if self.current_url.as_deref() == Some(url) {
+            return;
+        }
+        if let Some(current) = self.current_url.replace(url.to_string()) {
+            self.back.push(current);
+        }
+        self.forward.clear();
+    }
+
+    /// Go back one page, returns the URL to show
+    pub fn go_back(&mut self) -> Option<String> {
+        let url = self.back.pop()?;
+        if let Some(current) = self.current_url.replace(url.clone()) {
+            self.forward.push(current);
+        }
+        Some(url)
+    }
+
+    /// Go forward one page, returns the URL to show
+    pub fn go_forward(&mut self) -> Option<String> {
+        let url = self.forward.pop()?;
+        if let Some(current) = self.current_url.replace(url.clone()) {
+            self.back.push(current);
+        }
+        Some(url)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+/// A lookup session
+pub struct LookupSession {
+    pub session_id: SessionId,
+    pub lookup_context: Option<LookupContext>,
+    pub navigation: NavigationState,
+}
+
+/// Session summary returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub session_id: SessionId,
+    pub profile_id: ProfileId,
+    pub current_url: Option<String>,
+    pub can_go_back: bool,
+    pub can_go_forward: bool,
+}
+
+impl LookupSession {
+    fn new(session_id: SessionId) -> Self {
+        Self {
+            session_id,
+            lookup_context: None,
+            navigation: NavigationState::default(),
+        }
+    }
+
+    pub fn profile_id(&self) -> ProfileId {
+        self.lookup_context.as_ref()
+            .map(|context| context.db.profile().profile_id)
+            .unwrap_or(INVALID_PROFILE_ID)
+    }
+
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            session_id: self.session_id,
+            profile_id: self.profile_id(),
+            current_url: self.navigation.current_url.clone(),
+            can_go_back: self.navigation.can_go_back(),
+            can_go_forward: self.navigation.can_go_forward(),
+        }
+    }
+}
+
+pub struct SessionManager {
+    sessions: HashMap<SessionId, LookupSession>,
+    next_session_id: SessionId,
+    /// Databases opened by any session, by profile id
+    open_dbs: HashMap<ProfileId, Weak<DbType>>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        let mut sessions = HashMap::new();
+        sessions.insert(DEFAULT_SESSION_ID, LookupSession::new(DEFAULT_SESSION_ID));
+        Self {
+            sessions,
+            next_session_id: DEFAULT_SESSION_ID + 1,
+            open_dbs: HashMap::new(),
+        }
+    }
+
+    /// Create a new session without an open profile
+    pub fn create_session(&mut self) -> SessionId {
+        let session_id = self.next_session_id;
+        self.next_session_id += 1;
+        self.sessions.insert(session_id, LookupSession::new(session_id));
+        session_id
+    }
+
+    /// Close a session, the default session can't be closed
+    pub fn close_session(&mut self, session_id: SessionId) -> Result<()> {
+        if session_id == DEFAULT_SESSION_ID {
+            return Err(ZdbError::invalid_parameter("The default session can't be closed".to_string()));
+        }
+        self.sessions.remove(&session_id)
+            .map(|_| ())
+            .ok_or_else(|| session_not_found(session_id))
+    }
+
+    /// Get a session, `None` is the default session
+    pub fn get_session(&self, session_id: Option<SessionId>) -> Result<&LookupSession> {
+        let session_id = session_id.unwrap_or(DEFAULT_SESSION_ID);
+        self.sessions.get(&session_id).ok_or_else(|| session_not_found(session_id))
+    }
+
+    pub fn get_session_mut(&mut self, session_id: Option<SessionId>) -> Result<&mut LookupSession> {
+        let session_id = session_id.unwrap_or(DEFAULT_SESSION_ID);
+        self.sessions.get_mut(&session_id).ok_or_else(|| session_not_found(session_id))
+    }
+
+    pub fn list_sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self.sessions.values().map(|session| session.info()).collect();
+        sessions.sort_by_key(|info| info.session_id);
+        sessions
+    }
+
+    /// Get a database already opened by some session
+    pub fn get_open_db(&self, profile_id: ProfileId) -> Option<Arc<DbType>> {
+        self.open_dbs.get(&profile_id).and_then(|db| db.upgrade())
+    }
+
+    /// Find an open database containing the library `profile_id` (the library itself or a group containing it)
+    pub fn find_db_for_profile(&self, profile_id: ProfileId) -> Option<Arc<DbType>> {
+        self.open_dbs.values()
+            .filter_map(|db| db.upgrade())
+            .find(|db| db.get_db(profile_id).is_ok())
+    }
+
+    /// Give a session a new lookup context on `db`, its cached search results and history are reset
+    pub fn set_session_db(&mut self, session_id: Option<SessionId>, db: Arc<DbType>) -> Result<()> {
+        let profile_id = db.profile().profile_id;
+        let session = self.get_session_mut(session_id)?;
+        session.lookup_context = Some(LookupContext::new(db.clone()));
+        session.navigation = NavigationState::default();
+
+        self.open_dbs.retain(|_, db| db.strong_count() > 0);
+        self.open_dbs.insert(profile_id, Arc::downgrade(&db));
+        Ok(())
+    }
+
+    /// Close the database of a session
+    pub fn clear_session_db(&mut self, session_id: Option<SessionId>) -> Result<()> {
+        let session = self.get_session_mut(session_id)?;
+        session.lookup_context = None;
+        session.navigation = NavigationState::default();
+        Ok(())
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation_history() {
+        let mut navigation = NavigationState::default();
+        navigation.navigate("a");
+        navigation.navigate("b");
+        navigation.navigate("c");
+        assert_eq!(navigation.go_back().as_deref(), Some("b"));
+        assert_eq!(navigation.go_back().as_deref(), Some("a"));
+        assert_eq!(navigation.go_back(), None);
+        assert_eq!(navigation.go_forward().as_deref(), Some("b"));
+
+        // A new page drops the forward history
+        navigation.navigate("d");
+        assert!(!navigation.can_go_forward());
+        assert_eq!(navigation.go_back().as_deref(), Some("b"));
+        assert_eq!(navigation.current_url.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_sessions() {
+        let mut manager = SessionManager::new();
+        let session_id = manager.create_session();
+        assert_ne!(session_id, DEFAULT_SESSION_ID);
+        assert_eq!(manager.list_sessions().len(), 2);
+
+        manager.get_session_mut(Some(session_id)).unwrap().navigation.navigate("x");
+        assert!(manager.get_session(None).unwrap().navigation.current_url.is_none());
+        assert_eq!(manager.get_session(Some(session_id)).unwrap().profile_id(), INVALID_PROFILE_ID);
+
+        assert!(manager.close_session(DEFAULT_SESSION_ID).is_err());
+        manager.close_session(session_id).unwrap();
+        assert!(manager.get_session(Some(session_id)).is_err());
+    }
+}
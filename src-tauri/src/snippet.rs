@@ -0,0 +1,240 @@
+// Snippet module - finds the terms of a full-text query in entry text
+// Terms match case-insensitively at the start of a word ("colour" matches "Colours" but not "tricolour"),
+// in scripts written without spaces (Chinese, Japanese) they match anywhere
+use crate::api_types::Snippet;
+use crate::chinese_conv;
+use crate::html_text::{decoded_chars, find_tag_end, tag_name, SKIPPED_TAGS};
+
+/// Length of a snippet in characters, without the ellipses
+const SNIPPET_LENGTH: usize = 160;
+/// Characters of context kept before the first match of a snippet
+const CONTEXT_BEFORE: usize = 40;
+const ELLIPSIS: char = '\u{2026}';
+/// Id of the first highlighted term, an entry URL ending with `#mdict-first-match` scrolls to it
+pub const FIRST_MATCH_ID: &str = "mdict-first-match";
+
+/// Terms of a full-text query: its words without surrounding punctuation, lowercase and without repeats
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query.split_whitespace() {
+        let term = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+        if !term.is_empty() && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Scripts written without spaces between words
+pub fn is_unspaced(c: char) -> bool {
+    chinese_conv::is_han(c) || matches!(c, '\u{3040}'..='\u{30FF}' | '\u{AC00}'..='\u{D7AF}')
+}
+
+/// Whether a term may start matching at `pos`, i.e. `pos` is the start of a word
+fn is_word_start(chars: &[char], pos: usize) -> bool {
+    pos == 0 || !chars[pos - 1].is_alphanumeric() || is_unspaced(chars[pos - 1]) || is_unspaced(chars[pos])
+}
+
+/// Matches of the terms in `chars`, as sorted non-overlapping [start, end) character ranges
+/// The longest term matching at a position is taken
+pub fn find_terms_in(chars: &[char], terms: &[String]) -> Vec<(usize, usize)> {
+    let terms: Vec<Vec<char>> = terms.iter().map(|term| term.chars().map(fold).collect()).collect();
+    let mut matches = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let longest = terms.iter()
+            .filter(|term| !term.is_empty() && pos + term.len() <= chars.len())
+            .filter(|term| term.iter().zip(&chars[pos..]).all(|(t, c)| *t == fold(*c)))
+            .map(|term| term.len())
+            .max();
+        match longest {
+            Some(len) if is_word_start(chars, pos) => {
+                matches.push((pos, pos + len));
+                pos += len;
+            }
+            _ => pos += 1,
+        }
+    }
+    matches
+}
+
+/// Matches of the terms in `text`, see `find_terms_in`
+pub fn find_terms(text: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    find_terms_in(&text.chars().collect::<Vec<_>>(), terms)
+}
+
+/// A short single line excerpt of `text` around the part with the most matches of the terms,
+/// the beginning of the text when none of them matches
+pub fn make_snippet(text: &str, terms: &[String]) -> Snippet {
+    let chars: Vec<char> = text.chars().map(|c| if c == '\n' { ' ' } else { c }).collect();
+    let matches = find_terms_in(&chars, terms);
+
+    // The match starting the window of SNIPPET_LENGTH with the most matches, the first one on a tie
+    let mut best: Option<(usize, usize)> = None;
+    let mut window_end = 0;
+    for (i, (start, _)) in matches.iter().enumerate() {
+        window_end = window_end.max(i);
+        while window_end < matches.len() && matches[window_end].1 <= start + SNIPPET_LENGTH {
+            window_end += 1;
+        }
+        if best.is_none_or(|(_, count)| window_end - i > count) {
+            best = Some((*start, window_end - i));
+        }
+    }
+
+    let mut start = 0;
+    if let Some((first_match, _)) = best {
+        start = first_match.saturating_sub(CONTEXT_BEFORE);
+        // Begin at a word when the context has a space
+        if start > 0
+            && let Some(space) = chars[start..first_match].iter().position(|c| *c == ' ')
+        {
+            start += space + 1;
+        }
+    }
+    let mut end = (start + SNIPPET_LENGTH).min(chars.len());
+    // End at a word when the text goes on
+    if end < chars.len()
+        && let Some(space) = chars[start..end].iter().rposition(|c| *c == ' ')
+        && space > 0
+    {
+        end = start + space;
+    }
+
+    let mut snippet = String::new();
+    let offset = if start > 0 {
+        snippet.push(ELLIPSIS);
+        1
+    } else {
+        0
+    };
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push(ELLIPSIS);
+    }
+    Snippet {
+        text: snippet,
+        matches: matches.into_iter()
+            .filter(|(match_start, match_end)| *match_start >= start && *match_end <= end)
+            .map(|(match_start, match_end)| (match_start - start + offset, match_end - start + offset))
+            .collect(),
+    }
+}
+
+/// Wrap the terms in the text of an HTML page in `<mark>` elements, the first one gets the id `FIRST_MATCH_ID`
+/// Tags, attribute values, comments and the content of script, style and title elements are never changed
+pub fn highlight_html(html: &str, terms: &[String]) -> String {
+    if terms.is_empty() {
+        return html.to_string();
+    }
+    let mut out = String::with_capacity(html.len() + 64);
+    let mut first = true;
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        highlight_text(&mut out, &rest[..start], terms, &mut first);
+        rest = &rest[start..];
+
+        // Comments may contain '>' so they are kept as a whole
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |end| end + 3);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let Some(end) = find_tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        let name = tag_name(tag);
+        let mut kept = end + 1;
+        if !tag.starts_with('/') && SKIPPED_TAGS.contains(&name.as_str()) {
+            // Keep everything up to and including the closing tag
+            let closing = format!("</{}", name);
+            kept = match rest.to_ascii_lowercase().find(&closing) {
+                Some(pos) => find_tag_end(&rest[pos..]).map_or(rest.len(), |end| pos + end + 1),
+                None => rest.len(),
+            };
+        }
+        out.push_str(&rest[..kept]);
+        rest = &rest[kept..];
+    }
+    // What is left is text, or a tag that is never closed
+    if rest.starts_with('<') {
+        out.push_str(rest);
+    } else {
+        highlight_text(&mut out, rest, terms, &mut first);
+    }
+    out
+}
+
+/// Append HTML text with the terms wrapped in `<mark>`, character references are kept as they are
+fn highlight_text(out: &mut String, text: &str, terms: &[String], first: &mut bool) {
+    let units = decoded_chars(text);
+    let chars: Vec<char> = units.iter().map(|(_, c)| *c).collect();
+    let mut pos = 0;
+    for (start, end) in find_terms_in(&chars, terms) {
+        let match_start = units[start].0;
+        let match_end = units.get(end).map_or(text.len(), |(offset, _)| *offset);
+        out.push_str(&text[pos..match_start]);
+        if *first {
+            out.push_str(&format!("<mark id=\"{}\">", FIRST_MATCH_ID));
+            *first = false;
+        } else {
+            out.push_str("<mark>");
+        }
+        out.push_str(&text[match_start..match_end]);
+        out.push_str("</mark>");
+        pos = match_end;
+    }
+    out.push_str(&text[pos..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_terms() {
+        let terms = query_terms("\"Colour\" tint, colour");
+        assert_eq!(terms, vec!["colour", "tint"]);
+        assert_eq!(find_terms("Colours and a tricolour tint", &terms), vec![(0, 6), (24, 28)]);
+        assert_eq!(find_terms("红色的颜色", &query_terms("颜色")), vec![(3, 5)]);
+    }
+
+    #[test]
+    fn test_make_snippet() {
+        let terms = query_terms("hue");
+        let snippet = make_snippet("colour\nthe hue of a thing", &terms);
+        assert_eq!(snippet.text, "colour the hue of a thing");
+        assert_eq!(snippet.matches, vec![(11, 14)]);
+
+        let text = format!("{} the hue {}", "word ".repeat(40), "word ".repeat(40));
+        let snippet = make_snippet(&text, &terms);
+        assert!(snippet.text.starts_with('\u{2026}') && snippet.text.ends_with('\u{2026}'));
+        let (start, end) = snippet.matches[0];
+        let matched: String = snippet.text.chars().skip(start).take(end - start).collect();
+        assert_eq!(matched, "hue");
+        assert!(snippet.text.chars().count() <= SNIPPET_LENGTH + 2);
+
+        let snippet = make_snippet("no match here", &terms);
+        assert_eq!(snippet.text, "no match here");
+        assert!(snippet.matches.is_empty());
+    }
+
+    #[test]
+    fn test_highlight_html() {
+        let terms = query_terms("hue caf\u{e9}");
+        let html = r#"<head><title>hue</title></head><a title="hue">Hue</a> <!-- hue --><script>var hue = "<b>";</script>caf&#xE9; hue"#;
+        assert_eq!(
+            highlight_html(html, &terms),
+            r#"<head><title>hue</title></head><a title="hue"><mark id="mdict-first-match">Hue</mark></a> <!-- hue --><script>var hue = "<b>";</script><mark>caf&#xE9;</mark> <mark>hue</mark>"#
+        );
+        assert_eq!(highlight_html("<p>no match", &terms), "<p>no match");
+        assert_eq!(highlight_html("<p>hue", &[]), "<p>hue");
+    }
+}
@@ -0,0 +1,95 @@
+// Spelling module - weighted Damerau-Levenshtein distance for "did you mean" suggestions
+// Substituting a key next to the intended one on a QWERTY keyboard costs less than other
+// substitutions, so typos rank before unrelated words at the same plain edit distance
+
+/// Cost of inserting, deleting or substituting a character
+const EDIT_COST: f32 = 1.0;
+/// Cost of substituting a character with a neighbouring key
+const ADJACENT_KEY_COST: f32 = 0.5;
+/// Cost of swapping two adjacent characters
+const TRANSPOSITION_COST: f32 = 0.75;
+
+/// QWERTY letter rows, each row is shifted half a key further right than the one above
+const KEYBOARD_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Row and horizontal position of a letter on the keyboard
+fn key_position(c: char) -> Option<(f32, f32)> {
+    KEYBOARD_ROWS.iter().enumerate().find_map(|(row, keys)| {
+        keys.chars()
+            .position(|key| key == c)
+            .map(|column| (row as f32, column as f32 + row as f32 * 0.5))
+    })
+}
+
+/// Whether two letters are neighbouring keys
+pub fn is_adjacent_key(a: char, b: char) -> bool {
+    match (key_position(a), key_position(b)) {
+        (Some((row_a, x_a)), Some((row_b, x_b))) => a != b && (row_a - row_b).abs() <= 1.0 && (x_a - x_b).abs() <= 1.0,
+        _ => false,
+    }
+}
+
+fn substitution_cost(a: char, b: char) -> f32 {
+    if a == b {
+        0.0
+    } else if is_adjacent_key(a, b) {
+        ADJACENT_KEY_COST
+    } else {
+        EDIT_COST
+    }
+}
+
+/// Weighted Damerau-Levenshtein (optimal string alignment) distance, case-sensitive
+pub fn edit_distance(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0.0f32; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i as f32 * EDIT_COST;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j as f32 * EDIT_COST;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let mut distance = (d[i - 1][j] + EDIT_COST)
+                .min(d[i][j - 1] + EDIT_COST)
+                .min(d[i - 1][j - 1] + substitution_cost(a[i - 1], b[j - 1]));
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(d[i - 2][j - 2] + TRANSPOSITION_COST);
+            }
+            d[i][j] = distance;
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Largest distance a suggestion may have, short words allow fewer edits
+pub fn max_distance(word: &str) -> f32 {
+    if word.chars().count() <= 4 { 1.0 } else { 2.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacent_keys() {
+        assert!(is_adjacent_key('a', 'q') && is_adjacent_key('a', 'w') && is_adjacent_key('g', 'h'));
+        assert!(is_adjacent_key('b', 'g') && is_adjacent_key('b', 'h'));
+        assert!(!is_adjacent_key('a', 'e') && !is_adjacent_key('q', 's') && !is_adjacent_key('a', 'a'));
+        assert!(!is_adjacent_key('1', '2'));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("color", "color"), 0.0);
+        assert_eq!(edit_distance("colour", "color"), 1.0);
+        assert_eq!(edit_distance("", "abc"), 3.0);
+        // Swapped letters
+        assert_eq!(edit_distance("recieve", "receive"), TRANSPOSITION_COST);
+        // "s" is next to "a", "p" is not
+        assert_eq!(edit_distance("hsppy", "happy"), ADJACENT_KEY_COST);
+        assert!(edit_distance("hsppy", "happy") < edit_distance("hpppy", "happy"));
+    }
+}
@@ -1,11 +1,39 @@
 // System commands module - Tauri command implementations for system-level operations
 use tauri::command;
 
-use crate::error::IntoStringResult;
-use crate::mdict_app::with_write_access;
+use crate::app_config::{ConfigSection, ConfigKey};
+use crate::error::{CommandResult, IntoCommandResult};
+use crate::http_server;
+use crate::mdict_app::{with_config_read, with_write_access};
 
 /// Set base URL for MDX protocol
 #[command]
-pub async fn system_set_base_url(base_url: String) -> std::result::Result<(), String> {
-    with_write_access(|app| app.set_base_url(base_url)).into_string_result()
+pub async fn system_set_base_url(base_url: String) -> CommandResult<()> {
+    with_write_access(|app| app.set_base_url(base_url)).into_command_result()
+}
+
+/// Start the local HTTP server, uses the configured port if none is given
+/// Returns the service base URL
+#[command]
+pub async fn system_start_http_server(port: Option<u16>) -> CommandResult<String> {
+    let port = match port {
+        Some(port) => port,
+        None => with_config_read(|config| {
+            Ok(config.get_config_with_default(ConfigSection::Global, ConfigKey::HttpServerPort, 0u16))
+        }).into_command_result()?,
+    };
+    http_server::start_http_server(port).into_command_result()
+}
+
+/// Stop the local HTTP server
+#[command]
+pub async fn system_stop_http_server() -> CommandResult<()> {
+    http_server::stop_http_server();
+    Ok(())
+}
+
+/// Get the service base URL of the local HTTP server, None if it is not running
+#[command]
+pub async fn system_get_http_server_base_url() -> CommandResult<Option<String>> {
+    Ok(http_server::get_http_server_base_url())
 }
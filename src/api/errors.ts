@@ -0,0 +1,54 @@
+/**
+ * Command Errors
+ *
+ * Tauri commands reject with a structured error `{ code, message, details }`.
+ * `code` is stable and lets the UI react (e.g. offer to open a dictionary
+ * when `no_database_open` is returned), `message` is an English sentence
+ * that is also the translation key, `details` is the underlying error text.
+ */
+
+import i18n from '../i18n/i18n';
+
+export type ErrorCode =
+  | 'not_found'
+  | 'no_database_open'
+  | 'profile_not_found'
+  | 'session_not_found'
+  | 'invalid_query'
+  | 'invalid_argument'
+  | 'io'
+  | 'cancelled'
+  | 'internal';
+
+export interface CommandError {
+  code: ErrorCode;
+  message: string;
+  details?: string;
+}
+
+/**
+ * Check whether a rejected value is a command error
+ */
+export const isCommandError = (error: unknown): error is CommandError => {
+  return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+};
+
+/**
+ * Check whether a rejected value is a command error with the given code
+ */
+export const hasErrorCode = (error: unknown, code: ErrorCode): boolean => {
+  return isCommandError(error) && error.code === code;
+};
+
+/**
+ * Get the translated user-facing message of any rejected value
+ */
+export const getErrorMessage = (error: unknown): string => {
+  if (isCommandError(error)) {
+    return i18n.t(error.message);
+  }
+  if (error instanceof Error) {
+    return error.message;
+  }
+  return String(error);
+};
@@ -18,8 +18,9 @@ import * as systemAPI from './system';
 import * as historyAPI from './history';
 import * as favoritesAPI from './favorites';
 import * as hotkeyAPI from './hotkey';
+import * as sessionAPI from './session';
 
-export { libraryAPI, searchAPI, configAPI, systemAPI, historyAPI, favoritesAPI, hotkeyAPI };
+export { libraryAPI, searchAPI, configAPI, systemAPI, historyAPI, favoritesAPI, hotkeyAPI, sessionAPI };
 
 // Also export individual functions for convenience
 export * from './library';
@@ -29,3 +30,5 @@ export * from './system';
 export * from './history';
 export * from './favorites';
 export * from './hotkey';
+export * from './session';
+export * from './errors';
@@ -11,7 +11,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import { MdxProfile } from '../types';
+import { KeyNormalization, Languages, MdxProfile } from '../types';
 
 /**
  * Create a new dictionary group
@@ -34,6 +34,61 @@ export const renameDictGroup = async (groupId: number, newName: string): Promise
   await invoke('library_rename_dict_group', { groupId, newName });
 };
 
+/**
+ * Set how headwords are merged in search results, for the group itself or one of its dictionaries
+ * Takes effect when the group is opened again
+ */
+export const setKeyNormalization = async (
+  groupId: number,
+  profileId: number | null,
+  keyNormalization: KeyNormalization
+): Promise<void> => {
+  await invoke('library_set_key_normalization', { groupId, profileId, keyNormalization });
+};
+
+/**
+ * Set the locale of the order of a group's merged key list (BCP 47, like "de" or "zh-u-co-pinyin"),
+ * empty for the root collation. Takes effect when the group is opened again
+ */
+export const setCollationLocale = async (
+  groupId: number,
+  collationLocale: string
+): Promise<void> => {
+  await invoke('library_set_collation_locale', { groupId, collationLocale });
+};
+
+/**
+ * Set the weight of the full-text scores of a library of a group, null resets it to 1.0
+ * Takes effect when the group is opened again
+ */
+export const setFtsWeight = async (
+  groupId: number,
+  profileId: number,
+  ftsWeight: number | null
+): Promise<void> => {
+  await invoke('library_set_fts_weight', { groupId, profileId, ftsWeight });
+};
+
+/**
+ * Set the languages of the headwords and definitions of a library of a group (BCP 47 codes like "en"),
+ * an empty language is detected from the dictionary. Takes effect when the group is opened again
+ */
+export const setLanguages = async (
+  groupId: number,
+  profileId: number,
+  sourceLanguage: string,
+  targetLanguage: string
+): Promise<void> => {
+  await invoke('library_set_languages', { groupId, profileId, sourceLanguage, targetLanguage });
+};
+
+/**
+ * Get the languages of a library of the opened dictionary, as set or detected
+ */
+export const getLanguages = async (profileId: number, sessionId?: number): Promise<Languages> => {
+  return await invoke('library_get_languages', { profileId, sessionId });
+};
+
 /**
  * Refresh library from disk
  */
@@ -8,52 +8,124 @@
  * - Single responsibility (search operations only)
  * - Clean API boundary
  * - Reusable across components
+ *
+ * All functions take an optional session id, the default session is used without it.
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import { MdxGroupIndex, SearchResultEntry, MdxIndex } from '../types';
+import { MatchKind, MdxGroupIndex, SearchResultEntry, MdxIndex, Snippet, Suggestion } from '../types';
 import { useSystemStore } from '../store/useSystemStore';
 
 /**
  * Perform incremental (index) search
  * Returns {start_entry_no: -1, total_count: 0} if no results found
+ * In group mode `has_more` tells that the key list continues past `total_count` when it is scrolled
  */
 export const searchIncremental = async (
-  query: string
-): Promise<{ start_entry_no: number; total_count: number }> => {
-  return await invoke('search_search_incremental', { query });
+  query: string,
+  sessionId?: number
+): Promise<{ start_entry_no: number; total_count: number; has_more?: boolean }> => {
+  return await invoke('search_search_incremental', { query, sessionId });
 };
 
 /**
  * Perform fulltext search
  */
 export const fulltextSearch = async (
-  query: string
+  query: string,
+  sessionId?: number
 ): Promise<{ start_entry_no: number; total_count: number }> => {
-  return await invoke('search_fulltext_search', { query });
+  return await invoke('search_fulltext_search', { query, sessionId });
+};
+
+/**
+ * Incremental search that falls back to full-text search when no headword starts with the query
+ * The results are then the nearest headwords followed by the full-text results, tagged with `match_kind`
+ * `fulltext_failed` tells that the fallback failed and only headwords are listed
+ */
+export const combinedSearch = async (
+  query: string,
+  sessionId?: number
+): Promise<{ start_entry_no: number; total_count: number; has_more?: boolean; fulltext_failed?: boolean }> => {
+  return await invoke('search_combined_search', { query, sessionId });
+};
+
+/**
+ * Headword search with `*` and `?` wildcards, or a regular expression when `regex` is true
+ */
+export const patternSearch = async (
+  pattern: string,
+  regex: boolean = false,
+  sessionId?: number
+): Promise<{ start_entry_no: number; total_count: number }> => {
+  return await invoke('search_pattern_search', { query: pattern, regex, sessionId });
+};
+
+/**
+ * Detect a pattern query: `/regex/` or a query containing `*` or `?`
+ * Returns null for a plain headword
+ */
+export const parsePatternQuery = (query: string): { pattern: string; regex: boolean } | null => {
+  const trimmed = query.trim();
+  if (trimmed.length > 2 && trimmed.startsWith('/') && trimmed.endsWith('/')) {
+    return { pattern: trimmed.slice(1, -1), regex: true };
+  }
+  if (/[*?]/.test(trimmed)) {
+    return { pattern: trimmed, regex: false };
+  }
+  return null;
+};
+
+/**
+ * Incremental search falling back to pinyin search when no headword starts with the query,
+ * tones are optional ("zhongguo", "zhong1guo2")
+ */
+export const pinyinSearch = async (
+  query: string,
+  sessionId?: number
+): Promise<{ start_entry_no: number; total_count: number; has_more?: boolean }> => {
+  return await invoke('search_pinyin_search', { query, sessionId });
+};
+
+/**
+ * Whether a query can be pinyin: latin letters with optional tone numbers, spaces and apostrophes
+ */
+export const isPinyinQuery = (query: string): boolean => {
+  return /^[a-zA-ZüÜ][a-zA-ZüÜ:1-5' ]*$/.test(query.trim());
 };
 
 /**
  * Get content URL for an entry by index number
  */
-export const getContentUrl = async (indexNo: number): Promise<string> => {
-  return await invoke('search_get_content_url', { indexNo });
+export const getContentUrl = async (indexNo: number, sessionId?: number): Promise<string> => {
+  return await invoke('search_get_content_url', { indexNo, sessionId });
 };
 
 /**
  * Get total entry count
+ * `has_more` tells that the key list of a group is still merged and the count is partial
  */
-export const getEntryCount = async (): Promise<number> => {
-  return await invoke('search_get_entry_count');
+export const getEntryCount = async (sessionId?: number): Promise<{ total_count: number; has_more: boolean }> => {
+  return await invoke('search_get_entry_count', { sessionId });
+};
+
+/**
+ * Whether the key list of a group incremental search continues past the entry count
+ */
+export const hasMoreResults = async (sessionId?: number): Promise<boolean> => {
+  return await invoke('search_has_more_results', { sessionId });
 };
 
 /**
  * Find index by keyword
+ * An inflected form missing from the dictionaries is found by its stem, `stem` is then set
+ * and is the `primary_key` of the results
  */
 export const findIndex = async (
-  key: string
-): Promise<{ group_index: MdxGroupIndex; total_count: number }> => {
-  return await invoke('search_find_index', { key });
+  key: string,
+  sessionId?: number
+): Promise<{ results: MdxGroupIndex[]; stem?: string }> => {
+  return await invoke('search_find_index', { key, sessionId });
 };
 
 /**
@@ -61,24 +133,40 @@ export const findIndex = async (
  */
 export const getResultKeyList = async (
   startIndexNo: number,
-  maxCount: number
+  maxCount: number,
+  sessionId?: number
 ): Promise<SearchResultEntry[]> => {
-  const results = await invoke<[string, number][]>('search_get_result_key_list', {
+  const results = await invoke<{ key: string; count: number; score?: number; snippet?: Snippet; match_kind?: MatchKind }[]>('search_get_result_key_list', {
     startIndexNo,
     maxCount,
+    sessionId,
   });
-  // Transform tuple array to SearchResultEntry objects
-  return results.map(([keyword, entry_count]) => ({
-    keyword,
-    entry_count,
+  // Transform result keys to SearchResultEntry objects
+  return results.map(({ key, count, score, snippet, match_kind }) => ({
+    keyword: key,
+    entry_count: count,
+    score,
+    snippet,
+    match_kind,
   }));
 };
 
 /**
  * Get group indexes for a given index number
  */
-export const getGroupIndexes = async (indexNo: number): Promise<MdxGroupIndex[]> => {
-  return await invoke('search_get_group_indexes', { indexNo });
+export const getGroupIndexes = async (indexNo: number, sessionId?: number): Promise<MdxGroupIndex[]> => {
+  return await invoke('search_get_group_indexes', { indexNo, sessionId });
+};
+
+/**
+ * "Did you mean" suggestions for a headword that is not found, closest first
+ */
+export const suggest = async (
+  word: string,
+  maxResults?: number,
+  sessionId?: number
+): Promise<Suggestion[]> => {
+  return await invoke('search_suggest', { word, maxResults, sessionId });
 };
 
 /**
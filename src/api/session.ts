@@ -0,0 +1,67 @@
+/**
+ * Session API Module
+ *
+ * Lookup sessions let windows, tabs and split views look up in their own
+ * dictionary or group, with their own search results and navigation history.
+ * Navigation of a session other than the default one is reported by the
+ * `session_navigate_to` event with `{ session_id, url }`.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface SessionInfo {
+  session_id: number;
+  profile_id: number;
+  current_url: string | null;
+  can_go_back: boolean;
+  can_go_forward: boolean;
+}
+
+/**
+ * Create a session, optionally opening a profile in it
+ */
+export const createSession = async (profileId?: number): Promise<number> => {
+  return await invoke('session_create', { profileId });
+};
+
+/**
+ * Close a session
+ */
+export const closeSession = async (sessionId: number): Promise<void> => {
+  await invoke('session_close', { sessionId });
+};
+
+/**
+ * Open a dictionary or group in a session
+ */
+export const openSessionProfile = async (sessionId: number, profileId: number): Promise<void> => {
+  await invoke('session_open_profile', { sessionId, profileId });
+};
+
+/**
+ * List all sessions
+ */
+export const listSessions = async (): Promise<SessionInfo[]> => {
+  return await invoke('session_list');
+};
+
+/**
+ * Navigate a session to a URL
+ */
+export const sessionNavigate = async (sessionId: number, url: string): Promise<void> => {
+  await invoke('session_navigate', { sessionId, url });
+};
+
+/**
+ * Go back in the history of a session, returns the URL shown
+ */
+export const sessionGoBack = async (sessionId: number): Promise<string | null> => {
+  return await invoke('session_go_back', { sessionId });
+};
+
+/**
+ * Go forward in the history of a session, returns the URL shown
+ */
+export const sessionGoForward = async (sessionId: number): Promise<string | null> => {
+  return await invoke('session_go_forward', { sessionId });
+};
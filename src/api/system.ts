@@ -18,3 +18,24 @@ export const setBaseUrl = async (baseUrl: string): Promise<void> => {
   await invoke('system_set_base_url', { baseUrl });
 };
 
+/**
+ * Start the local HTTP server (uses the configured port if none is given)
+ * Returns the service base URL, e.g. http://127.0.0.1:8765/service/
+ */
+export const startHttpServer = async (port?: number): Promise<string> => {
+  return await invoke('system_start_http_server', { port });
+};
+
+/**
+ * Stop the local HTTP server
+ */
+export const stopHttpServer = async (): Promise<void> => {
+  await invoke('system_stop_http_server');
+};
+
+/**
+ * Get the service base URL of the local HTTP server, null if it is not running
+ */
+export const getHttpServerBaseUrl = async (): Promise<string | null> => {
+  return await invoke('system_get_http_server_base_url');
+};
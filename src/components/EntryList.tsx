@@ -6,6 +6,8 @@ import { useSearchStore } from '../store/useSearchStore';
 import { EntryRow } from './EntryRow';
 
 const ITEM_HEIGHT = 48;
+// Full-text and combined results show a line of the entry (or how they matched) below the headword
+const SNIPPET_ITEM_HEIGHT = 72;
 
 /**
  * Props for the EntryList component
@@ -71,6 +73,10 @@ export const EntryList = forwardRef<EntryListRef, EntryListProps>(
 
     // Get data from search store
     const  totalCount = useSearchStore((state) => state.totalCount);
+    // One more row than loaded while a group's key list continues, loading it loads the next keys
+    const hasMore = useSearchStore((state) => state.hasMore);
+    const searchMode = useSearchStore((state) => state.searchMode);
+    const rowCount = hasMore ? totalCount + 1 : totalCount;
     const getItem = useSearchStore((state) => state.getItem);
     const isItemLoaded = useSearchStore((state) => state.isItemLoaded);
     const loadPages = useSearchStore((state) => state.loadPages);
@@ -105,7 +111,7 @@ export const EntryList = forwardRef<EntryListRef, EntryListProps>(
       loadMoreRows: async (startIndex: number, stopIndex: number) => {
         await loadPages(startIndex, stopIndex);
       },
-      rowCount: totalCount,
+      rowCount,
       minimumBatchSize,
       threshold,
     });
@@ -141,8 +147,8 @@ export const EntryList = forwardRef<EntryListRef, EntryListProps>(
             ...(style || {})
           }}
           className={className}
-          rowCount={totalCount}
-          rowHeight={ITEM_HEIGHT}
+          rowCount={rowCount}
+          rowHeight={searchMode === 'index' ? ITEM_HEIGHT : SNIPPET_ITEM_HEIGHT}
           rowComponent={rowComponent}
           rowProps={ {} }
           overscanCount={overscanCount}
@@ -1,5 +1,6 @@
 import { IonItem, IonLabel, IonText } from "@ionic/react";
-import { SearchResultEntry } from "../types";
+import { useTranslation } from "react-i18next";
+import { SearchResultEntry, Snippet } from "../types";
 
 /**
  * EntryRow Component
@@ -7,6 +8,22 @@ import { SearchResultEntry } from "../types";
  * Internal component for rendering individual entry rows
  */
 
+/**
+ * Snippet text with the matched terms in <mark>, offsets are code points
+ */
+const SnippetText: React.FC<{ snippet: Snippet }> = ({ snippet }) => {
+    const chars = Array.from(snippet.text);
+    const parts: React.ReactNode[] = [];
+    let pos = 0;
+    snippet.matches.forEach(([start, end], i) => {
+      parts.push(chars.slice(pos, start).join(''));
+      parts.push(<mark key={i}>{chars.slice(start, end).join('')}</mark>);
+      pos = end;
+    });
+    parts.push(chars.slice(pos).join(''));
+    return <>{parts}</>;
+  };
+
 interface EntryRowProps {
     index: number;
     item: SearchResultEntry;
@@ -16,6 +33,7 @@ interface EntryRowProps {
   }
   
   export const EntryRow: React.FC<EntryRowProps> = ({ index, item, isSelected, style, onClick }) => {
+    const { t } = useTranslation();
     if (!item) {
       return (
         <div style={{ 
@@ -64,7 +82,37 @@ interface EntryRowProps {
                 ({item.entry_count})
               </span>
             )}
+            {item.score !== undefined && (
+              <span style={{
+                marginLeft: 'auto',
+                paddingLeft: '8px',
+                color: 'var(--ion-color-medium)',
+                fontSize: 'var(--ion-font-size-small)'
+              }}>
+                {item.match_kind === 'full_text' && `${t('Found in text')} · `}
+                {Math.round(item.score * 100)}%
+              </span>
+            )}
           </div>
+          {item.snippet && (
+            <div style={{
+              whiteSpace: 'nowrap',
+              overflow: 'hidden',
+              textOverflow: 'ellipsis',
+              color: 'var(--ion-color-medium)',
+              fontSize: 'var(--ion-font-size-small)'
+            }}>
+              <SnippetText snippet={item.snippet} />
+            </div>
+          )}
+          {item.match_kind === 'nearest' && (
+            <div style={{
+              color: 'var(--ion-color-medium)',
+              fontSize: 'var(--ion-font-size-small)'
+            }}>
+              {t('Nearest headword')}
+            </div>
+          )}
         </IonLabel>
       </IonItem>
     );
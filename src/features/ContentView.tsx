@@ -19,6 +19,7 @@ import { useTranslation } from 'react-i18next';
 import { MdxGroupIndex, MdxProfile, TargetInfo } from '../types';
 import { INVALID_PROFILE_ID, INVALID_ENTRY_NO } from '../utils/mdxUrlParser';
 import { getIconUrl } from '../utils/iframeTools/mdxHtmlTools';
+import { displayKey } from '../utils/displayUtils';
 import { useFavoritesStore } from '../store';
 import { EntriesView } from '../components/EntriesView';
 import { TabContainer, TabItem, TabContentRenderer } from '../components/TabContainer';
@@ -88,7 +89,7 @@ export const ContentView = forwardRef<ContentViewRef, ContentViewProps>(({
       
       // Check if current entry is favorited
       if (dbProfile && currentMdxGroupIndexes[0]?.indexes[0]) {
-        const keyword = currentMdxGroupIndexes[0].indexes[0].key;
+        const keyword = displayKey(currentMdxGroupIndexes[0].indexes[0]);
         const profileId = dbProfile.profileId;
         const favorited = checkIsFavorited(keyword, profileId);
         setIsFavorited(favorited);
@@ -236,7 +237,7 @@ export const ContentView = forwardRef<ContentViewRef, ContentViewProps>(({
   const handleFavoriteToggle = useCallback(async () => {
     if (currentMdxGroupIndexes.length > 0 && dbProfile) {
       const firstEntry = currentMdxGroupIndexes[0].indexes[0];
-      const keyword = firstEntry.key;
+      const keyword = displayKey(firstEntry);
       const profileId = dbProfile.profileId;
       const profileName = dbProfile.title;      
       
@@ -51,6 +51,7 @@ export const LookupView = forwardRef<LookupViewRef, LookupViewProps>(({
   const entryCacheVersion = useSearchStore((state) => state.entryCacheVersion);
   const searchMode = useSearchStore((state) => state.searchMode);
   const error = useSearchStore((state) => state.error);
+  const fulltextFailed = useSearchStore((state) => state.fulltextFailed);
   const performSearch = useSearchStore((state) => state.performSearch);
   const setSearchMode = useSearchStore((state) => state.setSearchMode);
   const currentProfile = useSearchStore((state) => state.currentProfile);
@@ -86,11 +87,12 @@ export const LookupView = forwardRef<LookupViewRef, LookupViewProps>(({
     }
   }, [handleSearch]);
 
-  const handleItemSelect = useCallback(async (_item: SearchResultEntry, index: number) => {
+  const handleItemSelect = useCallback(async (item: SearchResultEntry, index: number) => {
     if (onIndexesSelected) {
       try {
         const indexGroups = await searchAPI.getGroupIndexes(index);
-        if (searchMode === 'index') {
+        // Only results found in the text of the entries are highlighted
+        if (searchMode === 'index' || (searchMode === 'combined' && item.match_kind !== 'full_text')) {
           onIndexesSelected(indexGroups, "");
         } else {
           onIndexesSelected(indexGroups, searchTerm);
@@ -162,10 +164,21 @@ export const LookupView = forwardRef<LookupViewRef, LookupViewProps>(({
             </IonText>
           </div>
         ) : (
-          <EntryList
-            ref={entryListRef}
-            onItemSelect={handleItemSelect}
-          />
+          <div style={{ height: '100%', display: 'flex', flexDirection: 'column' }}>
+            {fulltextFailed && (
+              <div style={{ padding: '4px 12px' }}>
+                <IonText color="warning">
+                  <small>{t('Full-text search failed, only headwords are listed')}</small>
+                </IonText>
+              </div>
+            )}
+            <div style={{ flex: 1, minHeight: 0 }}>
+              <EntryList
+                ref={entryListRef}
+                onItemSelect={handleItemSelect}
+              />
+            </div>
+          </div>
         )}
       </div>
 
@@ -189,6 +202,11 @@ export const LookupView = forwardRef<LookupViewRef, LookupViewProps>(({
               {t('Full-text Search ({{count}})', { count: fts_count(currentProfile) })}
             </IonLabel>
           </IonSegmentButton>
+          <IonSegmentButton value="combined">
+            <IonLabel>
+              {t('Combined')}
+            </IonLabel>
+          </IonSegmentButton>
         </IonSegment>
     </div>
   );
@@ -8,18 +8,23 @@ import {
   IonButton,
   IonButtons,
   IonIcon,
+  IonItem,
+  IonSelect,
+  IonSelectOption,
 } from '@ionic/react';
 import { arrowBackOutline } from 'ionicons/icons';
 import { useTranslation } from 'react-i18next';
 
-import { MdxProfile } from '../../../types';
+import { KeyNormalization, MdxProfile } from '../../../types';
 import { ProfileListView } from '../ProfileListView';
+import { useLibraryStore } from '../../../store/useLibraryStore';
 
 /**
  * GroupEditView Component
  * 
  * Displays the edit view for a dictionary group.
- * Allows managing (enabling/disabling, reordering) profiles within a group.
+ * Allows managing (enabling/disabling, reordering) profiles within a group,
+ * and choosing how headwords of the dictionaries are merged in search results.
  */
 
 export interface GroupEditViewProps {
@@ -34,6 +39,7 @@ export const GroupEditView: React.FC<GroupEditViewProps> = ({
   onSelectProfile,
 }) => {
   const { t } = useTranslation();
+  const setKeyNormalization = useLibraryStore((state) => state.setKeyNormalization);
   
   return (
     <IonPage>
@@ -51,6 +57,19 @@ export const GroupEditView: React.FC<GroupEditViewProps> = ({
       
       <IonContent>
       <div style={{ padding: '16px' }}>          
+        <IonItem lines="full">
+          <IonSelect
+            label={t('Merge headwords')}
+            value={group.options.keyNormalization ?? 'caseFold'}
+            onIonChange={(e) => setKeyNormalization(group.profileId, e.detail.value as KeyNormalization)}
+            interface="popover"
+          >
+            <IonSelectOption value="strict">{t('Only identical headwords')}</IonSelectOption>
+            <IonSelectOption value="caseFold">{t('Ignore case')}</IonSelectOption>
+            <IonSelectOption value="diacriticFold">{t('Ignore case and accents')}</IonSelectOption>
+            <IonSelectOption value="punctuationInsensitive">{t('Ignore case, accents and punctuation')}</IonSelectOption>
+          </IonSelect>
+        </IonItem>
         <div style={{ minHeight: 0 }}>
           <ProfileListView
             parentGroupId={group.profileId}
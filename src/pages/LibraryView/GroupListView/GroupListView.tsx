@@ -12,6 +12,7 @@ import { GroupListItem } from './GroupListItem';
 import { confirmDialog } from '../../../components/ConfirmDialog';
 import { useLibraryStore } from '../../../store/useLibraryStore';
 import { useSearchStore } from '../../../store/useSearchStore';
+import { getErrorMessage } from '../../../api/errors';
 
 /**
  * GroupListView - Combined Component
@@ -54,7 +55,7 @@ export const GroupListView: React.FC<GroupListViewProps> = ({
       await renameGroup(profileId, newName);
     } catch (err) {
       console.log('Failed to rename group:', err);
-      onError?.(t('Failed to rename dictionary group') + (err as Error).message);
+      onError?.(t('Failed to rename dictionary group') + getErrorMessage(err));
     }
   }, [onError, t, renameGroup]);
 
@@ -78,7 +79,7 @@ export const GroupListView: React.FC<GroupListViewProps> = ({
         await deleteGroup(profile.profileId);
       } catch (err) {
         console.error('Failed to delete group:', err);
-        onError?.(t('Failed to delete dictionary group') + (err as Error).message);
+        onError?.(t('Failed to delete dictionary group') + getErrorMessage(err));
       }
     }
   }, [onError, t, deleteGroup]);
@@ -24,6 +24,7 @@ import { ProfileListView } from './ProfileListView';
 import { GroupListView } from './GroupListView';
 import { GroupEditView } from './GroupEditView';
 import { useLibraryStore } from '../../store';
+import { getErrorMessage } from '../../api/errors';
 
 /**
  * LibraryView - Combined Component
@@ -102,7 +103,7 @@ export const LibraryView: React.FC<LibraryViewProps> = ({
       }
     } catch (err) {
       console.error('Failed to get group:', err);
-      setErrorWithTimeout(t('Failed to get dictionary group') + (err as Error).message);
+      setErrorWithTimeout(t('Failed to get dictionary group') + getErrorMessage(err));
     }
   }, [getGroup, setErrorWithTimeout, setSelectedGroup]);
   
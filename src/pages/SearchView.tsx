@@ -6,11 +6,12 @@ import {
 import { useTranslation } from 'react-i18next';
 import { Allotment } from 'allotment';
 import 'allotment/dist/style.css';
-import { MdxGroupIndex, TargetInfo } from '../types';
+import { MdxGroupIndex, Suggestion, TargetInfo } from '../types';
 import * as searchAPI from '../api/search';
 import { LookupView, LookupViewRef } from '../features/LookupView';
 import { ContentView, ContentViewRef } from '../features/ContentView';
 import { parseMdxNavigationUrl, INVALID_PROFILE_ID, INVALID_ENTRY_NO } from '../utils/mdxUrlParser';
+import { displayKey } from '../utils/displayUtils';
 import { useHistoryStore, useSearchStore, useSettingsStore } from '../store';
 
 /**
@@ -58,11 +59,15 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
   
   // Error state
   const [navigationError, setNavigationError] = useState<string>('');
+  // Spelling suggestions shown with the error when a headword is not found
+  const [suggestions, setSuggestions] = useState<Suggestion[]>([]);
+  // Stem shown instead of an inflected form that is not a headword
+  const [stem, setStem] = useState<string>('');
   
   const addToHistoryStore = useCallback((indexes: MdxGroupIndex[], addToHistory: boolean = true) => {
     if (addToHistory && currentProfile && indexes.length > 0) {
       addToHistoryAction(
-        indexes[0].indexes[0].key,
+        displayKey(indexes[0].indexes[0]),
         indexes,
         currentProfile.profileId,
         currentProfile.title
@@ -77,6 +82,8 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
       //Need to force perform search to update the current index
       //The current index is updated in the next update cycle in LookupView.tsx, but we need to force it to update now.
       await performSearch(searchTerm);
+      setSuggestions([]);
+      setStem('');
       const currentIndex = useSearchStore.getState().currentIndex;
       if (currentIndex < 0) {
         setMdxGroupIndexes([]);
@@ -84,6 +91,28 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
       }
       const findResult = await searchAPI.getGroupIndexes(currentIndex);
 
+      // The incremental search lands on the nearest key, offer suggestions instead of an unrelated word
+      const word = searchTerm.trim().toLowerCase();
+      const isHeadwordSearch = useSearchStore.getState().searchMode === 'index' && !searchAPI.parsePatternQuery(searchTerm);
+      if (isHeadwordSearch && !findResult.some(group => group.primary_key.toLowerCase() === word)) {
+        // An inflected form ("mice") is found by its stem
+        const stemResult = await searchAPI.findIndex(searchTerm.trim()).catch(() => ({ results: [] as MdxGroupIndex[], stem: undefined }));
+        const stemGroups = stemResult.results.filter(group => group.indexes && group.indexes.length > 0);
+        if (stemGroups.length > 0) {
+          setNavigationError('');
+          setStem(stemResult.stem || '');
+          setMdxGroupIndexes(stemGroups);
+          addToHistoryStore(stemGroups, addToHistory);
+          return;
+        }
+        const found = await searchAPI.suggest(searchTerm.trim()).catch(() => []);
+        if (found.length > 0) {
+          setSuggestions(found);
+          setNavigationError(t('Keyword not found: {keyword}', { keyword: searchTerm }));
+          return;
+        }
+      }
+
       if (Array.isArray(findResult) && findResult.length > 0) {
         const validGroups = findResult.filter(group => group.indexes && group.indexes.length > 0);
         if (validGroups.length > 0) {
@@ -117,6 +146,7 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
       return;
     }
     setNavigationError('');
+    setStem('');
     setMdxGroupIndexes(mdxGroupIndexes);    
     addToHistoryStore(mdxGroupIndexes, addToHistory);
     
@@ -128,6 +158,7 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
   // Handle indexes selection
   const handleIndexesSelect = useCallback((indexes: MdxGroupIndex[], highlight?: string) => {
     setNavigationError('');
+    setStem('');
     setMdxGroupIndexes(indexes);
     setHighlight(highlight || '');
     addToHistoryStore(indexes, true);
@@ -142,13 +173,14 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
     }
     
     try {
-      const findResult = await searchAPI.findIndex(parsed.key);
+      const { results: findResult, stem: foundStem } = await searchAPI.findIndex(parsed.key);
       
       if (Array.isArray(findResult) && findResult.length > 0) {
         const validGroups = findResult.filter(group => group.indexes && group.indexes.length > 0);
         
         if (validGroups.length > 0) {
           setNavigationError('');
+          setStem(foundStem || '');
           setMdxGroupIndexes(validGroups);
           addToHistoryStore(validGroups, true);          
           
@@ -316,6 +348,15 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
                 overflow: 'hidden'
               }}
             >
+              {/* Stem looked up instead of an inflected form */}
+              {stem && !navigationError && (
+                <div style={{ padding: '8px 16px 0' }}>
+                  <IonText color="medium">
+                    {t('Showing results for {{stem}}', { stem })}
+                  </IonText>
+                </div>
+              )}
+
               {/* Navigation error display */}
               {navigationError && (
                 <div style={{ padding: '16px' }}>
@@ -331,9 +372,30 @@ export const SearchView = forwardRef<SearchViewRef, SearchViewProps>(({
                   }}>
                     <IonText color="warning">
                       <strong>{navigationError}</strong>
+                      {suggestions.length > 0 && (
+                        <div style={{ marginTop: '8px' }}>
+                          {t('Did you mean:')}{' '}
+                          {suggestions.map((suggestion) => (
+                            <a
+                              key={suggestion.key}
+                              href="#"
+                              style={{ marginRight: '12px' }}
+                              onClick={(e) => {
+                                e.preventDefault();
+                                displayByKey(suggestion.key);
+                              }}
+                            >
+                              {suggestion.key}
+                            </a>
+                          ))}
+                        </div>
+                      )}
                     </IonText>
                     <button
-                      onClick={() => setNavigationError('')}
+                      onClick={() => {
+                        setNavigationError('');
+                        setSuggestions([]);
+                      }}
                       style={{
                         background: 'none',
                         border: 'none',
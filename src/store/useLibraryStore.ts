@@ -15,9 +15,10 @@
 import { create } from 'zustand';
 import { devtools } from 'zustand/middleware';
 import { immer } from 'zustand/middleware/immer';
-import { MdxProfile } from '../types';
+import { KeyNormalization, MdxProfile } from '../types';
 import * as libraryAPI from '../api/library';
 import i18n from '../i18n/i18n';
+import { getErrorMessage } from '../api/errors';
 
 interface LibraryState {
   // Current state
@@ -35,6 +36,7 @@ interface LibraryState {
   createGroup: (groupName: string) => Promise<number>;
   deleteGroup: (groupId: number) => Promise<void>;
   renameGroup: (groupId: number, newName: string) => Promise<void>;
+  setKeyNormalization: (groupId: number, keyNormalization: KeyNormalization) => Promise<void>;
   updateProfileDisabledStatus: (
     parentGroupId: number,
     profileId: number,
@@ -102,7 +104,7 @@ export const useLibraryStore = create<LibraryState>()(
             // Translate the error message
             const errorMessage = i18n.t(errorMessageKey);
             // Log the error
-            console.error(errorMessage+': '+(error as Error).message || (error as Error).toString());
+            console.error(errorMessage + ': ' + getErrorMessage(error));
             set({ error: errorMessage });
             return undefined;
           } finally {
@@ -175,6 +177,15 @@ export const useLibraryStore = create<LibraryState>()(
           'Failed to rename dictionary group',
         ),
 
+        // Set how the group merges headwords
+        setKeyNormalization: withAsyncHandler(
+          async (groupId: number, keyNormalization: KeyNormalization) => {
+            await libraryAPI.setKeyNormalization(groupId, null, keyNormalization);
+          },
+          true,
+          'Failed to update dictionary group',
+        ),
+
         // Update profile disabled status
         updateProfileDisabledStatus: withAsyncHandler(
           async (
@@ -23,6 +23,7 @@ import { MdxProfile, SearchMode, SearchResultEntry } from '../types';
 import * as searchAPI from '../api/search';
 import { libraryAPI } from '../api';
 import { withAsyncHandler } from '../utils/storeUtils';
+import { getErrorMessage, hasErrorCode, isCommandError } from '../api/errors';
 
 const PAGE_SIZE = 50;
 const MAX_CACHED_PAGES = 20; // Cache up to 20 pages (1000 items)
@@ -104,7 +105,11 @@ interface SearchState {
   searchTerm: string;
   searchMode: SearchMode;
   totalCount: number;
+  // The key list of a group continues past totalCount, it grows while it is scrolled
+  hasMore: boolean;
   currentIndex: number;  
+  // The full-text fallback of a combined search failed, only headwords are listed
+  fulltextFailed: boolean;
   // Force re-render trigger - increment this to force component updates
   entryCacheVersion: number;
     
@@ -146,7 +151,9 @@ export const useSearchStore = create<SearchState>()(
         searchMode: 'index',
         currentProfile: null,
         totalCount: 0,
+        hasMore: false,
         currentIndex: -1,
+        fulltextFailed: false,
         entryCacheVersion: 0,
         loading: false,
         error: null,
@@ -174,7 +181,9 @@ export const useSearchStore = create<SearchState>()(
           set({
             searchTerm: '',
             totalCount: 0,
+            hasMore: false,
             currentIndex: -1,
+            fulltextFailed: false,
             error: null,
             loading: false,
           });
@@ -189,10 +198,31 @@ export const useSearchStore = create<SearchState>()(
             }
 
             const mode = get().searchMode;
+            const pattern = searchAPI.parsePatternQuery(query);
             const searchResult = mode === 'fulltext'
-              ? await searchAPI.fulltextSearch(query)
-              : await searchAPI.searchIncremental(query);
+              ? await searchAPI.fulltextSearch(query).catch((error) => {
+                  // A syntax error of the query is shown with its position instead of a generic message
+                  if (!hasErrorCode(error, 'invalid_query')) {
+                    throw error;
+                  }
+                  const details = isCommandError(error) ? error.details : undefined;
+                  set({ error: details ? `${getErrorMessage(error)}: ${details}` : getErrorMessage(error) });
+                  return null;
+                })
+              : mode === 'combined'
+                ? await searchAPI.combinedSearch(query)
+                : pattern
+                  ? await searchAPI.patternSearch(pattern.pattern, pattern.regex)
+                  // A pinyin query lists the Chinese headwords having that reading when no headword starts with it
+                  : searchAPI.isPinyinQuery(query)
+                    ? await searchAPI.pinyinSearch(query)
+                    : await searchAPI.searchIncremental(query);
             
+            if (!searchResult) {
+              return;
+            }
+            const fulltextFailed = 'fulltext_failed' in searchResult && searchResult.fulltext_failed === true;
+
             // Handle no results found (start_entry_no === -1 indicates not found)
             if (searchResult.start_entry_no === -1) {
               // Clear old cache
@@ -202,7 +232,9 @@ export const useSearchStore = create<SearchState>()(
                 entryCacheVersion: get().entryCacheVersion + 1,
                 searchTerm: query,
                 totalCount: 0,
+                hasMore: false,
                 currentIndex: -1,
+                fulltextFailed,
               });
               return;
             }
@@ -217,7 +249,9 @@ export const useSearchStore = create<SearchState>()(
             set({
               searchTerm: query,
               totalCount: searchResult.total_count,
+              hasMore: searchResult.has_more ?? false,
               currentIndex: searchResult.start_entry_no,
+              fulltextFailed,
             });
           },
           'Failed to perform search'
@@ -241,6 +275,13 @@ export const useSearchStore = create<SearchState>()(
           // Mark pages as loading and load them in parallel
           markPagesAsLoading(pagesToLoad);
           await loadPagesInParallel(pagesToLoad, set);
+
+          // Loading past the end continues the key list of a group
+          if (get().hasMore) {
+            // The count and whether it is partial are read together, the merge may end in between
+            const { total_count: totalCount, has_more: hasMore } = await searchAPI.getEntryCount();
+            set({ totalCount, hasMore });
+          }
         },
 
         // Get item by index
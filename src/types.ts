@@ -47,22 +47,56 @@ export interface MdxProfile {
 
 export interface MdxOptions {
   fontFilePath: string;
+  keyNormalization?: KeyNormalization;  // How headwords are merged in search results
+  collationLocale?: string;  // BCP 47 locale ordering the key list of a group, root collation when empty
+  ftsWeight?: number;  // Multiplies the full-text scores of a library of a group, 1.0 when not set
+  sourceLanguage?: string;  // BCP 47 language of the headwords, detected from the dictionary when empty
+  targetLanguage?: string;  // BCP 47 language of the definitions, detected from the dictionary when empty
 }
 
+// language::Script
+export type Script = 'latin' | 'greek' | 'cyrillic' | 'armenian' | 'georgian' | 'hebrew' | 'arabic'
+  | 'devanagari' | 'thai' | 'hangul' | 'kana' | 'han';
+
+// language::Languages, the languages routing the lookups of a group to its libraries
+// Empty languages and headword scripts are unknown, such a library is searched for every query
+export interface Languages {
+  sourceLanguage: string;
+  targetLanguage: string;
+  headwordScripts: Script[];
+}
+
+// key_normalization::KeyNormalization
+export type KeyNormalization = 'strict' | 'caseFold' | 'diacriticFold' | 'punctuationInsensitive';
+
 // Library view types
 export type LibraryViewMode = 'databases' | 'groups';
 
 // Search result types
+// Plain text of an entry around the matched terms of a full-text query
+// matches are [start, end) offsets in code points (not UTF-16 units) of text
+export interface Snippet {
+  text: string;
+  matches: [number, number][];
+}
+
 export interface SearchResultEntry {
   keyword: string;
   entry_count: number;
+  score?: number;  // Relevance of a full-text result, the best hit of a dictionary scores its weight (1.0 by default)
+  snippet?: Snippet;  // Text of the first entry around the matched terms of a full-text result
+  match_kind?: MatchKind;  // How the result of a combined search matched
 }
 
+// Corresponds to api_types::MatchKind
+export type MatchKind = 'nearest' | 'full_text';
+
 // MDX Index types - corresponding to Rust structs
 export interface MdxIndex {
   profile_id: number;
   entry_no: number;
   key: string;
+  resolved_key?: string;   // target key when the entry is an @@@LINK= redirect
 }
 
 // Corresponds to mdx_db_groups::MdxGroupIndex
@@ -72,8 +106,15 @@ export interface MdxGroupIndex {
   indexes: MdxIndex[];
 }
 
-// Search mode enum
-export type SearchMode = 'index' | 'fulltext';
+// Corresponds to api_types::Suggestion, a lower distance is a closer match
+export interface Suggestion {
+  key: string;
+  distance: number;
+  profile_ids: number[];
+}
+
+// Search mode enum, `combined` is an index search that falls back to full-text search
+export type SearchMode = 'index' | 'fulltext' | 'combined';
 
 // Sort order for favorites
 export type FavoriteSortBy = 'name' | 'time';
@@ -1,3 +1,5 @@
+import { MdxIndex } from '../types';
+
   // Format timestamp to readable date
   export function formatDate(timestamp: number) {
     const date = new Date(timestamp);
@@ -10,3 +12,8 @@
       return date.toLocaleDateString('zh-CN', { month: 'short', day: 'numeric' });
     }
   };
+
+  // Key shown for an entry, "colour → color" for a redirect entry
+  export function displayKey(index: MdxIndex) {
+    return index.resolved_key ? `${index.key} \u2192 ${index.resolved_key}` : index.key;
+  };
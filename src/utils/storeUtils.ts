@@ -12,6 +12,7 @@
 
 import { MdxProfile } from '../types';
 import i18n from '../i18n/i18n';
+import { getErrorMessage, isCommandError } from '../api/errors';
 
 /**
  * Creates an async handler wrapper that manages loading and error states
@@ -53,7 +54,7 @@ export function withAsyncHandler<T extends any[], R>(
       // Translate the error message
       const errorMessage = i18n.t(errorMessageKey);
       // Log the error
-      console.error(errorMessage + ': ' + ((error as Error).message || (error as Error).toString()));
+      console.error(errorMessage + ': ' + getErrorMessage(error), isCommandError(error) ? error.details : '');
       set({ error: errorMessage, loading: false });
       return undefined;
     } finally {